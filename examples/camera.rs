@@ -6,7 +6,7 @@ extern crate "nalgebra" as na;
 use glfw::{Key, Action, WindowEvent};
 use na::Pnt3;
 use kiss3d::window::Window;
-use kiss3d::camera::{ArcBall, FirstPerson};
+use kiss3d::camera::{ArcBall, FirstPerson, Projection, CameraBindings};
 use kiss3d::light::Light;
 
 fn main() {
@@ -15,6 +15,8 @@ fn main() {
     let mut first_person = FirstPerson::new(eye, at);
     let mut arc_ball     = ArcBall::new(eye, at);
     let mut use_arc_ball = true;
+    let presets          = [ CameraBindings::new(), CameraBindings::blender(), CameraBindings::maya() ];
+    let mut preset       = 0u;
 
     let mut window = Window::new("Kiss3d: camera");
     window.set_light(Light::StickToCamera);
@@ -34,6 +36,21 @@ fn main() {
                     else if key == Key::Num2 {
                         use_arc_ball = false
                     }
+                    else if key == Key::Num3 {
+                        // switch the arc-ball between perspective and orthographic.
+                        let mode = match arc_ball.projection_mode() {
+                            Projection::Perspective  => Projection::Orthographic,
+                            Projection::Orthographic => Projection::Perspective
+                        };
+
+                        arc_ball.set_projection_mode(mode)
+                    }
+                    else if key == Key::Num4 {
+                        // cycle through the default, Blender and Maya bindings.
+                        preset = (preset + 1) % presets.len();
+                        arc_ball.set_bindings(presets[preset]);
+                        first_person.set_bindings(presets[preset]);
+                    }
                 }
                 _ => { }
             }
@@ -0,0 +1,76 @@
+extern crate glfw;
+extern crate kiss3d;
+extern crate "nalgebra" as na;
+
+use glfw::{Key, Action, WindowEvent};
+use na::Pnt3;
+use kiss3d::window::Window;
+use kiss3d::camera::{Camera, ArcBall, CameraPath, PathPlayer};
+use kiss3d::light::Light;
+
+fn main() {
+    let file          = Path::new("camera_path.txt");
+    let mut window    = Window::new("Kiss3d: camera path");
+    let mut arc_ball  = ArcBall::new(Pnt3::new(0.0f32, 2.0, 5.0), na::orig());
+    let mut path      = CameraPath::new();
+    let mut player    = None::<PathPlayer>;
+    let mut recording = false;
+
+    let mut c = window.add_cube(1.0, 1.0, 1.0);
+    c.set_color(1.0, 0.0, 0.0);
+
+    window.set_light(Light::StickToCamera);
+
+    println!("Press R to start or stop recording, and P to replay the last recording.");
+
+    while !window.should_close() {
+        for event in window.events().iter() {
+            match event.value {
+                WindowEvent::Key(Key::R, _, Action::Release, _) => {
+                    if recording {
+                        // save the recorded path.
+                        match path.save(&file) {
+                            Ok(_)  => println!("Camera path saved to {}.", file.display()),
+                            Err(e) => println!("Failed to save the camera path: {}", e)
+                        }
+                    }
+                    else {
+                        path.clear();
+                        player = None;
+                    }
+
+                    recording = !recording;
+                },
+                WindowEvent::Key(Key::P, _, Action::Release, _) => {
+                    // replay the saved path.
+                    player = match CameraPath::from_file(&file) {
+                        Ok(ref p) if p.len() != 0 => Some(PathPlayer::new(p.clone())),
+                        Ok(_)  => None,
+                        Err(e) => { println!("Failed to load the camera path: {}", e); None }
+                    };
+                },
+                _ => { }
+            }
+        }
+
+        let finished = match player {
+            Some(ref mut p) => {
+                window.render_with_camera(p);
+                p.is_finished()
+            },
+            None => {
+                window.render_with_camera(&mut arc_ball);
+
+                if recording {
+                    path.record(arc_ball.eye(), arc_ball.at(), arc_ball.fov());
+                }
+
+                false
+            }
+        };
+
+        if finished {
+            player = None;
+        }
+    }
+}
@@ -10,7 +10,7 @@ use na::{Pnt3, Vec3, Mat3, Mat4, Iso3};
 use kiss3d::window::Window;
 use kiss3d::scene::ObjectData;
 use kiss3d::camera::Camera;
-use kiss3d::light::Light;
+use kiss3d::light::LightSource;
 use kiss3d::resource::{Shader, ShaderAttribute, ShaderUniform, Material, Mesh};
 
 fn main() {
@@ -58,7 +58,7 @@ impl Material for NormalMaterial {
               transform: &Iso3<f32>,
               scale:     &Vec3<f32>,
               camera:    &mut Camera,
-              _:         &Light,
+              _:         &[LightSource],
               _:         &ObjectData,
               mesh:      &mut Mesh) {
         self.shader.use_program();
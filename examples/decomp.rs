@@ -53,9 +53,9 @@ fn main() {
     let obj_path = Path::new(path.to_string());
     // let obj_path = Path::new("/home/tortue/Downloads/models/ATST_medium.obj");
     let mtl_path = Path::new("none");
-    let teapot   = obj::parse_file(&obj_path, &mtl_path, "none").unwrap();
+    let (teapot, _) = obj::parse_file(&obj_path, &mtl_path, "none").unwrap();
 
-    let mut m = window.add_obj(&obj_path, &mtl_path, scale);
+    let (mut m, _) = window.add_obj(&obj_path, &mtl_path, scale).unwrap();
     m.set_surface_rendering_activation(false);
     // m.set_lines_width(1.0);
     let coords  = m.data().object().expect("here").mesh().borrow().coords().clone();
@@ -0,0 +1,52 @@
+extern crate glfw;
+extern crate kiss3d;
+extern crate "nalgebra" as na;
+
+use glfw::{Key, Action, WindowEvent};
+use na::{Pnt3, Vec3};
+use kiss3d::window::Window;
+use kiss3d::camera::ArcBall;
+use kiss3d::light::Light;
+
+fn main() {
+    let mut window   = Window::new("Kiss3d: framing");
+    let mut arc_ball = ArcBall::new(Pnt3::new(0.0f32, 5.0, 10.0), na::orig());
+
+    // Objects far from the origin.
+    let mut c = window.add_cube(1.0, 1.0, 1.0);
+    let mut s = window.add_sphere(3.0);
+    let mut k = window.add_cone(1.0, 4.0);
+
+    c.set_local_translation(Vec3::new(100.0, 0.0, 0.0));
+    s.set_local_translation(Vec3::new(110.0, 0.0, 20.0));
+    k.set_local_translation(Vec3::new(90.0, 10.0, 30.0));
+
+    c.set_color(1.0, 0.0, 0.0);
+    s.set_color(0.0, 1.0, 0.0);
+    k.set_color(0.0, 0.0, 1.0);
+
+    window.set_light(Light::StickToCamera);
+
+    while !window.should_close() {
+        for event in window.events().iter() {
+            match event.value {
+                WindowEvent::Key(key, _, Action::Release, _) => {
+                    match key {
+                        // frame each object.
+                        Key::Num1  => { let _ = arc_ball.frame_node(&c); },
+                        Key::Num2  => { let _ = arc_ball.frame_node(&s); },
+                        Key::Num3  => { let _ = arc_ball.frame_node(&k); },
+                        // frame the whole scene.
+                        Key::F     => { let _ = arc_ball.frame_node(window.scene()); },
+                        // fly back to the initial point of view.
+                        Key::Space => arc_ball.animate_to(Pnt3::new(0.0, 5.0, 10.0), na::orig(), 2.0),
+                        _          => { }
+                    }
+                }
+                _ => { }
+            }
+        }
+
+        window.render_with_camera(&mut arc_ball);
+    }
+}
@@ -0,0 +1,52 @@
+extern crate "nalgebra" as na;
+extern crate kiss3d;
+
+use std::os;
+
+// Renders a frame without any display server and saves it to `headless.png`, e.g., to compare
+// it against a reference image on a continuous integration server. The process exits with an
+// error if the cube is not visible.
+//
+// Run with `cargo run --features headless --bin headless`.
+#[cfg(feature = "headless")]
+fn main() {
+    use na::{Pnt3, Vec3};
+    use kiss3d::window::Window;
+    use kiss3d::light::Light;
+    use kiss3d::text::Font;
+
+    let mut window = Window::new_headless(640, 480);
+    let font       = Font::new(&Path::new("media/font/Inconsolata.otf"), 40);
+
+    let mut c = window.add_cube(1.0, 1.0, 1.0);
+
+    c.set_color(1.0, 0.0, 0.0);
+    c.prepend_to_local_rotation(&Vec3::new(0.4f32, 0.6, 0.0));
+
+    window.set_background_color(0.2, 0.2, 0.2);
+    window.set_light(Light::StickToCamera);
+    window.draw_text("Rendered without any window.", &na::orig(), &font, &Pnt3::new(1.0, 1.0, 1.0));
+    window.render();
+
+    let snapshot  = window.snap_image();
+    let (r, g, b) = snapshot.pixel(320, 240);
+
+    match snapshot.save(&Path::new("headless.png")) {
+        Ok(_)  => println!("Frame saved to headless.png"),
+        Err(e) => {
+            println!("Unable to save the frame: {}", e);
+            os::set_exit_status(1);
+        }
+    }
+
+    if r <= g || r <= b {
+        println!("The cube is not visible at the center of the frame.");
+        os::set_exit_status(1);
+    }
+}
+
+#[cfg(not(feature = "headless"))]
+fn main() {
+    println!("This example requires the `headless` feature: cargo run --features headless --bin headless");
+    os::set_exit_status(1);
+}
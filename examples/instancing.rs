@@ -0,0 +1,38 @@
+extern crate kiss3d;
+extern crate "nalgebra" as na;
+
+use std::num::FloatMath;
+use na::{Pnt3, Vec3, Iso3};
+use kiss3d::window::Window;
+use kiss3d::resource::MeshManager;
+
+fn instances(t: f32) -> Vec<(Iso3<f32>, Vec3<f32>, Pnt3<f32>)> {
+    let mut res = Vec::new();
+
+    for i in range(0u, 100) {
+        for j in range(0u, 100) {
+            let x = i as f32 * 0.2 - 10.0;
+            let z = j as f32 * 0.2 - 10.0;
+            let y = (x * 0.5 + t).sin() * (z * 0.5 + t).cos();
+            let s = 0.1 + 0.05 * y;
+
+            res.push((Iso3::new(Vec3::new(x, y, z), na::zero()),
+                      Vec3::new(s, s, s),
+                      Pnt3::new(i as f32 / 100.0, 0.5 + y * 0.5, j as f32 / 100.0)));
+        }
+    }
+
+    res
+}
+
+fn main() {
+    let mut window = Window::new("Kiss3d: instancing");
+    let sphere     = MeshManager::get_global_manager(|mm| mm.get("sphere")).unwrap();
+    let mut node   = window.add_instanced(sphere, instances(0.0));
+    let mut t      = 0.0f32;
+
+    while window.render() {
+        t = t + 0.02;
+        node.set_instances(instances(t));
+    }
+}
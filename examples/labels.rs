@@ -0,0 +1,38 @@
+extern crate kiss3d;
+extern crate "nalgebra" as na;
+
+use na::{Pnt3, Vec2, Vec3};
+use kiss3d::window::Window;
+use kiss3d::light::Light;
+use kiss3d::text::{Font, Label};
+
+fn main() {
+    let mut window = Window::new("Kiss3d: labels");
+    let font       = Font::new(&Path::new("media/font/Inconsolata.otf"), 30);
+
+    let mut c = window.add_cube(1.0, 1.0, 1.0);
+    let mut s = window.add_sphere(0.5);
+
+    c.set_color(1.0, 0.0, 0.0);
+    s.set_color(0.0, 1.0, 0.0);
+    s.set_local_translation(Vec3::new(2.0, 0.0, 0.0));
+
+    // persistent labels follow their node and are hidden with it.
+    let mut cube_label = Label::new("cube", &font, &Pnt3::new(1.0, 1.0, 1.0));
+    cube_label.offset  = Vec2::new(10.0, -10.0);
+    c.add_label(Pnt3::new(0.5, 0.5, 0.5), cube_label);
+
+    // this label is hidden by the objects in front of it.
+    let mut sphere_label    = Label::new("sphere\n(depth-tested)", &font, &Pnt3::new(1.0, 1.0, 0.0));
+    sphere_label.depth_test = true;
+    s.add_label(Pnt3::new(0.0, 0.5, 0.0), sphere_label);
+
+    window.set_light(Light::StickToCamera);
+
+    while window.render() {
+        c.prepend_to_local_rotation(&Vec3::new(0.0f32, 0.014, 0.0));
+
+        // labels drawn for one frame only.
+        window.draw_text_3d("origin", &na::orig(), &font, &Pnt3::new(0.0, 1.0, 1.0));
+    }
+}
@@ -0,0 +1,45 @@
+extern crate kiss3d;
+extern crate "nalgebra" as na;
+
+use std::f32;
+use na::{Pnt3, Vec3};
+use kiss3d::window::Window;
+use kiss3d::light::{Light, LightSource};
+
+fn main() {
+    let mut window = Window::new("Kiss3d: lights");
+
+    let mut c = window.add_cube(1.0, 1.0, 1.0);
+    let mut s = window.add_sphere(0.5);
+    let mut q = window.add_quad(10.0, 10.0, 100, 100);
+
+    c.append_translation(&Vec3::new(1.0, 0.0, 0.0));
+    s.append_translation(&Vec3::new(-1.0, 0.0, 0.0));
+    q.append_rotation(&Vec3::new(f32::consts::FRAC_PI_2, 0.0, 0.0));
+    q.append_translation(&Vec3::new(0.0, -0.5, 0.0));
+
+    // Key light.
+    let mut key = LightSource::new_spot(Light::Absolute(Pnt3::new(2.0, 4.0, 2.0)),
+                                        Vec3::new(-2.0, -4.0, -2.0),
+                                        0.5, 8.0);
+    key.set_color(1.0, 0.9, 0.8);
+    key.set_intensity(1.5);
+
+    // Fill light.
+    let mut fill = LightSource::new_point(Light::Absolute(Pnt3::new(-3.0, 1.0, 3.0)));
+    fill.set_color(0.4, 0.5, 1.0);
+    fill.set_attenuation(1.0, 0.1, 0.05);
+
+    // Rim light.
+    let mut rim = LightSource::new_directional(Vec3::new(0.0, -1.0, 1.0));
+    rim.set_intensity(0.3);
+
+    window.clear_lights();
+    window.add_light(key);
+    window.add_light(fill);
+    window.add_light(rim);
+
+    while window.render() {
+        c.append_rotation_wrt_center(&Vec3::new(0.0f32, 0.014, 0.0));
+    }
+}
@@ -12,13 +12,18 @@ fn main() {
     // Teapot
     let obj_path   = Path::new("media/teapot/teapot.obj");
     let mtl_path   = Path::new("media/teapot");
-    let mut teapot = window.add_obj(&obj_path, &mtl_path, Vec3::new(0.001, 0.001, 0.001));
+    let (mut teapot, warnings) = window.add_obj(&obj_path, &mtl_path, Vec3::new(0.001, 0.001, 0.001)).unwrap();
+
+    for w in warnings.iter() {
+        println!("Warning: {}", w);
+    }
+
     teapot.append_translation(&Vec3::new(0.0, -0.05, -0.2));
 
     // Rust logo
     let obj_path = Path::new("media/rust_logo/rust_logo.obj");
     let mtl_path = Path::new("media/rust_logo");
-    let mut rust = window.add_obj(&obj_path, &mtl_path, Vec3::new(0.05, 0.05, 0.05));
+    let (mut rust, _) = window.add_obj(&obj_path, &mtl_path, Vec3::new(0.05, 0.05, 0.05)).unwrap();
     rust.prepend_to_local_rotation(&Vec3::new(-1.0 * f32::consts::FRAC_PI_2, 0.0, 0.0));
     rust.set_color(0.0, 0.0, 1.0);
 
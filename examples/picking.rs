@@ -0,0 +1,42 @@
+extern crate glfw;
+extern crate kiss3d;
+extern crate "nalgebra" as na;
+
+use glfw::{Action, MouseButton, WindowEvent};
+use na::{Pnt2, Vec3};
+use kiss3d::window::Window;
+use kiss3d::light::Light;
+
+fn main() {
+    let mut window = Window::new("Kiss3d: picking (click on an object)");
+
+    let mut c = window.add_cube(1.0, 1.0, 1.0);
+    let mut s = window.add_sphere(0.5);
+
+    c.append_translation(&Vec3::new(1.0, 0.0, 0.0));
+    s.append_translation(&Vec3::new(-1.0, 0.0, 0.0));
+
+    window.set_light(Light::StickToCamera);
+
+    let mut cursor = Pnt2::new(0.0f32, 0.0);
+
+    while window.render() {
+        for event in window.events().iter() {
+            match event.value {
+                WindowEvent::CursorPos(x, y) => {
+                    cursor = Pnt2::new(x as f32, y as f32);
+                },
+                WindowEvent::MouseButton(MouseButton::Button1, Action::Press, _) => {
+                    match window.pick(&cursor) {
+                        Some(mut hit) => {
+                            println!("Hit triangle {} at {}.", hit.triangle, hit.point);
+                            hit.node.set_color(1.0, 0.0, 0.0);
+                        },
+                        None => println!("Nothing under the cursor.")
+                    }
+                },
+                _ => { }
+            }
+        }
+    }
+}
@@ -0,0 +1,35 @@
+extern crate kiss3d;
+extern crate ncollide;
+extern crate "nalgebra" as na;
+
+use na::{Pnt2, Pnt3, Vec2, Vec3};
+use ncollide::procedural;
+use kiss3d::window::Window;
+
+fn main() {
+    let mut window = Window::new("Kiss3d: planar");
+
+    // There is no 3D scene: let the mouse pan and zoom the 2D layer.
+    window.set_2d_input(true);
+
+    let mut rect = window.add_rectangle(200.0, 100.0);
+    rect.set_color(0.0, 0.5, 1.0);
+    rect.append_translation(&Vec3::new(-150.0, 0.0, 0.0));
+
+    let mut circle = window.add_circle(60.0);
+    circle.set_color(1.0, 0.5, 0.0);
+    circle.append_translation(&Vec3::new(150.0, 0.0, 0.0));
+
+    let triangle = vec!(Pnt2::new(0.0, 1.0), Pnt2::new(-1.0, -1.0), Pnt2::new(1.0, -1.0));
+    let mut polygon = window.add_convex_polygon(triangle, Vec2::new(50.0, 50.0));
+    polygon.set_color(0.2, 1.0, 0.2);
+    polygon.append_translation(&Vec3::new(0.0, 150.0, 1.0));
+
+    // Outline the circle.
+    let mut outline = window.add_planar_polyline(&procedural::circle(&130.0, 64), Pnt3::new(1.0, 1.0, 1.0), 1.0);
+    outline.append_translation(&Vec3::new(150.0, 0.0, 1.0));
+
+    while window.render() {
+        rect.append_rotation(&Vec3::new(0.0f32, 0.0, 0.01));
+    }
+}
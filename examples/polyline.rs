@@ -0,0 +1,36 @@
+extern crate kiss3d;
+extern crate "nalgebra" as na;
+
+use std::num::FloatMath;
+use na::{Pnt3, Vec3};
+use kiss3d::window::Window;
+use kiss3d::light::Light;
+
+fn main() {
+    let mut window = Window::new("Kiss3d: polyline");
+
+    let mut coords = Vec::new();
+    let mut colors = Vec::new();
+
+    // A spiral with 100000 segments, uploaded once.
+    for i in range(0u, 100001) {
+        let t = i as f32 / 100000.0;
+        let a = t * 200.0;
+
+        coords.push(Pnt3::new(a.cos() * (1.0 - t), t * 2.0 - 1.0, a.sin() * (1.0 - t)));
+        colors.push(Pnt3::new(t, 1.0 - t, 1.0));
+    }
+
+    let points = coords.iter().map(|p| *p * 1.2f32).collect();
+    let white  = Vec::from_elem(coords.len(), Pnt3::new(1.0, 1.0, 1.0));
+
+    let mut spiral = window.add_polyline(coords, colors, 2.0);
+    let mut cloud  = window.add_point_cloud(points, white, 1.0);
+
+    window.set_light(Light::StickToCamera);
+
+    while window.render() {
+        spiral.append_rotation(&Vec3::new(0.0f32, 0.014, 0.0));
+        cloud.append_rotation(&Vec3::new(0.0f32, -0.007, 0.0));
+    }
+}
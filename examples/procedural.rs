@@ -24,7 +24,7 @@ fn main() {
     let cube  = procedural::cuboid(&Vec3::new(0.7f32, 0.2, 0.4));
     let mut c = window.add_trimesh(cube, na::one());
     c.append_translation(&Vec3::new(1.0, 0.0, 0.0));
-    c.set_texture_from_file(&Path::new("media/kitten.png"), "kitten");
+    c.set_texture_from_file(&Path::new("media/kitten.png"), "kitten").unwrap();
 
     /*
      * A sphere.
@@ -149,7 +149,7 @@ fn main() {
     let banana = ParametricBananas::new();
     let mesh   = procedural::parametric_surface_uniform(&banana, 20, 20);
     let mut m  = window.add_trimesh(mesh, Vec3::new(0.5, 0.5, 0.5));
-    m.set_texture_from_file(&Path::new("media/banana.jpg"), "banana");
+    m.set_texture_from_file(&Path::new("media/banana.jpg"), "banana").unwrap();
     m.append_translation(&Vec3::new(-3.5, 0.0, 0.0));
     m.set_surface_rendering_activation(false);
     m.set_lines_width(2.0);
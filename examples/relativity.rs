@@ -16,7 +16,7 @@ use kiss3d::window::Window;
 use kiss3d::text::Font;
 use kiss3d::scene::ObjectData;
 use kiss3d::camera::{Camera, FirstPerson};
-use kiss3d::light::Light;
+use kiss3d::light::{Light, LightSource};
 use kiss3d::resource::{Shader, ShaderAttribute, ShaderUniform, Material, Mesh};
 
 fn main() {
@@ -34,7 +34,7 @@ fn main() {
 
     let mut c = window.add_quad(800.0, 800.0, 40, 40);
     c.set_material(material.clone());
-    c.set_texture_from_file(&Path::new("media/kitten.png"), "kitten");
+    c.set_texture_from_file(&Path::new("media/kitten.png"), "kitten").unwrap();
 
     let mut c = window.add_quad(800.0, 800.0, 40, 40);
     c.append_rotation(&(Vec3::x() * 90.0f32.to_radians()));
@@ -253,7 +253,7 @@ impl Material for RelativisticMaterial {
               transform: &Iso3<f32>, 
               scale:     &Vec3<f32>,
               camera:    &mut Camera,
-              light:     &Light,
+              lights:    &[LightSource],
               data:      &ObjectData,
               mesh:      &mut Mesh) {
         self.activate();
@@ -265,9 +265,10 @@ impl Material for RelativisticMaterial {
          */
         camera.upload(pass, &mut self.view);
 
-        let pos = match *light {
-            Light::Absolute(ref p) => p.clone(),
-            Light::StickToCamera   => camera.eye()
+        // This material supports only one light.
+        let pos = match lights.get(0).map(|l| l.position.clone()) {
+            Some(Light::Absolute(p)) => p,
+            _                        => camera.eye()
         };
 
         self.light.upload(&pos);
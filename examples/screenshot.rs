@@ -0,0 +1,34 @@
+extern crate glfw;
+extern crate kiss3d;
+extern crate "nalgebra" as na;
+
+use glfw::{Action, Key, WindowEvent};
+use na::Vec3;
+use kiss3d::window::Window;
+use kiss3d::light::Light;
+
+fn main() {
+    let mut window = Window::new("Kiss3d: screenshot (press S to save screenshot.png)");
+
+    let mut c = window.add_cube(1.0, 1.0, 1.0);
+
+    c.set_color(1.0, 0.0, 0.0);
+
+    window.set_light(Light::StickToCamera);
+
+    while window.render() {
+        c.prepend_to_local_rotation(&Vec3::new(0.0f32, 0.014, 0.0));
+
+        for event in window.events().iter() {
+            match event.value {
+                WindowEvent::Key(Key::S, _, Action::Release, _) => {
+                    match window.save_screenshot(&Path::new("screenshot.png")) {
+                        Ok(_)  => println!("Screenshot saved to screenshot.png"),
+                        Err(e) => println!("Unable to save the screenshot: {}", e)
+                    }
+                },
+                _ => { }
+            }
+        }
+    }
+}
@@ -0,0 +1,35 @@
+extern crate kiss3d;
+extern crate "nalgebra" as na;
+
+use std::f32;
+use na::{Pnt3, Vec3};
+use kiss3d::window::Window;
+use kiss3d::light::{Light, LightSource};
+
+fn main() {
+    let mut window = Window::new("Kiss3d: shadows");
+
+    let mut c = window.add_cube(1.0, 1.0, 1.0);
+    let mut s = window.add_sphere(0.5);
+    let mut q = window.add_quad(10.0, 10.0, 1, 1);
+
+    c.append_translation(&Vec3::new(1.0, 0.5, 0.0));
+    s.append_translation(&Vec3::new(-1.0, 0.5, 0.0));
+    q.append_rotation(&Vec3::new(f32::consts::FRAC_PI_2, 0.0, 0.0));
+
+    // The floor does not need to cast shadows.
+    q.set_cast_shadows(false);
+
+    window.clear_lights();
+    window.add_light(LightSource::new_spot(Light::Absolute(Pnt3::new(2.0, 6.0, 2.0)),
+                                           Vec3::new(-2.0, -6.0, -2.0),
+                                           0.6, 4.0));
+    window.add_light(LightSource::new_directional(Vec3::new(1.0, -1.0, 0.0)));
+
+    window.set_shadows(true);
+    window.shadow_map_mut().unwrap().set_region(na::orig(), 6.0);
+
+    while window.render() {
+        c.append_rotation_wrt_center(&Vec3::new(0.0f32, 0.014, 0.0));
+    }
+}
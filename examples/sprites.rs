@@ -0,0 +1,38 @@
+extern crate kiss3d;
+extern crate "nalgebra" as na;
+
+use std::rand;
+use na::Pnt3;
+use kiss3d::window::Window;
+use kiss3d::light::Light;
+use kiss3d::point_renderer::{PointSize, PointShape, PointSprite};
+
+fn main() {
+    let mut window = Window::new("Kiss3d: sprites");
+
+    window.set_light(Light::StickToCamera);
+
+    let points: Vec<Pnt3<f32>> = range(0u, 3000).map(|_| {
+        Pnt3::new(rand::random::<f32>() * 2.0 - 1.0, rand::random::<f32>() * 2.0 - 1.0, rand::random::<f32>() * 2.0 - 1.0)
+    }).collect();
+
+    let spheres = PointSprite::new(PointShape::Sphere);
+    let disks   = PointSprite::new(PointShape::Round);
+    let squares = PointSprite::new(PointShape::Square);
+
+    while window.render() {
+        // Sprites with the same style are batched: draw them together.
+        for p in points.slice_to(1000).iter() {
+            let color = Pnt3::new(p.x * 0.5 + 0.5, p.y * 0.5 + 0.5, p.z * 0.5 + 0.5);
+            window.draw_sprite(p, &color, PointSize::World(0.02 + 0.03 * color.x), &spheres);
+        }
+
+        for p in points.slice(1000, 2000).iter() {
+            window.draw_sprite(p, &Pnt3::new(1.0, 0.5, 0.0), PointSize::Pixels(6.0), &disks);
+        }
+
+        for p in points.slice_from(2000).iter() {
+            window.draw_sprite(p, &Pnt3::new(0.0, 0.5, 1.0), PointSize::Pixels(3.0), &squares);
+        }
+    }
+}
@@ -17,5 +17,8 @@ fn main() {
         let ascii = " !\"#$%&'`()*+,-_./0123456789:;<=>?@ABCDEFGHIJKLMNOPQRSTUVWXYZ[\\]^abcdefghijklmnopqrstuvwxyz{|}~";
 
         window.draw_text(ascii, &Pnt2::new(0.0, 120.0), &smallfont, &Pnt3::new(1.0, 1.0, 0.0));
+
+        // glyphs are rasterized on demand, so any character of the font can be displayed.
+        window.draw_text("Units: 25 µm, 90°, 1.5 Å. Kerning: AVAWAY.", &Pnt2::new(0.0, 240.0), &smallfont, &Pnt3::new(1.0, 0.5, 0.0));
     }
 }
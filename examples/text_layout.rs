@@ -0,0 +1,49 @@
+extern crate "nalgebra" as na;
+extern crate kiss3d;
+
+use na::{Pnt2, Pnt3, Vec3};
+use kiss3d::window::Window;
+use kiss3d::light::Light;
+use kiss3d::text::{Font, TextAlign, TextLayout};
+
+fn main() {
+    let mut window = Window::new("Kiss3d: text layout");
+    let mut c      = window.add_cube(1.0, 1.0, 1.0);
+    let font       = Font::new(&Path::new("media/font/Inconsolata.otf"), 40);
+
+    c.set_color(1.0, 0.0, 0.0);
+    window.set_light(Light::StickToCamera);
+
+    let paragraph = "Long texts are wrapped at spaces to fit the maximum width, and the lines are \
+                     aligned as requested.";
+
+    // A readable overlay over the scene.
+    let mut hud          = TextLayout::new();
+    hud.background       = Some(Pnt3::new(0.0, 0.0, 0.0));
+    hud.background_alpha = 0.7;
+
+    let mut centered      = hud;
+    centered.align        = TextAlign::Center;
+    centered.max_width    = Some(500.0);
+    centered.line_spacing = 1.2;
+
+    let mut right = TextLayout::new();
+    right.align   = TextAlign::Right;
+
+    while window.render() {
+        c.prepend_to_local_rotation(&Vec3::new(0.0f32, 0.014, 0.0));
+
+        let width = window.width();
+        let title = "Kiss3d";
+        let size  = font.measure(title);
+
+        window.draw_text_with_layout(title, &Pnt2::new(10.0, 10.0), &font, &Pnt3::new(1.0, 1.0, 1.0), &hud);
+
+        // Use the measured size to draw next to the title.
+        window.draw_text(format!("{} x {} pixels", size.x, size.y).as_slice(),
+                         &Pnt2::new(size.x + 30.0, 10.0), &font, &Pnt3::new(0.5, 0.5, 0.5));
+
+        window.draw_text_with_layout(paragraph, &Pnt2::new(width / 2.0, 200.0), &font, &Pnt3::new(1.0, 1.0, 0.0), &centered);
+        window.draw_text_with_layout("right\naligned", &Pnt2::new(width - 10.0, 10.0), &font, &Pnt3::new(0.0, 1.0, 1.0), &right);
+    }
+}
@@ -0,0 +1,38 @@
+extern crate "nalgebra" as na;
+extern crate kiss3d;
+
+use std::num::FloatMath;
+use na::{Pnt2, Pnt3, Vec2};
+use kiss3d::window::Window;
+use kiss3d::text::{Font, TextLayout};
+
+fn main() {
+    let mut window = Window::new("Kiss3d: text with signed distance fields");
+
+    // A single atlas is used for every size.
+    let font       = Font::new_sdf(&Path::new("media/font/Inconsolata.otf"), 48);
+    let mut t      = 0.0f32;
+
+    let mut outlined       = TextLayout::new();
+    outlined.outline       = Some(Pnt3::new(0.0, 0.0, 0.0));
+    outlined.outline_width = 2.0;
+    outlined.shadow        = Some(Pnt3::new(0.0, 0.0, 0.0));
+    outlined.shadow_offset = Vec2::new(4.0, 4.0);
+
+    while window.render() {
+        t = t + 0.02;
+
+        for (i, size) in [ 12.0f32, 24.0, 48.0, 96.0 ].iter().enumerate() {
+            let mut layout = TextLayout::new();
+            layout.size    = Some(*size);
+
+            window.draw_text_with_layout("Sharp at any size", &Pnt2::new(10.0, 10.0 + 20.0 * (i * i) as f32),
+                                         &font, &Pnt3::new(1.0, 1.0, 1.0), &layout);
+        }
+
+        outlined.size = Some(80.0 + 40.0 * t.sin());
+
+        window.draw_text_with_layout("Outline and shadow", &Pnt2::new(10.0, 300.0), &font,
+                                     &Pnt3::new(1.0, 0.8, 0.0), &outlined);
+    }
+}
@@ -10,7 +10,7 @@ fn main() {
     let mut c      = window.add_cube(1.0, 1.0, 1.0);
 
     c.set_color(1.0, 0.0, 0.0);
-    c.set_texture_from_file(&Path::new("media/kitten.png"), "kitten");
+    c.set_texture_from_file(&Path::new("media/kitten.png"), "kitten").unwrap();
 
     window.set_light(Light::StickToCamera);
 
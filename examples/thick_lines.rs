@@ -0,0 +1,57 @@
+extern crate kiss3d;
+extern crate "nalgebra" as na;
+
+use std::num::FloatMath;
+use na::Pnt3;
+use kiss3d::window::Window;
+use kiss3d::light::Light;
+use kiss3d::thick_line_renderer::{LineStyle, LineCap, LineJoin};
+
+fn main() {
+    let mut window = Window::new("Kiss3d: thick lines");
+
+    window.set_light(Light::StickToCamera);
+
+    let mut cube = window.add_cube(0.5, 0.5, 0.5);
+    cube.set_color(0.3, 0.3, 0.3);
+
+    // A retained, dashed sine wave.
+    let coords: Vec<Pnt3<f32>> = range(0u, 200).map(|i| {
+        let x = i as f32 / 50.0 - 2.0;
+        Pnt3::new(x, (x * 3.0).sin() * 0.3 + 0.8, 0.0)
+    }).collect();
+    let colors = coords.iter().map(|p| Pnt3::new(0.5 + p.x / 4.0, 0.5, 1.0)).collect();
+
+    let mut wave  = window.add_polyline(coords, colors, 1.0);
+    let mut style = LineStyle::new(4.0);
+    style.dash    = Some((0.1, 0.05));
+    wave.data_mut().polyline_mut().unwrap().set_style(Some(style));
+
+    let butt   = LineStyle { cap: LineCap::Butt, join: LineJoin::Miter, .. LineStyle::new(12.0) };
+    let square = LineStyle { cap: LineCap::Square, .. LineStyle::new(12.0) };
+    let round  = LineStyle::new(12.0);
+
+    let zigzag = [
+        Pnt3::new(-1.5f32, -0.8, 0.0),
+        Pnt3::new(-1.0, -0.4, 0.0),
+        Pnt3::new(-0.5, -0.8, 0.0),
+        Pnt3::new(0.0, -0.4, 0.0)
+    ];
+
+    while window.render() {
+        // Outline the cube edges with lines of constant width on the screen.
+        let h = 0.25f32;
+
+        for i in range(0u, 4) {
+            let (x, y) = match i { 0 => (h, h), 1 => (-h, h), 2 => (-h, -h), _ => (h, -h) };
+
+            window.draw_thick_line(&Pnt3::new(x, y, -h), &Pnt3::new(x, y, h), &Pnt3::new(1.0, 1.0, 0.0), &round);
+            window.draw_thick_line(&Pnt3::new(x, -h, y), &Pnt3::new(x, h, y), &Pnt3::new(1.0, 1.0, 0.0), &round);
+            window.draw_thick_line(&Pnt3::new(-h, x, y), &Pnt3::new(h, x, y), &Pnt3::new(1.0, 1.0, 0.0), &round);
+        }
+
+        window.draw_thick_polyline(zigzag.as_slice(), &Pnt3::new(1.0, 0.0, 0.0), &butt);
+        window.draw_thick_line(&Pnt3::new(0.5, -0.6, 0.0), &Pnt3::new(1.0, -0.6, 0.0), &Pnt3::new(0.0, 1.0, 0.0), &square);
+        window.draw_thick_line(&Pnt3::new(1.3, -0.6, 0.0), &Pnt3::new(1.8, -0.6, 0.0), &Pnt3::new(0.0, 0.0, 1.0), &round);
+    }
+}
@@ -0,0 +1,49 @@
+extern crate kiss3d;
+extern crate "nalgebra" as na;
+
+use std::rc::Rc;
+use std::cell::RefCell;
+use na::{Pnt2, Pnt3, Vec3};
+use kiss3d::window::Window;
+use kiss3d::resource::{Mesh, TextureManager, TextureOptions};
+use kiss3d::light::Light;
+
+fn main() {
+    let mut window = Window::new("Kiss3d: tiled_floor");
+
+    // A black and white checkerboard generated in memory.
+    let size       = 64u;
+    let mut pixels = Vec::with_capacity(size * size * 4);
+
+    for i in range(0u, size) {
+        for j in range(0u, size) {
+            let c = if (i / 32 + j / 32) % 2 == 0 { 255u8 } else { 40 };
+
+            pixels.push_all(&[ c, c, c, 255 ]);
+        }
+    }
+
+    // Repeated, with mipmaps and anisotropic filtering so that the far tiles do not shimmer.
+    let options = TextureOptions::tiled();
+    let texture = TextureManager::get_global_manager(
+        |tm| tm.add_rgba(pixels.as_slice(), size, size, "checkerboard", &options));
+
+    // Texture coordinates greater than 1 repeat the texture.
+    let extent   = 50.0;
+    let vertices = vec!(Pnt3::new(-extent, 0.0, -extent), Pnt3::new(extent, 0.0, -extent),
+                        Pnt3::new(extent, 0.0, extent),   Pnt3::new(-extent, 0.0, extent));
+    let uvs      = vec!(Pnt2::new(0.0, 0.0),       Pnt2::new(extent, 0.0),
+                        Pnt2::new(extent, extent), Pnt2::new(0.0, extent));
+    let indices  = vec!(Vec3::new(0u32, 2, 1), Vec3::new(0u32, 3, 2));
+
+    let mesh      = Rc::new(RefCell::new(Mesh::new(vertices, indices, None, Some(uvs), false)));
+    let mut floor = window.add_mesh(mesh, na::one());
+
+    floor.set_texture(texture);
+    floor.enable_backface_culling(false);
+
+    window.set_light(Light::StickToCamera);
+
+    while window.render() {
+    }
+}
@@ -0,0 +1,25 @@
+extern crate kiss3d;
+extern crate "nalgebra" as na;
+
+use na::{Pnt3, Vec3};
+use kiss3d::window::Window;
+use kiss3d::camera::Trackball;
+use kiss3d::light::Light;
+
+fn main() {
+    let mut window    = Window::new("Kiss3d: trackball");
+    let mut trackball = Trackball::new(Pnt3::new(0.0f32, 0.0, 5.0), na::orig());
+
+    // an asymmetric object to show that the camera can roll and go upside down.
+    let mut c = window.add_cube(1.0, 1.0, 1.0);
+    let mut k = window.add_cone(0.3, 1.0);
+
+    c.set_color(1.0, 0.0, 0.0);
+    k.set_color(0.0, 1.0, 0.0);
+    k.set_local_translation(Vec3::new(0.0, 1.0, 0.0));
+
+    window.set_light(Light::StickToCamera);
+
+    while window.render_with_camera(&mut trackball) {
+    }
+}
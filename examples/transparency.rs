@@ -0,0 +1,25 @@
+extern crate kiss3d;
+extern crate "nalgebra" as na;
+
+use na::Vec3;
+use kiss3d::window::Window;
+use kiss3d::light::Light;
+
+fn main() {
+    let mut window = Window::new("Kiss3d: transparency");
+
+    let mut c = window.add_cube(0.5, 0.5, 0.5);
+    let mut s = window.add_sphere(0.6);
+    let mut y = window.add_cylinder(0.2, 2.0);
+
+    c.set_color(1.0, 0.0, 0.0);
+    s.set_color_with_alpha(0.0, 0.5, 1.0, 0.3);
+    y.set_color_with_alpha(0.0, 1.0, 0.0, 0.6);
+    y.append_translation(&Vec3::new(0.8, 0.0, 0.0));
+
+    window.set_light(Light::StickToCamera);
+
+    while window.render() {
+        c.append_rotation_wrt_center(&Vec3::new(0.0f32, 0.014, 0.0));
+    }
+}
@@ -0,0 +1,167 @@
+use std::ptr;
+use gl;
+use gl::types::*;
+use na::{Pnt3, Vec3, Mat3, Mat4, Iso3};
+use na;
+use resource::Material;
+use scene::ObjectData;
+use light::LightSource;
+use camera::Camera;
+use resource::{Mesh, Shader, ShaderAttribute, ShaderUniform, Instances};
+
+#[path = "../error.rs"]
+mod error;
+
+/// A material that only fills the depth buffer.
+///
+/// This is used to render shadow maps.
+pub struct DepthMaterial {
+    shader:     Shader,
+    position:   ShaderAttribute<Pnt3<f32>>,
+    inst_tra:   ShaderAttribute<Vec3<f32>>,
+    inst_def:   Vec<ShaderAttribute<Vec3<f32>>>,
+    use_inst:   ShaderUniform<GLint>,
+    view:       ShaderUniform<Mat4<f32>>,
+    transform:  ShaderUniform<Mat4<f32>>,
+    scale:      ShaderUniform<Mat3<f32>>,
+    instancing: bool
+}
+
+impl DepthMaterial {
+    /// Creates a new DepthMaterial.
+    pub fn new() -> DepthMaterial {
+        let mut shader = Shader::new_from_str(DEPTH_VERTEX_SRC, DEPTH_FRAGMENT_SRC);
+
+        shader.use_program();
+
+        DepthMaterial {
+            position:   shader.get_attrib("position").unwrap(),
+            inst_tra:   shader.get_attrib("inst_tra").unwrap(),
+            inst_def:   range(0u, 3).map(|i| shader.get_attrib(format!("inst_def_{}", i).as_slice()).unwrap()).collect(),
+            use_inst:   shader.get_uniform("use_instances").unwrap(),
+            transform:  shader.get_uniform("transform").unwrap(),
+            scale:      shader.get_uniform("scale").unwrap(),
+            view:       shader.get_uniform("view").unwrap(),
+            instancing: Instances::is_supported(),
+            shader:     shader
+        }
+    }
+}
+
+impl Material for DepthMaterial {
+    fn render(&mut self,
+              pass:      uint,
+              transform: &Iso3<f32>,
+              scale:     &Vec3<f32>,
+              camera:    &mut Camera,
+              _:         &[LightSource],
+              data:      &ObjectData,
+              mesh:      &mut Mesh) {
+        let mut instances = data.instances().map(|i| i.borrow_mut());
+        let num_instances = instances.as_ref().map(|i| i.len());
+
+        if !data.surface_rendering_active() || num_instances == Some(0) {
+            return
+        }
+
+        verify!(gl::Disable(gl::CULL_FACE));
+
+        self.shader.use_program();
+        self.position.enable();
+
+        /*
+         *
+         * Setup camera.
+         *
+         */
+        camera.upload(pass, &mut self.view);
+
+        /*
+         *
+         * Setup object-related stuffs.
+         *
+         */
+        let formated_transform: Mat4<f32> = na::to_homogeneous(transform);
+        let formated_scale:     Mat3<f32> = Mat3::new(scale.x, 0.0, 0.0, 0.0, scale.y, 0.0, 0.0, 0.0, scale.z);
+
+        self.transform.upload(&formated_transform);
+        self.scale.upload(&formated_scale);
+
+        mesh.bind_coords(&mut self.position);
+        mesh.bind_faces();
+
+        self.use_inst.upload(&(if instances.is_some() { 1 } else { 0 }));
+
+        verify!(gl::PolygonMode(gl::FRONT_AND_BACK, gl::FILL));
+
+        match instances {
+            Some(ref mut i) if self.instancing => {
+                i.bind(&mut self.inst_tra, self.inst_def.as_mut_slice(), None);
+                draw_triangles(mesh.num_pts(), num_instances);
+                i.unbind(&mut self.inst_tra, self.inst_def.as_mut_slice(), None)
+            },
+            Some(ref mut i) => {
+                // Without instanced draws, draw each instance separately.
+                for id in range(0u, i.len()) {
+                    i.set_constants(id, &mut self.inst_tra, self.inst_def.as_mut_slice(), None);
+                    draw_triangles(mesh.num_pts(), None);
+                }
+            },
+            None => draw_triangles(mesh.num_pts(), None)
+        }
+
+        mesh.unbind();
+
+        self.position.disable();
+    }
+}
+
+/// Draws the triangles of the bound mesh, once per instance if any.
+fn draw_triangles(num_pts: uint, num_instances: Option<uint>) {
+    unsafe {
+        match num_instances {
+            Some(n) => verify!(gl::DrawElementsInstanced(gl::TRIANGLES,
+                                                         num_pts as GLint,
+                                                         gl::UNSIGNED_INT,
+                                                         ptr::null(),
+                                                         n as GLint)),
+            None => verify!(gl::DrawElements(gl::TRIANGLES, num_pts as GLint, gl::UNSIGNED_INT, ptr::null()))
+        }
+    }
+}
+
+/// Vertex shader of the depth material.
+pub static DEPTH_VERTEX_SRC: &'static str = A_VERY_LONG_STRING;
+/// Fragment shader of the depth material.
+pub static DEPTH_FRAGMENT_SRC: &'static str = ANOTHER_VERY_LONG_STRING;
+
+const A_VERY_LONG_STRING: &'static str =
+"#version 120
+attribute vec3 position;
+attribute vec3 inst_tra;
+attribute vec3 inst_def_0;
+attribute vec3 inst_def_1;
+attribute vec3 inst_def_2;
+uniform mat4 view;
+uniform mat4 transform;
+uniform mat3 scale;
+uniform int  use_instances;
+
+void main() {
+    vec3 lposition = position;
+
+    if (use_instances != 0) {
+        lposition = mat3(inst_def_0, inst_def_1, inst_def_2) * position + inst_tra;
+    }
+
+    gl_Position = view * transform * mat4(scale) * vec4(lposition, 1.0);
+}
+";
+
+const ANOTHER_VERY_LONG_STRING: &'static str =
+"#version 120
+
+void main() {
+    gl_FragColor = vec4(1.0, 1.0, 1.0, 1.0);
+}
+";
@@ -0,0 +1,148 @@
+use std::ptr;
+use gl;
+use gl::types::*;
+use na::{Pnt2, Pnt3, Vec3, Mat3, Mat4, Iso3};
+use na;
+use resource::Material;
+use scene::ObjectData;
+use light::LightSource;
+use camera::Camera;
+use resource::{Mesh, Shader, ShaderAttribute, ShaderUniform};
+
+#[path = "../error.rs"]
+mod error;
+
+/// A material that draws objects with their color and texture, without lighting.
+///
+/// This is used to draw the shapes of the 2D scene layer.
+pub struct FlatMaterial {
+    shader:    Shader,
+    position:  ShaderAttribute<Pnt3<f32>>,
+    tex_coord: ShaderAttribute<Pnt2<f32>>,
+    color:     ShaderUniform<Pnt3<f32>>,
+    alpha:     ShaderUniform<GLfloat>,
+    tex:       ShaderUniform<GLint>,
+    view:      ShaderUniform<Mat4<f32>>,
+    transform: ShaderUniform<Mat4<f32>>,
+    scale:     ShaderUniform<Mat3<f32>>
+}
+
+impl FlatMaterial {
+    /// Creates a new FlatMaterial.
+    pub fn new() -> FlatMaterial {
+        let mut shader = Shader::new_from_str(FLAT_VERTEX_SRC, FLAT_FRAGMENT_SRC);
+
+        shader.use_program();
+
+        FlatMaterial {
+            position:  shader.get_attrib("position").unwrap(),
+            tex_coord: shader.get_attrib("tex_coord").unwrap(),
+            color:     shader.get_uniform("color").unwrap(),
+            alpha:     shader.get_uniform("alpha").unwrap(),
+            tex:       shader.get_uniform("tex").unwrap(),
+            transform: shader.get_uniform("transform").unwrap(),
+            scale:     shader.get_uniform("scale").unwrap(),
+            view:      shader.get_uniform("view").unwrap(),
+            shader:    shader
+        }
+    }
+}
+
+impl Material for FlatMaterial {
+    fn render(&mut self,
+              pass:      uint,
+              transform: &Iso3<f32>,
+              scale:     &Vec3<f32>,
+              camera:    &mut Camera,
+              _:         &[LightSource],
+              data:      &ObjectData,
+              mesh:      &mut Mesh) {
+        if !data.surface_rendering_active() {
+            return
+        }
+
+        if data.backface_culling_enabled() {
+            verify!(gl::Enable(gl::CULL_FACE));
+        }
+        else {
+            verify!(gl::Disable(gl::CULL_FACE));
+        }
+
+        self.shader.use_program();
+        self.position.enable();
+        self.tex_coord.enable();
+
+        /*
+         *
+         * Setup camera.
+         *
+         */
+        camera.upload(pass, &mut self.view);
+
+        /*
+         *
+         * Setup object-related stuffs.
+         *
+         */
+        let formated_transform: Mat4<f32> = na::to_homogeneous(transform);
+        let formated_scale:     Mat3<f32> = Mat3::new(scale.x, 0.0, 0.0, 0.0, scale.y, 0.0, 0.0, 0.0, scale.z);
+
+        self.transform.upload(&formated_transform);
+        self.scale.upload(&formated_scale);
+        self.color.upload(data.color());
+        self.alpha.upload(&data.alpha());
+        self.tex.upload(&0);
+
+        verify!(gl::ActiveTexture(gl::TEXTURE0));
+        verify!(gl::BindTexture(gl::TEXTURE_2D, data.texture().id()));
+
+        mesh.bind_coords(&mut self.position);
+        mesh.bind_uvs(&mut self.tex_coord);
+        mesh.bind_faces();
+
+        unsafe {
+            verify!(gl::PolygonMode(gl::FRONT_AND_BACK, gl::FILL));
+            gl::DrawElements(gl::TRIANGLES,
+                             mesh.num_pts() as GLint,
+                             gl::UNSIGNED_INT,
+                             ptr::null());
+        }
+
+        mesh.unbind();
+
+        self.position.disable();
+        self.tex_coord.disable();
+    }
+}
+
+/// Vertex shader of the flat material.
+pub static FLAT_VERTEX_SRC: &'static str = A_VERY_LONG_STRING;
+/// Fragment shader of the flat material.
+pub static FLAT_FRAGMENT_SRC: &'static str = ANOTHER_VERY_LONG_STRING;
+
+const A_VERY_LONG_STRING: &'static str =
+"#version 120
+attribute vec3 position;
+attribute vec2 tex_coord;
+uniform mat4 view;
+uniform mat4 transform;
+uniform mat3 scale;
+varying vec2 tex_coord_v;
+
+void main() {
+    gl_Position = view * transform * mat4(scale) * vec4(position, 1.0);
+    tex_coord_v = tex_coord;
+}
+";
+
+const ANOTHER_VERY_LONG_STRING: &'static str =
+"#version 120
+uniform vec3      color;
+uniform float     alpha;
+uniform sampler2D tex;
+varying vec2      tex_coord_v;
+
+void main() {
+    gl_FragColor = texture2D(tex, tex_coord_v) * vec4(color, alpha);
+}
+";
@@ -1,9 +1,19 @@
 //! Built-in geometries, shaders and effects.
 
-pub use builtin::object_material::{OBJECT_VERTEX_SRC, OBJECT_FRAGMENT_SRC, ObjectMaterial};
+pub use builtin::object_material::{OBJECT_VERTEX_SRC, OBJECT_FRAGMENT_SRC, MAX_LIGHTS, ObjectMaterial};
 pub use builtin::normals_material::{NORMAL_VERTEX_SRC, NORMAL_FRAGMENT_SRC, NormalsMaterial};
 pub use builtin::uvs_material::{UVS_VERTEX_SRC, UVS_FRAGMENT_SRC, UvsMaterial};
+pub use builtin::depth_material::{DEPTH_VERTEX_SRC, DEPTH_FRAGMENT_SRC, DepthMaterial};
+pub use builtin::flat_material::{FLAT_VERTEX_SRC, FLAT_FRAGMENT_SRC, FlatMaterial};
+pub use builtin::vertex_colors_shader::{VERTEX_COLORS_VERTEX_SRC, VERTEX_COLORS_FRAGMENT_SRC, VertexColorsShader};
+pub use builtin::thick_lines_shader::{THICK_LINES_VERTEX_SRC, THICK_LINES_FRAGMENT_SRC, ThickLinesShader};
+pub use builtin::sprites_shader::{SPRITES_VERTEX_SRC, SPRITES_FRAGMENT_SRC, SpritesShader};
 
 mod object_material;
 mod normals_material;
 mod uvs_material;
+mod depth_material;
+mod flat_material;
+mod vertex_colors_shader;
+mod thick_lines_shader;
+mod sprites_shader;
@@ -1,29 +1,100 @@
 use std::ptr;
+use std::cmp;
+use std::num::{Float, FloatMath};
 use gl;
 use gl::types::*;
 use na::{Pnt2, Pnt3, Vec3, Mat3, Mat4, Iso3};
 use na;
 use resource::Material;
 use scene::ObjectData;
-use light::Light;
+use light::{LightKind, LightSource};
 use camera::Camera;
-use resource::{Mesh, Shader, ShaderAttribute, ShaderUniform};
+use resource::{Mesh, Shader, ShaderAttribute, ShaderUniform, Instances};
+use shadow_map::ShadowMap;
 
 #[path = "../error.rs"]
 mod error;
 
+/// The maximum number of lights the default object material can handle.
+///
+/// Lights in excess are ignored.
+pub const MAX_LIGHTS: uint = 8;
+
+/// Uniforms describing one light source of the object material.
+struct LightUniforms {
+    kind:        ShaderUniform<GLint>,
+    position:    ShaderUniform<Pnt3<f32>>,
+    direction:   ShaderUniform<Vec3<f32>>,
+    color:       ShaderUniform<Pnt3<f32>>,
+    attenuation: ShaderUniform<Vec3<f32>>,
+    cos_cutoff:  ShaderUniform<f32>,
+    falloff:     ShaderUniform<f32>
+}
+
+impl LightUniforms {
+    fn new(shader: &Shader, i: uint) -> LightUniforms {
+        LightUniforms {
+            kind:        shader.get_uniform(format!("light_kind[{}]", i).as_slice()).unwrap(),
+            position:    shader.get_uniform(format!("light_position[{}]", i).as_slice()).unwrap(),
+            direction:   shader.get_uniform(format!("light_direction[{}]", i).as_slice()).unwrap(),
+            color:       shader.get_uniform(format!("light_color[{}]", i).as_slice()).unwrap(),
+            attenuation: shader.get_uniform(format!("light_attenuation[{}]", i).as_slice()).unwrap(),
+            cos_cutoff:  shader.get_uniform(format!("light_cos_cutoff[{}]", i).as_slice()).unwrap(),
+            falloff:     shader.get_uniform(format!("light_falloff[{}]", i).as_slice()).unwrap()
+        }
+    }
+
+    fn upload(&mut self, light: &LightSource, camera: &Camera) {
+        let (pos, dir) = light.position_and_direction(camera);
+
+        let kind: GLint = match light.kind {
+            LightKind::Directional => 0,
+            LightKind::Point       => 1,
+            LightKind::Spot        => 2
+        };
+
+        let color = Pnt3::new(light.color.x * light.intensity,
+                              light.color.y * light.intensity,
+                              light.color.z * light.intensity);
+
+        self.kind.upload(&kind);
+        self.position.upload(&pos);
+        self.direction.upload(&dir);
+        self.color.upload(&color);
+        self.attenuation.upload(&light.attenuation);
+        self.cos_cutoff.upload(&light.cutoff.cos());
+        self.falloff.upload(&light.falloff);
+    }
+}
+
 /// The default material used to draw objects.
 pub struct ObjectMaterial {
     shader:     Shader,
     pos:        ShaderAttribute<Pnt3<f32>>,
     normal:     ShaderAttribute<Vec3<f32>>,
     tex_coord:  ShaderAttribute<Pnt2<f32>>,
-    light:      ShaderUniform<Pnt3<f32>>,
+    inst_tra:   ShaderAttribute<Vec3<f32>>,
+    inst_def:   Vec<ShaderAttribute<Vec3<f32>>>,
+    inst_color: ShaderAttribute<Pnt3<f32>>,
+    use_inst:   ShaderUniform<GLint>,
+    num_lights: ShaderUniform<GLint>,
+    lights:     Vec<LightUniforms>,
     color:      ShaderUniform<Pnt3<f32>>,
+    alpha:      ShaderUniform<f32>,
+    cutoff:     ShaderUniform<f32>,
+    tex:        ShaderUniform<GLint>,
+    opac_tex:   ShaderUniform<GLint>,
+    use_opac:   ShaderUniform<GLint>,
     transform:  ShaderUniform<Mat4<f32>>,
     scale:      ShaderUniform<Mat3<f32>>,
     ntransform: ShaderUniform<Mat3<f32>>,
-    view:       ShaderUniform<Mat4<f32>>
+    view:       ShaderUniform<Mat4<f32>>,
+    light_view: ShaderUniform<Mat4<f32>>,
+    shadow_tex: ShaderUniform<GLint>,
+    use_shadow: ShaderUniform<GLint>,
+    texel_size: ShaderUniform<f32>,
+    shadow:     Option<(Mat4<f32>, GLuint, f32)>,
+    instancing: bool
 }
 
 impl ObjectMaterial {
@@ -39,12 +110,28 @@ impl ObjectMaterial {
             pos:        shader.get_attrib("position").unwrap(),
             normal:     shader.get_attrib("normal").unwrap(),
             tex_coord:  shader.get_attrib("tex_coord_v").unwrap(),
-            light:      shader.get_uniform("light_position").unwrap(),
+            inst_tra:   shader.get_attrib("inst_tra").unwrap(),
+            inst_def:   range(0u, 3).map(|i| shader.get_attrib(format!("inst_def_{}", i).as_slice()).unwrap()).collect(),
+            inst_color: shader.get_attrib("inst_color").unwrap(),
+            use_inst:   shader.get_uniform("use_instances").unwrap(),
+            num_lights: shader.get_uniform("num_lights").unwrap(),
+            lights:     range(0u, MAX_LIGHTS).map(|i| LightUniforms::new(&shader, i)).collect(),
             color:      shader.get_uniform("color").unwrap(),
+            alpha:      shader.get_uniform("alpha").unwrap(),
+            cutoff:     shader.get_uniform("alpha_cutoff").unwrap(),
+            tex:        shader.get_uniform("tex").unwrap(),
+            opac_tex:   shader.get_uniform("opacity_tex").unwrap(),
+            use_opac:   shader.get_uniform("use_opacity_tex").unwrap(),
             transform:  shader.get_uniform("transform").unwrap(),
             scale:      shader.get_uniform("scale").unwrap(),
             ntransform: shader.get_uniform("ntransform").unwrap(),
             view:       shader.get_uniform("view").unwrap(),
+            light_view: shader.get_uniform("light_view").unwrap(),
+            shadow_tex: shader.get_uniform("shadow_map").unwrap(),
+            use_shadow: shader.get_uniform("use_shadow_map").unwrap(),
+            texel_size: shader.get_uniform("shadow_texel").unwrap(),
+            shadow:     None,
+            instancing: Instances::is_supported(),
             shader:     shader
         }
     }
@@ -69,11 +156,17 @@ impl Material for ObjectMaterial {
               transform: &Iso3<f32>,
               scale:     &Vec3<f32>,
               camera:    &mut Camera,
-              light:     &Light,
+              lights:    &[LightSource],
               data:      &ObjectData,
               mesh:      &mut Mesh) {
-        self.activate();
+        let mut instances = data.instances().map(|i| i.borrow_mut());
+        let num_instances = instances.as_ref().map(|i| i.len());
+
+        if num_instances == Some(0) {
+            return;
+        }
 
+        self.activate();
 
         /*
          *
@@ -82,12 +175,13 @@ impl Material for ObjectMaterial {
          */
         camera.upload(pass, &mut self.view);
 
-        let pos = match *light {
-            Light::Absolute(ref p) => p.clone(),
-            Light::StickToCamera   => camera.eye()
-        };
+        let num_lights = cmp::min(lights.len(), MAX_LIGHTS);
 
-        self.light.upload(&pos);
+        self.num_lights.upload(&(num_lights as GLint));
+
+        for (light, uniforms) in lights.iter().zip(self.lights.iter_mut()) {
+            uniforms.upload(light, &*camera);
+        }
 
         /*
          *
@@ -103,56 +197,118 @@ impl Material for ObjectMaterial {
             self.ntransform.upload(&formated_ntransform);
             self.scale.upload(&formated_scale);
             self.color.upload(data.color());
+            self.alpha.upload(&data.alpha());
+            // A negative cutoff disables the alpha test.
+            self.cutoff.upload(&data.alpha_cutoff().unwrap_or(-1.0));
 
             mesh.bind(&mut self.pos, &mut self.normal, &mut self.tex_coord);
 
-            verify!(gl::ActiveTexture(gl::TEXTURE0));
-            verify!(gl::BindTexture(gl::TEXTURE_2D, data.texture().id()));
+            match instances {
+                Some(ref mut i) => {
+                    if self.instancing {
+                        i.bind(&mut self.inst_tra, self.inst_def.as_mut_slice(), Some(&mut self.inst_color));
+                    }
 
-            if data.surface_rendering_active() {
-                if data.backface_culling_enabled() {
-                    verify!(gl::Enable(gl::CULL_FACE));
-                }
-                else {
-                    verify!(gl::Disable(gl::CULL_FACE));
-                }
-
-                verify!(gl::PolygonMode(gl::FRONT_AND_BACK, gl::FILL));
-                verify!(gl::DrawElements(
-                            gl::TRIANGLES,
-                            mesh.num_pts() as GLint,
-                            gl::UNSIGNED_INT,
-                            ptr::null()));
+                    self.use_inst.upload(&1);
+                },
+                None => self.use_inst.upload(&0)
             }
 
-            if data.lines_width() != 0.0 {
-                verify!(gl::Disable(gl::CULL_FACE));
-                verify!(gl::PolygonMode(gl::FRONT_AND_BACK, gl::LINE));
-                gl::LineWidth(data.lines_width());
-                verify!(gl::DrawElements(
-                            gl::TRIANGLES,
-                            mesh.num_pts() as GLint,
-                            gl::UNSIGNED_INT,
-                            ptr::null()));
-                gl::LineWidth(1.0);
+            match data.opacity_texture() {
+                Some(t) => {
+                    verify!(gl::ActiveTexture(gl::TEXTURE1));
+                    verify!(gl::BindTexture(gl::TEXTURE_2D, t.id()));
+                    self.use_opac.upload(&1);
+                },
+                None => self.use_opac.upload(&0)
             }
 
-            if data.points_size() != 0.0 {
-                verify!(gl::Disable(gl::CULL_FACE));
-                verify!(gl::PolygonMode(gl::FRONT_AND_BACK, gl::POINT));
-                gl::PointSize(data.points_size());
-                verify!(gl::DrawElements(
-                            gl::TRIANGLES,
-                            mesh.num_pts() as GLint,
-                            gl::UNSIGNED_INT,
-                            ptr::null()));
-                gl::PointSize(1.0);
+            match self.shadow {
+                Some((ref light_view, depth, texel)) if data.receives_shadows() => {
+                    verify!(gl::ActiveTexture(gl::TEXTURE2));
+                    verify!(gl::BindTexture(gl::TEXTURE_2D, depth));
+                    self.light_view.upload(light_view);
+                    self.texel_size.upload(&texel);
+                    self.use_shadow.upload(&1);
+                },
+                _ => self.use_shadow.upload(&0)
             }
+
+            self.tex.upload(&0);
+            self.opac_tex.upload(&1);
+            self.shadow_tex.upload(&2);
+
+            verify!(gl::ActiveTexture(gl::TEXTURE0));
+            verify!(gl::BindTexture(gl::TEXTURE_2D, data.texture().id()));
+        }
+
+        match instances {
+            Some(ref mut i) if self.instancing => {
+                draw(data, mesh.num_pts(), num_instances);
+                i.unbind(&mut self.inst_tra, self.inst_def.as_mut_slice(), Some(&mut self.inst_color))
+            },
+            Some(ref mut i) => {
+                // Without instanced draws, draw each instance separately.
+                for id in range(0u, i.len()) {
+                    i.set_constants(id, &mut self.inst_tra, self.inst_def.as_mut_slice(), Some(&mut self.inst_color));
+                    draw(data, mesh.num_pts(), None);
+                }
+            },
+            None => draw(data, mesh.num_pts(), None)
         }
 
         mesh.unbind();
         self.deactivate();
     }
+
+    fn set_shadow_map(&mut self, shadow_map: Option<&ShadowMap>) {
+        self.shadow = shadow_map.map(|s| (s.transformation(), s.depth_texture_id(), 1.0 / s.size() as f32));
+    }
+}
+
+/// Draws the bound mesh with every rendering mode enabled for the object.
+fn draw(data: &ObjectData, num_pts: uint, num_instances: Option<uint>) {
+    if data.surface_rendering_active() {
+        if data.backface_culling_enabled() {
+            verify!(gl::Enable(gl::CULL_FACE));
+        }
+        else {
+            verify!(gl::Disable(gl::CULL_FACE));
+        }
+
+        verify!(gl::PolygonMode(gl::FRONT_AND_BACK, gl::FILL));
+        draw_triangles(num_pts, num_instances);
+    }
+
+    if data.lines_width() != 0.0 {
+        verify!(gl::Disable(gl::CULL_FACE));
+        verify!(gl::PolygonMode(gl::FRONT_AND_BACK, gl::LINE));
+        unsafe { gl::LineWidth(data.lines_width()) };
+        draw_triangles(num_pts, num_instances);
+        unsafe { gl::LineWidth(1.0) };
+    }
+
+    if data.points_size() != 0.0 {
+        verify!(gl::Disable(gl::CULL_FACE));
+        verify!(gl::PolygonMode(gl::FRONT_AND_BACK, gl::POINT));
+        unsafe { gl::PointSize(data.points_size()) };
+        draw_triangles(num_pts, num_instances);
+        unsafe { gl::PointSize(1.0) };
+    }
+}
+
+/// Draws the triangles of the bound mesh, once per instance if any.
+fn draw_triangles(num_pts: uint, num_instances: Option<uint>) {
+    unsafe {
+        match num_instances {
+            Some(n) => verify!(gl::DrawElementsInstanced(gl::TRIANGLES,
+                                                         num_pts as GLint,
+                                                         gl::UNSIGNED_INT,
+                                                         ptr::null(),
+                                                         n as GLint)),
+            None => verify!(gl::DrawElements(gl::TRIANGLES, num_pts as GLint, gl::UNSIGNED_INT, ptr::null()))
+        }
+    }
 }
 
 /// Vertex shader of the default object material.
@@ -164,49 +320,169 @@ const A_VERY_LONG_STRING: &'static str =
    "#version 120
     attribute vec3 position;
     attribute vec3 normal;
-    attribute vec3 color;
     attribute vec2 tex_coord_v;
+    attribute vec3 inst_tra;
+    attribute vec3 inst_def_0;
+    attribute vec3 inst_def_1;
+    attribute vec3 inst_def_2;
+    attribute vec3 inst_color;
+    varying vec3 instance_color;
     varying vec3 ws_normal;
     varying vec3 ws_position;
     varying vec2 tex_coord;
+    varying vec4 shadow_coord;
     uniform mat4 view;
+    uniform mat4 light_view;
     uniform mat4 transform;
     uniform mat3 scale;
     uniform mat3 ntransform;
+    uniform int  use_instances;
     void main() {
+        vec3 lposition = position;
+        vec3 lnormal   = normal;
+        instance_color = vec3(1.0, 1.0, 1.0);
+
+        if (use_instances != 0) {
+            mat3 def       = mat3(inst_def_0, inst_def_1, inst_def_2);
+            // The cofactor matrix is the inverse transpose times the determinant. Only the sign
+            // of the latter matters since normals are normalized afterward.
+            mat3 cof       = mat3(cross(inst_def_1, inst_def_2), cross(inst_def_2, inst_def_0), cross(inst_def_0, inst_def_1));
+            lposition      = def * position + inst_tra;
+            lnormal        = cof * normal * sign(dot(inst_def_0, cof[0]));
+            instance_color = inst_color;
+        }
+
         mat4 scale4 = mat4(scale);
-        vec4 pos4   = transform * scale4 * vec4(position, 1.0);
+        vec4 pos4   = transform * scale4 * vec4(lposition, 1.0);
         tex_coord   = tex_coord_v;
         ws_position = pos4.xyz;
         gl_Position = view * pos4;
-        ws_normal   = normalize(ntransform * scale * normal);
+        shadow_coord = light_view * pos4;
+        ws_normal   = normalize(ntransform * scale * lnormal);
     }";
 
 // phong-like lighting (heavily) inspired
 // by http://www.opengl.org/sdk/docs/tutorials/ClockworkCoders/lighting.php
+//
+// light_kind: 0 = directional, 1 = point, 2 = spot.
+// Only the first light casts shadows, filtered with a 3x3 percentage-closer filter.
 const ANOTHER_VERY_LONG_STRING: &'static str =
    "#version 120
+    const int MAX_LIGHTS = 8;
     uniform vec3      color;
-    uniform vec3      light_position;
+    uniform float     alpha;
+    uniform float     alpha_cutoff;
+    uniform int       num_lights;
+    uniform int       light_kind[MAX_LIGHTS];
+    uniform vec3      light_position[MAX_LIGHTS];
+    uniform vec3      light_direction[MAX_LIGHTS];
+    uniform vec3      light_color[MAX_LIGHTS];
+    uniform vec3      light_attenuation[MAX_LIGHTS];
+    uniform float     light_cos_cutoff[MAX_LIGHTS];
+    uniform float     light_falloff[MAX_LIGHTS];
     uniform sampler2D tex;
+    uniform sampler2D opacity_tex;
+    uniform int       use_opacity_tex;
+    uniform sampler2D shadow_map;
+    uniform int       use_shadow_map;
+    uniform float     shadow_texel;
+    varying vec4      shadow_coord;
+    varying vec3      instance_color;
     varying vec2      tex_coord;
     varying vec3      ws_normal;
     varying vec3      ws_position;
-    void main() {
-      vec3 L = normalize(light_position - ws_position);
-      vec3 E = normalize(-ws_position);
+    float lit_fraction() {
+      vec3 coord = shadow_coord.xyz / shadow_coord.w * 0.5 + 0.5;
 
+      if (coord.x < 0.0 || coord.x > 1.0 || coord.y < 0.0 || coord.y > 1.0 || coord.z > 1.0) {
+        return 1.0;
+      }
+
+      float lit = 0.0;
+
+      for (int x = -1; x <= 1; x++) {
+        for (int y = -1; y <= 1; y++) {
+          float depth = texture2D(shadow_map, coord.xy + vec2(float(x), float(y)) * shadow_texel).r;
+
+          if (coord.z - 0.0005 <= depth) {
+            lit = lit + 1.0;
+          }
+        }
+      }
+
+      return lit / 9.0;
+    }
+
+    void main() {
       //calculate Ambient Term:
-      vec4 Iamb = vec4(color, 1.0);
+      vec3 Iamb = color * instance_color;
+
+      vec3 Idiff = vec3(0.0, 0.0, 0.0);
+
+      float shadow = 1.0;
 
-      //calculate Diffuse Term:
-      vec4 Idiff1 = vec4(1.0, 1.0, 1.0, 1.0) * max(dot(ws_normal,L), 0.0);
-      Idiff1 = clamp(Idiff1, 0.0, 1.0);
+      if (use_shadow_map != 0) {
+        shadow = lit_fraction();
+      }
+
+      for (int i = 0; i < MAX_LIGHTS; i++) {
+        if (i >= num_lights) {
+          break;
+        }
+
+        vec3  L;
+        float factor = 1.0;
+
+        if (light_kind[i] == 0) {
+          L = normalize(-light_direction[i]);
+        }
+        else {
+          vec3  to_light = light_position[i] - ws_position;
+          float dist     = length(to_light);
+          vec3  att      = light_attenuation[i];
+
+          L      = to_light / dist;
+          factor = 1.0 / (att.x + att.y * dist + att.z * dist * dist);
+
+          if (light_kind[i] == 2) {
+            float cos_angle = dot(-L, normalize(light_direction[i]));
+
+            if (cos_angle < light_cos_cutoff[i]) {
+              factor = 0.0;
+            }
+            else {
+              factor = factor * pow(cos_angle, light_falloff[i]);
+            }
+          }
+        }
+
+        if (i == 0) {
+          factor = factor * shadow;
+        }
+
+        //calculate Diffuse Term:
+        float Idiff1 = clamp(max(dot(ws_normal, L), 0.0), 0.0, 1.0);
+
+        // double sided lighting:
+        float Idiff2 = clamp(max(dot(-ws_normal, L), 0.0), 0.0, 1.0);
+
+        Idiff = Idiff + light_color[i] * factor * (Idiff1 + Idiff2) / 2.0;
+      }
+
+      vec4  tex_color = texture2D(tex, tex_coord);
+      float opacity   = alpha * tex_color.a;
+
+      if (use_opacity_tex != 0) {
+        opacity = opacity * texture2D(opacity_tex, tex_coord).r;
+      }
+
+      if (alpha_cutoff >= 0.0) {
+        if (opacity < alpha_cutoff) {
+          discard;
+        }
 
-      // double sided lighting:
-      vec4 Idiff2 = vec4(1.0, 1.0, 1.0, 1.0) * max(dot(-ws_normal,L), 0.0);
-      Idiff2 = clamp(Idiff2, 0.0, 1.0);
+        opacity = 1.0;
+      }
 
-      vec4 tex_color = texture2D(tex, tex_coord);
-      gl_FragColor   = tex_color * (Iamb + (Idiff1 + Idiff2) / 2) / 2;
+      gl_FragColor = vec4(tex_color.rgb * (Iamb + Idiff) / 2.0, opacity);
     }";
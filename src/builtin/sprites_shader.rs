@@ -0,0 +1,205 @@
+use std::cell::RefCell;
+use gl;
+use gl::types::*;
+use na::{Pnt3, Vec2, Vec3, Mat3, Mat4, Iso3};
+use na;
+use camera::Camera;
+use resource::{GPUVector, Shader, ShaderAttribute, ShaderUniform};
+use point_renderer::{PointSprite, PointShape};
+
+#[path = "../error.rs"]
+mod error;
+
+thread_local!(static KEY_SPRITES_SHADER: RefCell<SpritesShader> = RefCell::new(SpritesShader::new()));
+
+/// A shader drawing points as camera-facing quads.
+///
+/// It is shared by every set of sprites.
+pub struct SpritesShader {
+    shader:    Shader,
+    pos:       ShaderAttribute<Pnt3<f32>>,
+    color:     ShaderAttribute<Pnt3<f32>>,
+    corner:    ShaderAttribute<Pnt3<f32>>,
+    view:      ShaderUniform<Mat4<f32>>,
+    transform: ShaderUniform<Mat4<f32>>,
+    scale:     ShaderUniform<Mat3<f32>>,
+    right:     ShaderUniform<Vec3<f32>>,
+    up:        ShaderUniform<Vec3<f32>>,
+    viewport:  ShaderUniform<Vec2<f32>>,
+    shape:     ShaderUniform<GLint>,
+    use_tex:   ShaderUniform<GLint>,
+    tex:       ShaderUniform<GLint>
+}
+
+impl SpritesShader {
+    /// Creates a new sprites shader.
+    pub fn new() -> SpritesShader {
+        let mut shader = Shader::new_from_str(SPRITES_VERTEX_SRC, SPRITES_FRAGMENT_SRC);
+
+        shader.use_program();
+
+        SpritesShader {
+            pos:       shader.get_attrib("position").unwrap(),
+            color:     shader.get_attrib("color").unwrap(),
+            corner:    shader.get_attrib("corner").unwrap(),
+            view:      shader.get_uniform("view").unwrap(),
+            transform: shader.get_uniform("transform").unwrap(),
+            scale:     shader.get_uniform("scale").unwrap(),
+            right:     shader.get_uniform("right").unwrap(),
+            up:        shader.get_uniform("up").unwrap(),
+            viewport:  shader.get_uniform("viewport").unwrap(),
+            shape:     shader.get_uniform("shape").unwrap(),
+            use_tex:   shader.get_uniform("use_texture").unwrap(),
+            tex:       shader.get_uniform("tex").unwrap(),
+            shader:    shader
+        }
+    }
+
+    /// Mutably applies a function to the shader shared by the current thread.
+    pub fn get_global_shader<T>(f: |&mut SpritesShader| -> T) -> T {
+        KEY_SPRITES_SHADER.with(|shader| f(shader.borrow_mut().deref_mut()))
+    }
+
+    /// Draws sprites laid out as by `Sprites`.
+    ///
+    /// The sprites are alpha-blended with the scene.
+    pub fn render(&mut self,
+                  sprite:    &PointSprite,
+                  pass:      uint,
+                  transform: &Iso3<f32>,
+                  scale:     &Vec3<f32>,
+                  camera:    &mut Camera,
+                  vertices:  &mut GPUVector<Pnt3<GLfloat>>) {
+        let num_pts = vertices.len() / 3;
+
+        if num_pts == 0 {
+            return
+        }
+
+        self.shader.use_program();
+        self.pos.enable();
+        self.color.enable();
+        self.corner.enable();
+
+        camera.upload(pass, &mut self.view);
+
+        let formated_transform: Mat4<f32> = na::to_homogeneous(transform);
+        let formated_scale:     Mat3<f32> = Mat3::new(scale.x, 0.0, 0.0, 0.0, scale.y, 0.0, 0.0, 0.0, scale.z);
+
+        self.transform.upload(&formated_transform);
+        self.scale.upload(&formated_scale);
+
+        // The camera `x` axis points to the left of the screen.
+        let view_transform = camera.view_transform();
+        self.right.upload(&-na::rotate(&view_transform, &Vec3::x()));
+        self.up.upload(&na::rotate(&view_transform, &Vec3::y()));
+
+        let mut viewport: [GLint, ..4] = [0, 0, 0, 0];
+        unsafe { verify!(gl::GetIntegerv(gl::VIEWPORT, &mut viewport[0])); }
+        self.viewport.upload(&Vec2::new(viewport[2] as f32, viewport[3] as f32));
+
+        self.tex.upload(&0);
+        self.shape.upload(&match sprite.shape {
+            PointShape::Square => 0,
+            PointShape::Round  => 1,
+            PointShape::Sphere => 2
+        });
+
+        match sprite.texture {
+            Some(ref t) => {
+                self.use_tex.upload(&1);
+                verify!(gl::ActiveTexture(gl::TEXTURE0));
+                verify!(gl::BindTexture(gl::TEXTURE_2D, t.id()));
+            },
+            None => self.use_tex.upload(&0)
+        }
+
+        self.pos.bind_sub_buffer(vertices, 2, 0);
+        self.color.bind_sub_buffer(vertices, 2, 1);
+        self.corner.bind_sub_buffer(vertices, 2, 2);
+
+        verify!(gl::Disable(gl::CULL_FACE));
+        verify!(gl::PolygonMode(gl::FRONT_AND_BACK, gl::FILL));
+        verify!(gl::Enable(gl::BLEND));
+        verify!(gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA));
+
+        verify!(gl::DrawArrays(gl::TRIANGLES, 0, num_pts as GLint));
+
+        verify!(gl::Disable(gl::BLEND));
+
+        self.pos.disable();
+        self.color.disable();
+        self.corner.disable();
+    }
+}
+
+/// Vertex shader used to display sprites.
+pub static SPRITES_VERTEX_SRC:   &'static str = A_VERY_LONG_STRING;
+/// Fragment shader used to display sprites.
+pub static SPRITES_FRAGMENT_SRC: &'static str = ANOTHER_VERY_LONG_STRING;
+
+const A_VERY_LONG_STRING: &'static str =
+   "#version 120
+    attribute vec3 position;
+    attribute vec3 color;
+    attribute vec3 corner;   // (corner.x, corner.y, diameter). World diameters are negative.
+    uniform   mat4 view;
+    uniform   mat4 transform;
+    uniform   mat3 scale;
+    uniform   vec3 right;    // The camera axes, in world coordinates.
+    uniform   vec3 up;
+    uniform   vec2 viewport;
+    varying   vec3 Color;
+    varying   vec2 Coord;
+    void main() {
+        float radius = abs(corner.z) * 0.5;
+        vec4  world  = transform * mat4(scale) * vec4(position, 1.0);
+
+        if (corner.z < 0.0) {
+            gl_Position = view * vec4(world.xyz + (right * corner.x + up * corner.y) * radius, 1.0);
+        }
+        else {
+            vec4 center = view * world;
+            gl_Position = center + vec4(corner.xy * radius * 2.0 / viewport * center.w, 0.0, 0.0);
+        }
+
+        Color = color;
+        Coord = corner.xy;
+    }";
+
+const ANOTHER_VERY_LONG_STRING: &'static str =
+   "#version 120
+    uniform int       shape;  // 0: square, 1: round, 2: sphere.
+    uniform int       use_texture;
+    uniform sampler2D tex;
+    varying vec3      Color;
+    varying vec2      Coord;
+    void main() {
+        float r2    = dot(Coord, Coord);
+        vec3  color = Color;
+
+        if (shape != 0 && r2 > 1.0) {
+            discard;
+        }
+
+        if (shape == 2) {
+            vec3  n        = vec3(Coord, sqrt(1.0 - r2));
+            vec3  l        = normalize(vec3(-0.4, 0.4, 1.0));
+            float diffuse  = max(dot(n, l), 0.0);
+            float specular = pow(max(dot(reflect(-l, n), vec3(0.0, 0.0, 1.0)), 0.0), 30.0);
+
+            color = color * (0.25 + 0.75 * diffuse) + vec3(0.3 * specular);
+        }
+
+        vec4 res = vec4(color, 1.0);
+
+        if (use_texture != 0) {
+            res = res * texture2D(tex, vec2(Coord.x * 0.5 + 0.5, 0.5 - Coord.y * 0.5));
+        }
+
+        if (res.a < 0.01) {
+            discard;
+        }
+
+        gl_FragColor = res;
+    }";
@@ -0,0 +1,305 @@
+use std::ptr;
+use std::cell::RefCell;
+use gl;
+use gl::types::*;
+use na::{Pnt3, Vec2, Vec3, Mat3, Mat4, Iso3};
+use na;
+use camera::Camera;
+use resource::{GPUVector, Shader, ShaderAttribute, ShaderUniform};
+use thick_line_renderer::{LineStyle, LineCap, LineJoin};
+
+#[path = "../error.rs"]
+mod error;
+
+thread_local!(static KEY_THICK_LINES_SHADER: RefCell<ThickLinesShader> = RefCell::new(ThickLinesShader::new()));
+
+/// A shader expanding line segments into anti-aliased, camera-facing quads.
+///
+/// It is shared by every set of thick lines.
+pub struct ThickLinesShader {
+    shader:    Shader,
+    prev:      ShaderAttribute<Pnt3<f32>>,
+    a:         ShaderAttribute<Pnt3<f32>>,
+    b:         ShaderAttribute<Pnt3<f32>>,
+    next:      ShaderAttribute<Pnt3<f32>>,
+    color:     ShaderAttribute<Pnt3<f32>>,
+    corner:    ShaderAttribute<Pnt3<f32>>,
+    view:      ShaderUniform<Mat4<f32>>,
+    transform: ShaderUniform<Mat4<f32>>,
+    scale:     ShaderUniform<Mat3<f32>>,
+    viewport:  ShaderUniform<Vec2<f32>>,
+    width:     ShaderUniform<GLfloat>,
+    cap:       ShaderUniform<GLint>,
+    join:      ShaderUniform<GLint>,
+    dash:      ShaderUniform<Vec2<f32>>
+}
+
+impl ThickLinesShader {
+    /// Creates a new thick lines shader.
+    pub fn new() -> ThickLinesShader {
+        let mut shader = Shader::new_from_str(THICK_LINES_VERTEX_SRC, THICK_LINES_FRAGMENT_SRC);
+
+        shader.use_program();
+
+        ThickLinesShader {
+            prev:      shader.get_attrib("prev").unwrap(),
+            a:         shader.get_attrib("a").unwrap(),
+            b:         shader.get_attrib("b").unwrap(),
+            next:      shader.get_attrib("next").unwrap(),
+            color:     shader.get_attrib("color").unwrap(),
+            corner:    shader.get_attrib("corner").unwrap(),
+            view:      shader.get_uniform("view").unwrap(),
+            transform: shader.get_uniform("transform").unwrap(),
+            scale:     shader.get_uniform("scale").unwrap(),
+            viewport:  shader.get_uniform("viewport").unwrap(),
+            width:     shader.get_uniform("width").unwrap(),
+            cap:       shader.get_uniform("cap").unwrap(),
+            join:      shader.get_uniform("join").unwrap(),
+            dash:      shader.get_uniform("dash").unwrap(),
+            shader:    shader
+        }
+    }
+
+    /// Mutably applies a function to the shader shared by the current thread.
+    pub fn get_global_shader<T>(f: |&mut ThickLinesShader| -> T) -> T {
+        KEY_THICK_LINES_SHADER.with(|shader| f(shader.borrow_mut().deref_mut()))
+    }
+
+    /// Draws segments laid out as by `ThickLines`.
+    ///
+    /// The segments are alpha-blended with the scene to smooth their edges.
+    pub fn render(&mut self,
+                  style:     &LineStyle,
+                  pass:      uint,
+                  transform: &Iso3<f32>,
+                  scale:     &Vec3<f32>,
+                  camera:    &mut Camera,
+                  vertices:  &mut GPUVector<Pnt3<GLfloat>>,
+                  faces:     &mut GPUVector<Vec3<GLuint>>) {
+        let num_pts = faces.len() * 3;
+
+        if num_pts == 0 {
+            return
+        }
+
+        self.shader.use_program();
+
+        camera.upload(pass, &mut self.view);
+
+        let formated_transform: Mat4<f32> = na::to_homogeneous(transform);
+        let formated_scale:     Mat3<f32> = Mat3::new(scale.x, 0.0, 0.0, 0.0, scale.y, 0.0, 0.0, 0.0, scale.z);
+
+        self.transform.upload(&formated_transform);
+        self.scale.upload(&formated_scale);
+
+        let mut viewport: [GLint, ..4] = [0, 0, 0, 0];
+        unsafe { verify!(gl::GetIntegerv(gl::VIEWPORT, &mut viewport[0])); }
+
+        self.viewport.upload(&Vec2::new(viewport[2] as f32, viewport[3] as f32));
+        self.width.upload(&style.width);
+        self.cap.upload(&match style.cap {
+            LineCap::Butt   => 0,
+            LineCap::Square => 1,
+            LineCap::Round  => 2
+        });
+        self.join.upload(&match style.join {
+            LineJoin::Miter => 0,
+            LineJoin::Round => 1
+        });
+        self.dash.upload(&match style.dash {
+            Some((dash, gap)) => Vec2::new(dash, gap),
+            None              => na::zero()
+        });
+
+        let mut attributes = [ &mut self.prev, &mut self.a, &mut self.b, &mut self.next, &mut self.color, &mut self.corner ];
+
+        for (i, attribute) in attributes.iter_mut().enumerate() {
+            attribute.enable();
+            attribute.bind_sub_buffer(vertices, 5, i);
+        }
+
+        verify!(gl::Disable(gl::CULL_FACE));
+        verify!(gl::PolygonMode(gl::FRONT_AND_BACK, gl::FILL));
+        verify!(gl::Enable(gl::BLEND));
+        verify!(gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA));
+
+        faces.bind();
+        verify!(gl::DrawElements(gl::TRIANGLES, num_pts as GLint, gl::UNSIGNED_INT, ptr::null()));
+        faces.unbind();
+
+        verify!(gl::Disable(gl::BLEND));
+
+        for attribute in attributes.iter_mut() {
+            attribute.disable();
+        }
+    }
+}
+
+/// Vertex shader used to draw thick lines.
+pub static THICK_LINES_VERTEX_SRC:   &'static str = A_VERY_LONG_STRING;
+/// Fragment shader used to draw thick lines.
+pub static THICK_LINES_FRAGMENT_SRC: &'static str = ANOTHER_VERY_LONG_STRING;
+
+const A_VERY_LONG_STRING: &'static str =
+"#version 120
+attribute vec3  prev;
+attribute vec3  a;
+attribute vec3  b;
+attribute vec3  next;
+attribute vec3  color;
+attribute vec3  corner;   // (0 at `a` and 1 at `b`, side of the segment, arc length at `a`).
+uniform   mat4  view;
+uniform   mat4  transform;
+uniform   mat3  scale;
+uniform   vec2  viewport;
+uniform   float width;
+uniform   int   cap;      // 0: butt, 1: square, 2: round.
+uniform   int   join;     // 0: miter, 1: round.
+varying   vec3  Color;
+varying   vec2  Local;    // Pixel coordinates relative to `a`, along and across the segment.
+varying   float Length;   // Pixel length of the segment.
+varying   vec2  Ends;     // Shape of each end. 0: square, 1: round, 2: not clipped.
+varying   vec2  Arcs;     // Arc lengths at `a` and `b`.
+
+vec4 project(vec3 p) {
+    return view * transform * mat4(scale) * vec4(p, 1.0);
+}
+
+// Moves `p` to the near plane if it is behind it.
+vec4 clip_near(vec4 p, vec4 q) {
+    float dp = p.z + p.w;
+    float dq = q.z + q.w;
+
+    if (dp < 0.0 && dq > dp) {
+        return mix(p, q, -dp / (dq - dp));
+    }
+
+    return p;
+}
+
+vec2 to_screen(vec4 p) {
+    return (p.xy / p.w * 0.5 + 0.5) * viewport;
+}
+
+float end_shape(bool joined) {
+    if (joined) {
+        return join == 0 ? 2.0 : 1.0;
+    }
+
+    return cap == 2 ? 1.0 : 0.0;
+}
+
+void main() {
+    vec4  ca    = project(a);
+    vec4  cb    = project(b);
+
+    if (ca.z + ca.w < 0.0 && cb.z + cb.w < 0.0) {
+        // The whole segment is behind the near plane: move its quad out of the clip volume.
+        gl_Position = vec4(2.0, 2.0, 2.0, 1.0);
+        return;
+    }
+
+    vec4  pa    = clip_near(ca, cb);
+    vec4  pb    = clip_near(cb, ca);
+    vec2  sa    = to_screen(pa);
+    vec2  sb    = to_screen(pb);
+    vec2  d     = sb - sa;
+    float len   = length(d);
+    vec2  dir   = len > 0.0 ? d / len : vec2(1.0, 0.0);
+    vec2  nrm   = vec2(-dir.y, dir.x);
+    float hw    = width * 0.5 + 1.0; // One more pixel for anti-aliasing.
+    bool  at_b  = corner.x > 0.5;
+    vec4  clip  = at_b ? pb : pa;
+    vec3  other = at_b ? next : prev;
+    vec2  offset = nrm * hw * corner.y;
+
+    Ends = vec2(end_shape(prev != a), end_shape(next != b));
+
+    float shape = at_b ? Ends.y : Ends.x;
+
+    if (shape > 1.5) {
+        // Miter join: move the vertex along the bisector of both segments normals.
+        vec4 co = project(other);
+
+        if (co.w > 0.0) {
+            vec2  od  = at_b ? to_screen(co) - sb : sa - to_screen(co);
+            float ol  = length(od);
+
+            if (ol > 0.0) {
+                vec2  m  = nrm + vec2(-od.y, od.x) / ol;
+                float ml = length(m);
+
+                if (ml > 1.0e-3) {
+                    m      = m / ml;
+                    offset = m * corner.y * hw / max(dot(m, nrm), 0.25);
+                }
+            }
+        }
+    }
+    else if (other != (at_b ? b : a) || cap != 0) {
+        // Square and round ends are extended by half the width.
+        offset += dir * (at_b ? hw : -hw);
+    }
+
+    vec2 screen = (at_b ? sb : sa) + offset;
+
+    Local  = vec2((at_b ? len : 0.0) + dot(offset, dir), dot(offset, nrm));
+    Length = len;
+    Arcs   = vec2(corner.z, corner.z + length(b - a));
+    Color  = color;
+
+    // Use w = 1 so that the pixel coordinates are interpolated linearly on the screen.
+    gl_Position = vec4(screen / viewport * 2.0 - 1.0, clip.z / clip.w, 1.0);
+}
+";
+
+const ANOTHER_VERY_LONG_STRING: &'static str =
+"#version 120
+uniform float width;
+uniform vec2  dash;       // Lengths of the dashes and of the gaps. Solid if the first is zero.
+varying vec3  Color;
+varying vec2  Local;
+varying float Length;
+varying vec2  Ends;
+varying vec2  Arcs;
+
+float end_distance(float x, float y, float shape) {
+    if (shape > 1.5) {
+        return abs(y);
+    }
+    else if (shape > 0.5) {
+        return length(vec2(x, y));
+    }
+    else {
+        return max(x, abs(y));
+    }
+}
+
+void main() {
+    float dist = abs(Local.y);
+
+    if (Local.x < 0.0) {
+        dist = end_distance(-Local.x, Local.y, Ends.x);
+    }
+    else if (Local.x > Length) {
+        dist = end_distance(Local.x - Length, Local.y, Ends.y);
+    }
+
+    float alpha = clamp(width * 0.5 + 0.5 - dist, 0.0, 1.0);
+
+    if (dash.x > 0.0) {
+        float t   = Length > 0.0 ? clamp(Local.x / Length, 0.0, 1.0) : 0.0;
+        float arc = mix(Arcs.x, Arcs.y, t);
+
+        if (mod(arc, dash.x + dash.y) > dash.x) {
+            discard;
+        }
+    }
+
+    if (alpha <= 0.0) {
+        discard;
+    }
+
+    gl_FragColor = vec4(Color, alpha);
+}
+";
@@ -5,7 +5,7 @@ use na::{Pnt3, Pnt2, Vec3, Mat3, Mat4, Iso3};
 use na;
 use resource::Material;
 use scene::ObjectData;
-use light::Light;
+use light::LightSource;
 use camera::Camera;
 use resource::{Mesh, Shader, ShaderAttribute, ShaderUniform};
 
@@ -47,7 +47,7 @@ impl Material for UvsMaterial {
               transform: &Iso3<f32>,
               scale:     &Vec3<f32>,
               camera:    &mut Camera,
-              _:         &Light,
+              _:         &[LightSource],
               data:      &ObjectData,
               mesh:      &mut Mesh) {
         if !data.surface_rendering_active() {
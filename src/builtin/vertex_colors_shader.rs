@@ -0,0 +1,115 @@
+use std::cmp;
+use std::cell::RefCell;
+use gl;
+use gl::types::*;
+use na::{Pnt3, Vec3, Mat3, Mat4, Iso3};
+use na;
+use camera::Camera;
+use resource::{GPUVector, Shader, ShaderAttribute, ShaderUniform};
+
+#[path = "../error.rs"]
+mod error;
+
+thread_local!(static KEY_VERTEX_COLORS_SHADER: RefCell<VertexColorsShader> = RefCell::new(VertexColorsShader::new()));
+
+/// A shader drawing lines or points with one color per vertex.
+///
+/// It is shared by every polyline and point cloud of the scene.
+pub struct VertexColorsShader {
+    shader:    Shader,
+    position:  ShaderAttribute<Pnt3<f32>>,
+    color:     ShaderAttribute<Pnt3<f32>>,
+    view:      ShaderUniform<Mat4<f32>>,
+    transform: ShaderUniform<Mat4<f32>>,
+    scale:     ShaderUniform<Mat3<f32>>
+}
+
+impl VertexColorsShader {
+    /// Creates a new vertex colors shader.
+    pub fn new() -> VertexColorsShader {
+        let mut shader = Shader::new_from_str(VERTEX_COLORS_VERTEX_SRC, VERTEX_COLORS_FRAGMENT_SRC);
+
+        shader.use_program();
+
+        VertexColorsShader {
+            position:  shader.get_attrib("position").unwrap(),
+            color:     shader.get_attrib("color").unwrap(),
+            view:      shader.get_uniform("view").unwrap(),
+            transform: shader.get_uniform("transform").unwrap(),
+            scale:     shader.get_uniform("scale").unwrap(),
+            shader:    shader
+        }
+    }
+
+    /// Mutably applies a function to the shader shared by the current thread.
+    pub fn get_global_shader<T>(f: |&mut VertexColorsShader| -> T) -> T {
+        KEY_VERTEX_COLORS_SHADER.with(|shader| f(shader.borrow_mut().deref_mut()))
+    }
+
+    /// Draws colored vertices with the primitive `mode` (e.g. `gl::LINE_STRIP` or `gl::POINTS`).
+    ///
+    /// Only the first `min(coords.len(), colors.len())` vertices are drawn.
+    pub fn render(&mut self,
+                  mode:      GLenum,
+                  pass:      uint,
+                  transform: &Iso3<f32>,
+                  scale:     &Vec3<f32>,
+                  camera:    &mut Camera,
+                  coords:    &mut GPUVector<Pnt3<GLfloat>>,
+                  colors:    &mut GPUVector<Pnt3<GLfloat>>) {
+        let num_pts = cmp::min(coords.len(), colors.len());
+
+        if num_pts == 0 {
+            return
+        }
+
+        self.shader.use_program();
+        self.position.enable();
+        self.color.enable();
+
+        camera.upload(pass, &mut self.view);
+
+        let formated_transform: Mat4<f32> = na::to_homogeneous(transform);
+        let formated_scale:     Mat3<f32> = Mat3::new(scale.x, 0.0, 0.0, 0.0, scale.y, 0.0, 0.0, 0.0, scale.z);
+
+        self.transform.upload(&formated_transform);
+        self.scale.upload(&formated_scale);
+
+        self.position.bind(coords);
+        self.color.bind(colors);
+
+        verify!(gl::DrawArrays(mode, 0, num_pts as GLint));
+
+        self.position.disable();
+        self.color.disable();
+    }
+}
+
+/// Vertex shader used to draw lines and points with per-vertex colors.
+pub static VERTEX_COLORS_VERTEX_SRC:   &'static str = A_VERY_LONG_STRING;
+/// Fragment shader used to draw lines and points with per-vertex colors.
+pub static VERTEX_COLORS_FRAGMENT_SRC: &'static str = ANOTHER_VERY_LONG_STRING;
+
+const A_VERY_LONG_STRING: &'static str =
+"#version 120
+attribute vec3 position;
+attribute vec3 color;
+varying   vec3 Color;
+uniform   mat4 view;
+uniform   mat4 transform;
+uniform   mat3 scale;
+
+void main() {
+    gl_Position = view * transform * mat4(scale) * vec4(position, 1.0);
+    Color       = color;
+}
+";
+
+const ANOTHER_VERY_LONG_STRING: &'static str =
+"#version 120
+varying vec3 Color;
+
+void main() {
+    gl_FragColor = vec4(Color, 1.0);
+}
+";
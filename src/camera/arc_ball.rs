@@ -1,22 +1,65 @@
 use std::f32;
+use std::fmt;
 use std::num::{Float, FloatMath};
-use glfw::{Key, Action};
+use glfw::Action;
 use glfw;
 use glfw::WindowEvent;
 use na::{Pnt3, Vec2, Vec3, Mat4, Iso3, PerspMat3};
 use na;
-use camera::Camera;
+use time;
+use camera::{Camera, Projection, CameraBindings};
+use camera::bindings;
+use scene::SceneNode;
+
+/// Duration, in seconds, of the transitions started by `frame_node`.
+static FRAMING_DURATION: f32 = 0.5;
+
+// An animated move of the camera. Every parameter is interpolated separately so that the eye
+// turns around the focus point instead of cutting through it.
+#[deriving(Clone, Show)]
+struct Transition {
+    from_at:    Pnt3<f32>,
+    from_yaw:   f32,
+    from_pitch: f32,
+    from_dist:  f32,
+    to_at:      Pnt3<f32>,
+    to_yaw:     f32,
+    to_pitch:   f32,
+    to_dist:    f32,
+    start:      u64,
+    duration:   f32
+}
+
+// The node framed by the reset key. This only exists because scene nodes are not `Show`.
+#[deriving(Clone)]
+struct ResetTarget(SceneNode);
+
+impl fmt::Show for ResetTarget {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "ResetTarget")
+    }
+}
 
 /// Arc-ball camera mode.
 ///
 /// An arc-ball camera is a camera rotating around a fixed point (the focus point) and always
-/// looking at it. The following inputs are handled:
+/// looking at it. The following inputs are handled by default:
 ///
 /// * Left button press + drag - rotates the camera around the focus point
 /// * Right button press + drag - translates the focus point on the plane orthogonal to the view
 /// direction
 /// * Scroll in/out - zoom in/out
-/// * Enter key - set the focus point to the origin
+/// * Enter key - set the focus point to the origin, or frame the node given to
+/// `set_frame_on_reset`
+///
+/// They can be changed with `set_bindings`.
+///
+/// Moves started by `animate_to` and `frame_node` are smoothly animated when the camera is
+/// updated. They are interrupted by any mouse input.
+///
+/// The projection can be switched between perspective and orthographic at any time. With an
+/// orthographic projection, the view extent at the focus point is the same as with the
+/// perspective projection so scrolling still zooms in and out.
 #[deriving(Clone, Show)]
 pub struct ArcBall {
     /// The focus point.
@@ -36,9 +79,13 @@ pub struct ArcBall {
     dist_step:  f32,
 
     projection:      PerspMat3<f32>,
+    projection_mode: Projection,
     proj_view:       Mat4<f32>,
     inv_proj_view:   Mat4<f32>,
-    last_cursor_pos: Vec2<f32>
+    last_cursor_pos: Vec2<f32>,
+    transition:      Option<Transition>,
+    bindings:        CameraBindings,
+    frame_on_reset:  Option<ResetTarget>
 }
 
 impl ArcBall {
@@ -62,9 +109,13 @@ impl ArcBall {
             pitch_step:      0.005,
             dist_step:       40.0,
             projection:      PerspMat3::new(800.0 / 600.0, fov, znear, zfar),
+            projection_mode: Projection::Perspective,
             proj_view:       na::zero(),
             inv_proj_view:   na::zero(),
-            last_cursor_pos: na::zero()
+            last_cursor_pos: na::zero(),
+            transition:      None,
+            bindings:        CameraBindings::new(),
+            frame_on_reset:  None
         };
 
         res.look_at_z(eye, at);
@@ -116,17 +167,152 @@ impl ArcBall {
         self.update_projviews();
     }
 
+    /// Sets the width-to-height ratio of the viewport.
+    ///
+    /// This is done automatically when the camera is attached to a window.
+    pub fn set_aspect(&mut self, aspect: f32) {
+        self.projection.set_aspect(aspect);
+        self.update_projviews();
+    }
+
+    /// The mouse buttons and keys controlling this camera.
+    pub fn bindings(&self) -> &CameraBindings {
+        &self.bindings
+    }
+
+    /// Mutably borrows the mouse buttons and keys controlling this camera.
+    pub fn bindings_mut(&mut self) -> &mut CameraBindings {
+        &mut self.bindings
+    }
+
+    /// Sets the mouse buttons and keys controlling this camera.
+    pub fn set_bindings(&mut self, bindings: CameraBindings) {
+        self.bindings = bindings
+    }
+
+    /// The node framed when the reset key is pressed, if any.
+    pub fn frame_on_reset(&self) -> Option<&SceneNode> {
+        self.frame_on_reset.as_ref().map(|t| &t.0)
+    }
+
+    /// Sets the node framed with `frame_node` when the reset key is pressed.
+    ///
+    /// With `None`, or if the node contains no visible vertex, the reset key sets the focus point
+    /// to the origin. Cameras created with `ArcBall::new` frame nothing, while the default camera
+    /// of a `Window` frames the whole scene.
+    pub fn set_frame_on_reset(&mut self, node: Option<SceneNode>) {
+        self.frame_on_reset = node.map(|n| ResetTarget(n))
+    }
+
+    /// The kind of projection of this camera.
+    pub fn projection_mode(&self) -> Projection {
+        self.projection_mode
+    }
+
+    /// Switches between the perspective and the orthographic projection.
+    ///
+    /// The objects at the focus point keep the same size on screen.
+    pub fn set_projection_mode(&mut self, mode: Projection) {
+        self.projection_mode = mode;
+        self.update_projviews();
+    }
+
+    /// The vertical field of view of the perspective projection, in radians.
+    pub fn fov(&self) -> f32 {
+        self.projection.fov()
+    }
+
+    /// The height of the region visible at the focus point.
+    pub fn view_height(&self) -> f32 {
+        Projection::view_height(&self.projection, self.dist)
+    }
+
     /// Move and orient the camera such that it looks at a specific point.
     pub fn look_at_z(&mut self, eye: Pnt3<f32>, at: Pnt3<f32>) {
+        let (yaw, pitch, dist) = ArcBall::spherical_coordinates(eye, at);
+
+        self.at         = at;
+        self.dist       = dist;
+        self.yaw        = yaw;
+        self.pitch      = pitch;
+        self.transition = None;
+        self.update_projviews();
+    }
+
+    /// Smoothly moves the camera such that it ends up at `eye`, looking at `at`, after
+    /// `duration` seconds.
+    ///
+    /// The camera turns around the focus point along the shortest path. The move is performed
+    /// when the camera is updated, i.e., once per frame when it is attached to a window.
+    pub fn animate_to(&mut self, eye: Pnt3<f32>, at: Pnt3<f32>, duration: f32) {
+        let (yaw, pitch, dist) = ArcBall::spherical_coordinates(eye, at);
+
+        // Turn by at most half a revolution.
+        let two_pi: f32 = 2.0 * f32::consts::PI;
+        let mut dyaw    = (yaw - self.yaw) % two_pi;
+
+        if dyaw > f32::consts::PI {
+            dyaw = dyaw - two_pi
+        }
+        else if dyaw < -f32::consts::PI {
+            dyaw = dyaw + two_pi
+        }
+
+        self.transition = Some(Transition {
+            from_at:    self.at,
+            from_yaw:   self.yaw,
+            from_pitch: self.pitch,
+            from_dist:  self.dist,
+            to_at:      at,
+            to_yaw:     self.yaw + dyaw,
+            to_pitch:   pitch,
+            to_dist:    dist,
+            start:      time::precise_time_ns(),
+            duration:   duration
+        });
+    }
+
+    /// Indicates whether the camera is being moved by `animate_to` or `frame_node`.
+    pub fn is_animating(&self) -> bool {
+        self.transition.is_some()
+    }
+
+    /// Smoothly moves the camera such that the whole content of `node` and its children is
+    /// visible.
+    ///
+    /// The camera keeps its orientation and looks at the center of the world bounding box of the
+    /// node. Returns `false`, leaving the camera untouched, if the node contains no visible
+    /// vertex.
+    pub fn frame_node(&mut self, node: &SceneNode) -> bool {
+        match node.world_bounding_box() {
+            Some((mins, maxs)) => {
+                let center = mins + (maxs - mins) / 2.0f32;
+                let radius = na::dist(&mins, &maxs) / 2.0;
+
+                // Fit the bounding sphere of the box in the narrowest field of view.
+                let half_fovy = self.projection.fov() / 2.0;
+                let half_fovx = (half_fovy.tan() * self.projection.aspect()).atan();
+                let dist      = radius / half_fovy.min(half_fovx).sin();
+                let dist      = dist.max(self.projection.znear() * 2.0);
+
+                let eye = self.eye();
+                let dir = if self.dist > 0.0 { (eye - self.at) / self.dist } else { Vec3::z() };
+
+                self.animate_to(center + dir * dist, center, FRAMING_DURATION);
+
+                true
+            },
+            None => false
+        }
+    }
+
+    // The yaw, pitch and distance of the camera at `eye` looking at `at`.
+    fn spherical_coordinates(eye: Pnt3<f32>, at: Pnt3<f32>) -> (f32, f32, f32) {
         let dist  = na::norm(&(eye - at));
-        let pitch = ((eye.y - at.y) / dist).acos();
+        let pitch = if dist > 0.0 { ((eye.y - at.y) / dist).acos() } else { f32::consts::PI / 2.0 };
         let yaw   = (eye.z - at.z).atan2(eye.x - at.x);
 
-        self.at    = at;
-        self.dist  = dist;
-        self.yaw   = yaw;
-        self.pitch = pitch;
-        self.update_projviews();
+        (yaw, pitch, dist)
     }
 
     /// Transformation applied by the camera without perspective.
@@ -146,6 +332,7 @@ impl ArcBall {
     }
 
     fn handle_left_button_displacement(&mut self, dpos: &Vec2<f32>) {
+        self.transition = None;
         self.yaw   = self.yaw   + dpos.x * self.yaw_step;
         self.pitch = self.pitch - dpos.y * self.pitch_step;
 
@@ -154,6 +341,8 @@ impl ArcBall {
     }
 
     fn handle_right_button_displacement(&mut self, dpos: &Vec2<f32>) {
+        self.transition = None;
+
         let eye       = self.eye();
         let dir       = na::normalize(&(self.at - eye));
         let tangent   = na::normalize(&na::cross(&Vec3::y(), &dir));
@@ -165,13 +354,16 @@ impl ArcBall {
     }
 
     fn handle_scroll(&mut self, off: f32) {
+        self.transition = None;
         self.dist = self.dist + self.dist_step * (off) / 120.0;
         self.update_restrictions();
         self.update_projviews();
     }
 
     fn update_projviews(&mut self) {
-        self.proj_view     = *self.projection.as_mat() * na::to_homogeneous(&na::inv(&self.view_transform()).unwrap());
+        let projection = self.projection_mode.to_mat(&self.projection, self.dist);
+
+        self.proj_view     = projection * na::to_homogeneous(&na::inv(&self.view_transform()).unwrap());
         self.inv_proj_view = na::inv(&self.proj_view).unwrap();
     }
 }
@@ -201,23 +393,35 @@ impl Camera for ArcBall {
             WindowEvent::CursorPos(x, y) => {
                 let curr_pos = Vec2::new(x as f32, y as f32);
 
-                if window.get_mouse_button(glfw::MouseButtonLeft) == Action::Press {
-                    let dpos = curr_pos - self.last_cursor_pos;
+                let dpos     = curr_pos - self.last_cursor_pos;
+
+                if bindings::is_dragging(window, &self.bindings.rotate) {
                     self.handle_left_button_displacement(&dpos)
                 }
 
-                if window.get_mouse_button(glfw::MouseButtonRight) == Action::Press {
-                    let dpos = curr_pos - self.last_cursor_pos;
+                if bindings::is_dragging(window, &self.bindings.pan) {
                     self.handle_right_button_displacement(&dpos)
                 }
 
+                if bindings::is_dragging(window, &self.bindings.zoom) {
+                    self.handle_scroll(dpos.y)
+                }
+
                 self.last_cursor_pos = curr_pos;
             },
-            WindowEvent::Key(Key::Enter, _, Action::Press, _) => {
-                self.at = na::orig();
-                self.update_projviews();
+            WindowEvent::Key(key, _, Action::Press, _) if Some(key) == self.bindings.reset => {
+                let framed = match self.frame_on_reset.clone() {
+                    Some(ResetTarget(node)) => self.frame_node(&node),
+                    None                    => false
+                };
+
+                if !framed {
+                    self.at         = na::orig();
+                    self.transition = None;
+                    self.update_projviews();
+                }
             },
-            WindowEvent::Scroll(_, off) => self.handle_scroll(off as f32),
+            WindowEvent::Scroll(_, off) if self.bindings.scroll_zoom => self.handle_scroll(off as f32),
             WindowEvent::FramebufferSize(w, h) => {
                 self.projection.set_aspect(w as f32 / h as f32);
                 self.update_projviews();
@@ -234,5 +438,29 @@ impl Camera for ArcBall {
         self.inv_proj_view
     }
 
-    fn update(&mut self, _: &glfw::Window) { }
+    fn update(&mut self, _: &glfw::Window) {
+        let finished = match self.transition {
+            Some(ref t) => {
+                let elapsed = (time::precise_time_ns() - t.start) as f32 / 1.0e9;
+                let s       = if t.duration > 0.0 { (elapsed / t.duration).min(1.0) } else { 1.0 };
+                // Ease in and out.
+                let s       = s * s * (3.0 - 2.0 * s);
+
+                self.at    = t.from_at + (t.to_at - t.from_at) * s;
+                self.yaw   = t.from_yaw   + (t.to_yaw   - t.from_yaw)   * s;
+                self.pitch = t.from_pitch + (t.to_pitch - t.from_pitch) * s;
+                self.dist  = t.from_dist  + (t.to_dist  - t.from_dist)  * s;
+
+                s >= 1.0
+            },
+            None => return
+        };
+
+        if finished {
+            self.transition = None;
+        }
+
+        self.update_restrictions();
+        self.update_projviews();
+    }
 }
@@ -0,0 +1,189 @@
+use glfw;
+use glfw::{Key, Action, MouseButton, Modifiers};
+
+/// A mouse button together with the modifier keys that must be held while dragging.
+#[deriving(Clone, Copy, PartialEq, Show)]
+pub struct MouseBinding {
+    /// The mouse button.
+    pub button:    MouseButton,
+    /// The modifier keys. If set, the binding is active only if exactly those modifiers are held.
+    /// If `None`, the binding is active whatever the modifiers held.
+    pub modifiers: Option<Modifiers>
+}
+
+impl MouseBinding {
+    /// A binding to a mouse button, whatever the modifier keys held.
+    pub fn new(button: MouseButton) -> MouseBinding {
+        MouseBinding {
+            button:    button,
+            modifiers: None
+        }
+    }
+
+    /// A binding to a mouse button while exactly the modifier keys `modifiers` are held.
+    ///
+    /// Use `Modifiers::empty()` for a binding that is inactive while any modifier key is held.
+    pub fn new_with_modifiers(button: MouseButton, modifiers: Modifiers) -> MouseBinding {
+        MouseBinding {
+            button:    button,
+            modifiers: Some(modifiers)
+        }
+    }
+
+    /// Indicates whether this binding is currently held on `window`.
+    pub fn is_pressed(&self, window: &glfw::Window) -> bool {
+        window.get_mouse_button(self.button) == Action::Press &&
+        self.modifiers.map_or(true, |m| current_modifiers(window) == m)
+    }
+}
+
+/// The mouse buttons and keys controlling a camera.
+///
+/// Any action can be disabled by setting it to `None` (or `false` for the scroll wheel). Each
+/// camera interprets the actions its own way:
+///
+/// * `ArcBall` and `Trackball` - rotate around and translate their focus point, and zoom in and
+/// out.
+/// * `FirstPerson` and `FirstPersonStereo` - look around, translate their position, and move
+/// forward and backward.
+/// * `Planar` - translates its view and zooms in and out. Its default bindings pan with the left
+/// button.
+///
+/// Presets for the conventions of some common tools are available.
+#[deriving(Clone, Copy, PartialEq, Show)]
+pub struct CameraBindings {
+    /// Drag to rotate the camera.
+    pub rotate:      Option<MouseBinding>,
+    /// Drag to translate the camera on the plane orthogonal to the view direction.
+    pub pan:         Option<MouseBinding>,
+    /// Drag vertically to zoom in and out.
+    pub zoom:        Option<MouseBinding>,
+    /// Whether the scroll wheel zooms in and out.
+    pub scroll_zoom: bool,
+    /// Key moving the focus point of an arc-ball or a trackball camera back to the origin.
+    pub reset:       Option<Key>,
+    /// Key held to move a first-person camera forward.
+    pub forward:     Option<Key>,
+    /// Key held to move a first-person camera backward.
+    pub backward:    Option<Key>,
+    /// Key held to move a first-person camera to the left.
+    pub left:        Option<Key>,
+    /// Key held to move a first-person camera to the right.
+    pub right:       Option<Key>
+}
+
+impl CameraBindings {
+    /// The default bindings of kiss3d.
+    ///
+    /// * Left button drag - rotate
+    /// * Right button drag - pan
+    /// * Scroll - zoom
+    /// * Enter - reset
+    /// * Arrow keys - move
+    pub fn new() -> CameraBindings {
+        CameraBindings {
+            rotate:      Some(MouseBinding::new(glfw::MouseButtonLeft)),
+            pan:         Some(MouseBinding::new(glfw::MouseButtonRight)),
+            zoom:        None,
+            scroll_zoom: true,
+            reset:       Some(Key::Enter),
+            forward:     Some(Key::Up),
+            backward:    Some(Key::Down),
+            left:        Some(Key::Left),
+            right:       Some(Key::Right)
+        }
+    }
+
+    /// Bindings with every action disabled.
+    pub fn none() -> CameraBindings {
+        CameraBindings {
+            rotate:      None,
+            pan:         None,
+            zoom:        None,
+            scroll_zoom: false,
+            reset:       None,
+            forward:     None,
+            backward:    None,
+            left:        None,
+            right:       None
+        }
+    }
+
+    /// Bindings similar to those of Blender.
+    ///
+    /// * Middle button drag - rotate
+    /// * Shift + middle button drag - pan
+    /// * Control + middle button drag - zoom
+    /// * Scroll - zoom
+    /// * Home - reset
+    /// * W, A, S, D - move
+    pub fn blender() -> CameraBindings {
+        CameraBindings {
+            rotate:      Some(MouseBinding::new_with_modifiers(glfw::MouseButtonMiddle, Modifiers::empty())),
+            pan:         Some(MouseBinding::new_with_modifiers(glfw::MouseButtonMiddle, glfw::Shift)),
+            zoom:        Some(MouseBinding::new_with_modifiers(glfw::MouseButtonMiddle, glfw::Control)),
+            scroll_zoom: true,
+            reset:       Some(Key::Home),
+            forward:     Some(Key::W),
+            backward:    Some(Key::S),
+            left:        Some(Key::A),
+            right:       Some(Key::D)
+        }
+    }
+
+    /// Bindings similar to those of Maya.
+    ///
+    /// * Alt + left button drag - rotate
+    /// * Alt + middle button drag - pan
+    /// * Alt + right button drag - zoom
+    /// * Scroll - zoom
+    /// * F - reset
+    /// * W, A, S, D - move
+    pub fn maya() -> CameraBindings {
+        CameraBindings {
+            rotate:      Some(MouseBinding::new_with_modifiers(glfw::MouseButtonLeft, glfw::Alt)),
+            pan:         Some(MouseBinding::new_with_modifiers(glfw::MouseButtonMiddle, glfw::Alt)),
+            zoom:        Some(MouseBinding::new_with_modifiers(glfw::MouseButtonRight, glfw::Alt)),
+            scroll_zoom: true,
+            reset:       Some(Key::F),
+            forward:     Some(Key::W),
+            backward:    Some(Key::S),
+            left:        Some(Key::A),
+            right:       Some(Key::D)
+        }
+    }
+}
+
+// Indicates whether the drag action `binding` is currently held on `window`.
+pub fn is_dragging(window: &glfw::Window, binding: &Option<MouseBinding>) -> bool {
+    binding.as_ref().map_or(false, |b| b.is_pressed(window))
+}
+
+// Indicates whether the key of the action `binding` is currently held on `window`.
+pub fn is_key_pressed(window: &glfw::Window, binding: &Option<Key>) -> bool {
+    binding.map_or(false, |k| window.get_key(k) == Action::Press)
+}
+
+// The modifier keys currently held on `window`.
+fn current_modifiers(window: &glfw::Window) -> Modifiers {
+    let mut res = Modifiers::empty();
+    let held    = |a: Key, b: Key| window.get_key(a) == Action::Press || window.get_key(b) == Action::Press;
+
+    if held(Key::LeftShift, Key::RightShift) {
+        res = res | glfw::Shift
+    }
+
+    if held(Key::LeftControl, Key::RightControl) {
+        res = res | glfw::Control
+    }
+
+    if held(Key::LeftAlt, Key::RightAlt) {
+        res = res | glfw::Alt
+    }
+
+    if held(Key::LeftSuper, Key::RightSuper) {
+        res = res | glfw::Super
+    }
+
+    res
+}
@@ -1,8 +1,46 @@
+use std::num::{Float, FloatMath};
 use glfw;
-use na::{Pnt2, Pnt3, Pnt4, Vec2, Vec3, Mat4, Iso3};
+use na::{Pnt2, Pnt3, Pnt4, Vec2, Vec3, Mat4, Iso3, PerspMat3};
 use na;
 use resource::ShaderUniform;
 
+/// The kind of projection applied by a camera.
+#[deriving(Clone, Copy, PartialEq, Show)]
+pub enum Projection {
+    /// Farther objects look smaller.
+    Perspective,
+    /// Objects look the same size regardless of their distance to the camera.
+    Orthographic
+}
+
+impl Projection {
+    /// The height of the region visible by `perspective` at the distance `dist` from the eye.
+    pub fn view_height(perspective: &PerspMat3<f32>, dist: f32) -> f32 {
+        2.0 * dist * (perspective.fov() / 2.0).tan()
+    }
+
+    /// The matrix of this kind of projection with the frustum of `perspective`.
+    ///
+    /// The orthographic projection shows the region visible by `perspective` at the distance
+    /// `dist` from the eye, so the objects at that distance keep the same size on screen. Its depth
+    /// range goes from `-zfar` to `zfar` so that zooming in does not cut through the scene.
+    pub fn to_mat(&self, perspective: &PerspMat3<f32>, dist: f32) -> Mat4<f32> {
+        match *self {
+            Projection::Perspective  => *perspective.as_mat(),
+            Projection::Orthographic => {
+                let h = Projection::view_height(perspective, dist);
+                let w = h * perspective.aspect();
+                let f = perspective.zfar();
+
+                Mat4::new(2.0 / w, 0.0,     0.0,      0.0,
+                          0.0,     2.0 / h, 0.0,      0.0,
+                          0.0,     0.0,     1.0 / f,  0.0,
+                          0.0,     0.0,     0.0,      1.0)
+            }
+        }
+    }
+}
+
 /// Trait every camera must implement.
 pub trait Camera {
     /*
@@ -87,3 +125,32 @@ pub trait Camera {
         (unprojected_begin, na::normalize(&(unprojected_end - unprojected_begin)))
     }
 }
+
+#[cfg(test)]
+mod test {
+    use na::{Pnt2, Pnt3, Vec2};
+    use na;
+    use camera::{ArcBall, Camera, Projection};
+
+    #[test]
+    fn rays_point_toward_the_focus_point() {
+        let eye  = Pnt3::new(1.0f32, 2.0, 10.0);
+        let at   = Pnt3::new(0.0f32, 0.5, 0.0);
+        let size = Vec2::new(800.0f32, 600.0);
+
+        for mode in [ Projection::Perspective, Projection::Orthographic ].iter() {
+            let mut camera = ArcBall::new(eye, at);
+            camera.set_projection_mode(*mode);
+
+            // Projected points have their origin at the bottom-left corner of the screen, and
+            // unprojected points at the top-left corner.
+            let screen      = camera.project(&at, &size);
+            let (orig, dir) = camera.unproject(&Pnt2::new(screen.x, size.y - screen.y), &size);
+            let t           = na::dot(&(at - orig), &dir);
+
+            assert!(na::approx_eq_eps(&screen, &(size * 0.5), &1.0e-2));
+            assert!(na::approx_eq_eps(&dir, &na::normalize(&(at - eye)), &1.0e-4));
+            assert!(na::approx_eq_eps(&(orig + dir * t), &at, &1.0e-3));
+        }
+    }
+}
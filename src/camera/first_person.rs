@@ -1,17 +1,23 @@
 use std::f32;
 use std::num::{Float, FloatMath};
 use glfw;
-use glfw::{Key, Action, WindowEvent};
+use glfw::WindowEvent;
 use na::{Translation, Pnt3, Vec2, Vec3, Mat4, Iso3, PerspMat3};
 use na;
-use camera::Camera;
+use camera::{Camera, CameraBindings};
+use camera::bindings;
 
 /// First-person camera mode.
 ///
+/// The following inputs are handled by default:
+///
 ///   * Left button press + drag - look around
 ///   * Right button press + drag - translates the camera position on the plane orthogonal to the
 ///   view direction
 ///   * Scroll in/out - zoom in/out
+///   * Arrow keys - move forward, backward, left and right
+///
+/// They can be changed with `set_bindings`.
 #[deriving(Show, Clone)]
 pub struct FirstPerson {
     eye:             Pnt3<f32>,
@@ -25,7 +31,8 @@ pub struct FirstPerson {
     projection:      PerspMat3<f32>,
     proj_view:       Mat4<f32>,
     inv_proj_view:   Mat4<f32>,
-    last_cursor_pos: Vec2<f32>
+    last_cursor_pos: Vec2<f32>,
+    bindings:        CameraBindings
 }
 
 impl FirstPerson {
@@ -51,6 +58,7 @@ impl FirstPerson {
             proj_view:       na::zero(),
             inv_proj_view:   na::zero(),
             last_cursor_pos: na::zero(),
+            bindings:        CameraBindings::new()
         };
 
         res.look_at_z(eye, at);
@@ -101,6 +109,21 @@ impl FirstPerson {
         self.yaw_step
     }
 
+    /// The mouse buttons and keys controlling this camera.
+    pub fn bindings(&self) -> &CameraBindings {
+        &self.bindings
+    }
+
+    /// Mutably borrows the mouse buttons and keys controlling this camera.
+    pub fn bindings_mut(&mut self) -> &mut CameraBindings {
+        &mut self.bindings
+    }
+
+    /// Sets the mouse buttons and keys controlling this camera.
+    pub fn set_bindings(&mut self, bindings: CameraBindings) {
+        self.bindings = bindings
+    }
+
     /// Changes the orientation and position of the camera to look at the specified point.
     pub fn look_at_z(&mut self, eye: Pnt3<f32>, at: Pnt3<f32>) {
         let dist  = na::norm(&(eye - at));
@@ -173,6 +196,11 @@ impl FirstPerson {
         let _ = na::inv(&self.proj_view).map(|inv_proj| self.inv_proj_view = inv_proj);
     }
 
+    /// The vertical field of view of the camera, in radians.
+    pub fn fov(&self) -> f32 {
+        self.projection.fov()
+    }
+
     /// The direction this camera is looking at.
     pub fn eye_dir(&self) -> Vec3<f32> {
         na::normalize(&(self.at() - self.eye))
@@ -229,19 +257,23 @@ impl Camera for FirstPerson {
             WindowEvent::CursorPos(x, y) => {
                 let curr_pos = Vec2::new(x as f32, y as f32);
 
-                if window.get_mouse_button(glfw::MouseButtonLeft) == Action::Press {
-                    let dpos = curr_pos - self.last_cursor_pos;
+                let dpos     = curr_pos - self.last_cursor_pos;
+
+                if bindings::is_dragging(window, &self.bindings.rotate) {
                     self.handle_left_button_displacement(&dpos)
                 }
 
-                if window.get_mouse_button(glfw::MouseButtonRight) == Action::Press {
-                    let dpos = curr_pos - self.last_cursor_pos;
+                if bindings::is_dragging(window, &self.bindings.pan) {
                     self.handle_right_button_displacement(&dpos)
                 }
 
+                if bindings::is_dragging(window, &self.bindings.zoom) {
+                    self.handle_scroll(-dpos.y / 10.0)
+                }
+
                 self.last_cursor_pos = curr_pos;
             },
-            WindowEvent::Scroll(_, off) => self.handle_scroll(off as f32),
+            WindowEvent::Scroll(_, off) if self.bindings.scroll_zoom => self.handle_scroll(off as f32),
             WindowEvent::FramebufferSize(w, h) => {
                 self.projection.set_aspect(w as f32 / h as f32);
                 self.update_projviews();
@@ -263,10 +295,10 @@ impl Camera for FirstPerson {
     }
 
     fn update(&mut self, window: &glfw::Window) {
-        let up    = window.get_key(Key::Up)    == Action::Press;
-        let down  = window.get_key(Key::Down)  == Action::Press;
-        let right = window.get_key(Key::Right) == Action::Press;
-        let left  = window.get_key(Key::Left)  == Action::Press;
+        let up    = bindings::is_key_pressed(window, &self.bindings.forward);
+        let down  = bindings::is_key_pressed(window, &self.bindings.backward);
+        let right = bindings::is_key_pressed(window, &self.bindings.right);
+        let left  = bindings::is_key_pressed(window, &self.bindings.left);
         let dir   = self.move_dir(up, down, right, left);
 
         let move_amount  = dir * self.move_step;
@@ -1,22 +1,28 @@
 use std::f32;
 use std::num::{Float, FloatMath};
 use glfw;
-use glfw::{Key, Action, WindowEvent};
+use glfw::WindowEvent;
 use gl;
 use na::{Pnt3, Pnt2, Vec2, Vec3, Mat4, Iso3, PerspMat3, Rotate};
 use na;
 use resource::ShaderUniform;
-use camera::Camera;
+use camera::{Camera, CameraBindings};
+use camera::bindings;
 
 #[path = "../error.rs"]
 mod error;
 
 /// First-person camera mode.
 ///
+/// The following inputs are handled by default:
+///
 ///   * Left button press + drag - look around
 ///   * Right button press + drag - translates the camera position on the plane orthogonal to the
 ///   view direction
 ///   * Scroll in/out - zoom in/out
+///   * Arrow keys - move forward, backward, left and right
+///
+/// They can be changed with `set_bindings`.
 #[deriving(Show)]
 pub struct FirstPersonStereo {
     /// The camera position
@@ -45,7 +51,8 @@ pub struct FirstPersonStereo {
     proj_view_left:  Mat4<f32>,
     proj_view_right: Mat4<f32>,
     inv_proj_view:   Mat4<f32>,
-    last_cursor_pos: Pnt2<f32>
+    last_cursor_pos: Pnt2<f32>,
+    bindings:        CameraBindings
 }
 
 impl FirstPersonStereo {
@@ -78,6 +85,7 @@ impl FirstPersonStereo {
             last_cursor_pos: na::orig(),
             proj_view_left:  na::zero(),
             proj_view_right: na::zero(),
+            bindings:        CameraBindings::new()
         };
 
         res.look_at_z(eye, at);
@@ -108,6 +116,21 @@ impl FirstPersonStereo {
         Pnt3::new(ax, ay, az)
     }
 
+    /// The mouse buttons and keys controlling this camera.
+    pub fn bindings(&self) -> &CameraBindings {
+        &self.bindings
+    }
+
+    /// Mutably borrows the mouse buttons and keys controlling this camera.
+    pub fn bindings_mut(&mut self) -> &mut CameraBindings {
+        &mut self.bindings
+    }
+
+    /// Sets the mouse buttons and keys controlling this camera.
+    pub fn set_bindings(&mut self, bindings: CameraBindings) {
+        self.bindings = bindings
+    }
+
     fn update_restrictions(&mut self) {
         if self.pitch <= 0.0001 {
             self.pitch = 0.0001
@@ -229,20 +252,23 @@ impl Camera for FirstPersonStereo {
         match *event {
             WindowEvent::CursorPos(x, y) => {
                 let curr_pos = Pnt2::new(x as f32, y as f32);
+                let dpos     = curr_pos - self.last_cursor_pos;
 
-                if window.get_mouse_button(glfw::MouseButtonLeft) == Action::Press {
-                    let dpos = curr_pos - self.last_cursor_pos;
+                if bindings::is_dragging(window, &self.bindings.rotate) {
                     self.handle_left_button_displacement(&dpos)
                 }
 
-                if window.get_mouse_button(glfw::MouseButtonRight) == Action::Press {
-                    let dpos = curr_pos - self.last_cursor_pos;
+                if bindings::is_dragging(window, &self.bindings.pan) {
                     self.handle_right_button_displacement(&dpos)
                 }
 
+                if bindings::is_dragging(window, &self.bindings.zoom) {
+                    self.handle_scroll(-dpos.y / 10.0)
+                }
+
                 self.last_cursor_pos = curr_pos;
             },
-            WindowEvent::Scroll(_, off) => self.handle_scroll(off as f32),
+            WindowEvent::Scroll(_, off) if self.bindings.scroll_zoom => self.handle_scroll(off as f32),
             WindowEvent::FramebufferSize(w, h) => {
                 self.projection.set_aspect(w as f32 / h as f32);
                 self.update_projviews();
@@ -268,19 +294,19 @@ impl Camera for FirstPersonStereo {
         let front: Vec3<f32> = t.rotate(&Vec3::z());
         let right: Vec3<f32> = t.rotate(&Vec3::x());
 
-        if window.get_key(Key::Up) == Action::Press {
+        if bindings::is_key_pressed(window, &self.bindings.forward) {
             self.eye = self.eye + front * self.move_step
         }
 
-        if window.get_key(Key::Down) == Action::Press {
+        if bindings::is_key_pressed(window, &self.bindings.backward) {
             self.eye = self.eye + front * (-self.move_step)
         }
 
-        if window.get_key(Key::Right) == Action::Press {
+        if bindings::is_key_pressed(window, &self.bindings.right) {
             self.eye = self.eye + right * (-self.move_step)
         }
 
-        if window.get_key(Key::Left) == Action::Press {
+        if bindings::is_key_pressed(window, &self.bindings.left) {
             self.eye = self.eye + right * self.move_step
         }
 
@@ -1,12 +1,20 @@
 //! Camera trait with some common implementations.
 
-pub use camera::camera::Camera;
+pub use camera::camera::{Camera, Projection};
 pub use camera::arc_ball::ArcBall;
+pub use camera::bindings::{CameraBindings, MouseBinding};
 pub use camera::first_person::FirstPerson;
 pub use camera::first_person_stereo::FirstPersonStereo;
+pub use camera::path::{CameraKeyframe, CameraPath, PathPlayer};
+pub use camera::planar::Planar;
+pub use camera::trackball::Trackball;
 
 #[doc(hidden)]
 pub mod camera;
 mod arc_ball;
+mod bindings;
 mod first_person;
 mod first_person_stereo;
+mod path;
+mod planar;
+mod trackball;
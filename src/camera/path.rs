@@ -0,0 +1,491 @@
+use std::io::fs::File;
+use std::io::{Writer, BufferedWriter, IoResult};
+use std::str::FromStr;
+use std::num::{Float, FloatMath};
+use glfw;
+use glfw::WindowEvent;
+use na::{Pnt3, Vec3, Mat4, Iso3, PerspMat3};
+use na;
+use camera::Camera;
+use loader::{LoadError, ParseError};
+
+/// The state of a camera at a given frame.
+#[deriving(Clone, Copy, PartialEq, Show)]
+pub struct CameraKeyframe {
+    /// The camera position.
+    pub eye: Pnt3<f32>,
+    /// The point the camera looks at.
+    pub at:  Pnt3<f32>,
+    /// The vertical field of view of the camera, in radians.
+    pub fov: f32
+}
+
+impl CameraKeyframe {
+    /// Creates a new keyframe.
+    pub fn new(eye: Pnt3<f32>, at: Pnt3<f32>, fov: f32) -> CameraKeyframe {
+        CameraKeyframe {
+            eye: eye,
+            at:  at,
+            fov: fov
+        }
+    }
+}
+
+/// A sequence of camera states, usually recorded once per frame.
+///
+/// Paths are saved as text files with one keyframe per line: the coordinates of `eye`, those of
+/// `at`, and the field of view. Lines starting with `#` are ignored.
+#[deriving(Clone, Show)]
+pub struct CameraPath {
+    keyframes: Vec<CameraKeyframe>
+}
+
+impl CameraPath {
+    /// Creates an empty path.
+    pub fn new() -> CameraPath {
+        CameraPath {
+            keyframes: Vec::new()
+        }
+    }
+
+    /// Creates a path from a list of keyframes.
+    pub fn new_with_keyframes(keyframes: Vec<CameraKeyframe>) -> CameraPath {
+        CameraPath {
+            keyframes: keyframes
+        }
+    }
+
+    /// The keyframes of this path.
+    #[inline]
+    pub fn keyframes<'a>(&'a self) -> &'a [CameraKeyframe] {
+        self.keyframes.as_slice()
+    }
+
+    /// The number of keyframes of this path.
+    #[inline]
+    pub fn len(&self) -> uint {
+        self.keyframes.len()
+    }
+
+    /// Appends a keyframe to this path.
+    #[inline]
+    pub fn push(&mut self, keyframe: CameraKeyframe) {
+        self.keyframes.push(keyframe)
+    }
+
+    /// Appends the current state of a camera to this path.
+    ///
+    /// Call this once per frame, after the scene is rendered, to record the camera moves.
+    pub fn record(&mut self, eye: Pnt3<f32>, at: Pnt3<f32>, fov: f32) {
+        self.push(CameraKeyframe::new(eye, at, fov))
+    }
+
+    /// Removes every keyframe.
+    pub fn clear(&mut self) {
+        self.keyframes.clear()
+    }
+
+    /// Loads a path from a file.
+    pub fn from_file(path: &Path) -> Result<CameraPath, LoadError> {
+        match File::open(path).and_then(|mut file| file.read_to_string()) {
+            Ok(s)  => CameraPath::from_str(s.as_slice()).map_err(|e| LoadError::Parse(e)),
+            Err(e) => Err(LoadError::Io(e))
+        }
+    }
+
+    /// Parses the content of a path file.
+    pub fn from_str(string: &str) -> Result<CameraPath, ParseError> {
+        let mut keyframes = Vec::new();
+
+        for (l, line) in string.lines_any().enumerate() {
+            let l     = l + 1;
+            let words: Vec<&str> = line.words().collect();
+
+            if words.is_empty() || words[0].starts_with("#") {
+                continue
+            }
+
+            if words.len() != 7 {
+                return Err(ParseError::new(l, "", format!("expected 7 numbers, found {}.", words.len())))
+            }
+
+            let mut comps = [ 0.0f32, ..7 ];
+
+            for (comp, word) in comps.iter_mut().zip(words.iter()) {
+                let x: Option<f32> = FromStr::from_str(*word);
+
+                match x {
+                    Some(x) => *comp = x,
+                    None    => return Err(ParseError::new(l, "", format!("failed to parse `{}' as a f32.", *word)))
+                }
+            }
+
+            keyframes.push(CameraKeyframe::new(Pnt3::new(comps[0], comps[1], comps[2]),
+                                               Pnt3::new(comps[3], comps[4], comps[5]),
+                                               comps[6]))
+        }
+
+        Ok(CameraPath::new_with_keyframes(keyframes))
+    }
+
+    /// Saves this path to a file.
+    pub fn save(&self, path: &Path) -> IoResult<()> {
+        let mut file = BufferedWriter::new(try!(File::create(path)));
+
+        self.write(&mut file)
+    }
+
+    /// Writes this path with the format read by `from_str`.
+    pub fn write(&self, w: &mut Writer) -> IoResult<()> {
+        try!(w.write_line("# kiss3d camera path: eye.x eye.y eye.z at.x at.y at.z fov"));
+
+        for k in self.keyframes.iter() {
+            try!(w.write_line(format!("{} {} {} {} {} {} {}",
+                                      k.eye.x, k.eye.y, k.eye.z,
+                                      k.at.x, k.at.y, k.at.z,
+                                      k.fov).as_slice()));
+        }
+
+        Ok(())
+    }
+
+    /// The camera state at the fractional keyframe index `t`.
+    ///
+    /// The camera position follows a Catmull-Rom spline through the keyframes. The view direction
+    /// is interpolated with a spherical linear interpolation, while the distance to the focus
+    /// point and the field of view are interpolated linearly. `t` is clamped to the extent of
+    /// the path.
+    ///
+    /// # Failures:
+    /// Fails if the path is empty.
+    pub fn sample(&self, t: f32) -> CameraKeyframe {
+        assert!(!self.keyframes.is_empty(), "Cannot sample an empty camera path.");
+
+        let last = self.keyframes.len() - 1;
+        let t    = t.max(0.0).min(last as f32);
+        let i    = t.floor() as uint;
+        let s    = t - i as f32;
+
+        let k0 = &self.keyframes[if i > 0 { i - 1 } else { 0 }];
+        let k1 = &self.keyframes[i];
+        let k2 = &self.keyframes[if i < last { i + 1 } else { last }];
+        let k3 = &self.keyframes[if i + 2 <= last { i + 2 } else { last }];
+
+        let eye = catmull_rom(k0.eye.to_vec(), k1.eye.to_vec(), k2.eye.to_vec(), k3.eye.to_vec(), s);
+        let eye = na::orig::<Pnt3<f32>>() + eye;
+
+        let d1   = k1.at - k1.eye;
+        let d2   = k2.at - k2.eye;
+        let n1   = na::norm(&d1);
+        let n2   = na::norm(&d2);
+        let dist = n1 + (n2 - n1) * s;
+        let dir  = if n1 > 0.0 && n2 > 0.0 { slerp(d1 / n1, d2 / n2, s) } else { Vec3::z() };
+
+        CameraKeyframe::new(eye, eye + dir * dist, k1.fov + (k2.fov - k1.fov) * s)
+    }
+}
+
+fn catmull_rom(p0: Vec3<f32>, p1: Vec3<f32>, p2: Vec3<f32>, p3: Vec3<f32>, s: f32) -> Vec3<f32> {
+    let s2 = s * s;
+    let s3 = s2 * s;
+
+    (p1 * 2.0 +
+     (p2 - p0) * s +
+     (p0 * 2.0 - p1 * 5.0 + p2 * 4.0 - p3) * s2 +
+     (p1 * 3.0 - p0 - p2 * 3.0 + p3) * s3) * 0.5
+}
+
+// Spherical linear interpolation between two unit vectors.
+fn slerp(a: Vec3<f32>, b: Vec3<f32>, s: f32) -> Vec3<f32> {
+    let cos   = na::dot(&a, &b).max(-1.0).min(1.0);
+    let angle = cos.acos();
+    let sin   = angle.sin();
+
+    if sin < 1.0e-5 {
+        let res = a + (b - a) * s;
+
+        if na::is_zero(&res) { a } else { na::normalize(&res) }
+    }
+    else {
+        a * (((1.0 - s) * angle).sin() / sin) + b * ((s * angle).sin() / sin)
+    }
+}
+
+/// A camera replaying a `CameraPath`.
+///
+/// The path advances by a fixed number of keyframes each time the camera is updated, i.e., once
+/// per frame when it is attached to a window. The playback thus does not depend on the frame
+/// rate, so that recording the same path twice produces identical videos. User inputs are
+/// ignored.
+///
+/// Headless windows do not update their camera since they have no glfw window: call `advance`
+/// before rendering each frame, or drive the playback with `set_time`.
+#[deriving(Clone, Show)]
+pub struct PathPlayer {
+    path:          CameraPath,
+    time:          f32,
+    speed:         f32,
+    looping:       bool,
+    started:       bool,
+    curr:          CameraKeyframe,
+    projection:    PerspMat3<f32>,
+    proj_view:     Mat4<f32>,
+    inv_proj_view: Mat4<f32>
+}
+
+impl PathPlayer {
+    /// Creates a camera replaying `path`.
+    ///
+    /// # Failures:
+    /// Fails if the path is empty.
+    pub fn new(path: CameraPath) -> PathPlayer {
+        PathPlayer::new_with_frustrum(path, 0.1, 1024.0)
+    }
+
+    /// Creates a camera replaying `path` with the given clipping planes.
+    ///
+    /// # Failures:
+    /// Fails if the path is empty.
+    pub fn new_with_frustrum(path: CameraPath, znear: f32, zfar: f32) -> PathPlayer {
+        let curr = path.sample(0.0);
+
+        let mut res = PathPlayer {
+            path:          path,
+            time:          0.0,
+            speed:         1.0,
+            looping:       false,
+            started:       false,
+            curr:          curr,
+            projection:    PerspMat3::new(800.0 / 600.0, curr.fov, znear, zfar),
+            proj_view:     na::zero(),
+            inv_proj_view: na::zero()
+        };
+
+        res.update_projviews();
+
+        res
+    }
+
+    /// The path replayed by this camera.
+    #[inline]
+    pub fn path<'a>(&'a self) -> &'a CameraPath {
+        &self.path
+    }
+
+    /// The current fractional keyframe index.
+    #[inline]
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+
+    /// Jumps to a fractional keyframe index.
+    pub fn set_time(&mut self, time: f32) {
+        self.time    = time;
+        self.started = true;
+        self.update_state();
+    }
+
+    /// The number of keyframes the path advances by per frame.
+    #[inline]
+    pub fn speed(&self) -> f32 {
+        self.speed
+    }
+
+    /// Sets the number of keyframes the path advances by per frame. The default value is 1.0.
+    #[inline]
+    pub fn set_speed(&mut self, speed: f32) {
+        self.speed = speed
+    }
+
+    /// Whether the playback restarts from the beginning once the end of the path is reached.
+    #[inline]
+    pub fn looping(&self) -> bool {
+        self.looping
+    }
+
+    /// Sets whether the playback restarts from the beginning once the end of the path is
+    /// reached. This is disabled by default.
+    #[inline]
+    pub fn set_looping(&mut self, looping: bool) {
+        self.looping = looping
+    }
+
+    /// Moves to the next frame of the playback.
+    ///
+    /// This is what updating the camera does. The first call shows the first keyframe.
+    pub fn advance(&mut self) {
+        if self.started {
+            self.time = self.time + self.speed;
+        }
+
+        self.started = true;
+        self.update_state();
+    }
+
+    /// Indicates whether the last keyframe has been reached.
+    pub fn is_finished(&self) -> bool {
+        !self.looping && self.time >= (self.path.len() - 1) as f32
+    }
+
+    /// The point the camera looks at.
+    pub fn at(&self) -> Pnt3<f32> {
+        self.curr.at
+    }
+
+    /// The vertical field of view of the camera, in radians.
+    pub fn fov(&self) -> f32 {
+        self.curr.fov
+    }
+
+    fn update_state(&mut self) {
+        let last = (self.path.len() - 1) as f32;
+
+        if self.looping && last > 0.0 {
+            self.time = self.time % last;
+        }
+
+        self.curr = self.path.sample(self.time);
+        self.projection.set_fov(self.curr.fov);
+        self.update_projviews();
+    }
+
+    fn update_projviews(&mut self) {
+        self.proj_view     = *self.projection.as_mat() * na::to_homogeneous(&na::inv(&self.view_transform()).unwrap());
+        self.inv_proj_view = na::inv(&self.proj_view).unwrap();
+    }
+}
+
+impl Camera for PathPlayer {
+    fn clip_planes(&self) -> (f32, f32) {
+        (self.projection.znear(), self.projection.zfar())
+    }
+
+    fn view_transform(&self) -> Iso3<f32> {
+        let mut id: Iso3<f32> = na::one();
+        id.look_at_z(&self.curr.eye, &self.curr.at, &Vec3::y());
+
+        id
+    }
+
+    fn eye(&self) -> Pnt3<f32> {
+        self.curr.eye
+    }
+
+    fn handle_event(&mut self, _: &glfw::Window, event: &WindowEvent) {
+        match *event {
+            WindowEvent::FramebufferSize(w, h) => {
+                self.projection.set_aspect(w as f32 / h as f32);
+                self.update_projviews();
+            },
+            _ => { }
+        }
+    }
+
+    fn transformation(&self) -> Mat4<f32> {
+        self.proj_view
+    }
+
+    fn inv_transformation(&self) -> Mat4<f32> {
+        self.inv_proj_view
+    }
+
+    fn update(&mut self, _: &glfw::Window) {
+        self.advance()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use na::{Pnt3, Vec3};
+    use na;
+    use super::{CameraKeyframe, CameraPath, PathPlayer, catmull_rom, slerp};
+
+    fn path() -> CameraPath {
+        CameraPath::new_with_keyframes(vec!(
+            CameraKeyframe::new(Pnt3::new(0.0f32, 0.0, -4.0), Pnt3::new(0.0, 0.0, 0.0), 0.5),
+            CameraKeyframe::new(Pnt3::new(4.0f32, 0.0, 0.0),  Pnt3::new(0.0, 0.0, 0.0), 1.0),
+            CameraKeyframe::new(Pnt3::new(0.0f32, 2.0, 4.0),  Pnt3::new(0.0, 1.0, 0.0), 0.25)))
+    }
+
+    #[test]
+    fn catmull_rom_interpolates_the_inner_points() {
+        let p0 = Vec3::new(0.0f32, 0.0, 0.0);
+        let p1 = Vec3::new(1.0f32, 2.0, 0.0);
+        let p2 = Vec3::new(3.0f32, 2.0, 1.0);
+        let p3 = Vec3::new(4.0f32, 0.0, 1.0);
+
+        assert!(na::approx_eq_eps(&catmull_rom(p0, p1, p2, p3, 0.0), &p1, &1.0e-5));
+        assert!(na::approx_eq_eps(&catmull_rom(p0, p1, p2, p3, 1.0), &p2, &1.0e-5));
+
+        // Evenly spaced aligned points are interpolated linearly.
+        let x = Vec3::x();
+        assert!(na::approx_eq_eps(&catmull_rom(x * 0.0f32, x, x * 2.0, x * 3.0, 0.25), &(x * 1.25), &1.0e-5));
+    }
+
+    #[test]
+    fn slerp_keeps_unit_vectors() {
+        let a = Vec3::x();
+        let b = Vec3::y();
+        let h = 0.5f32.sqrt();
+
+        assert!(na::approx_eq_eps(&slerp(a, b, 0.0), &a, &1.0e-5));
+        assert!(na::approx_eq_eps(&slerp(a, b, 1.0), &b, &1.0e-5));
+        assert!(na::approx_eq_eps(&slerp(a, b, 0.5), &Vec3::new(h, h, 0.0), &1.0e-5));
+        assert!(na::approx_eq_eps(&na::norm(&slerp(a, b, 0.3)), &1.0, &1.0e-5));
+
+        // Nearly identical vectors fall back to a normalized linear interpolation.
+        assert!(na::approx_eq_eps(&slerp(a, a, 0.5), &a, &1.0e-5));
+    }
+
+    #[test]
+    fn sample_goes_through_the_keyframes() {
+        let path = path();
+
+        for (i, k) in path.keyframes().iter().enumerate() {
+            let s = path.sample(i as f32);
+
+            assert!(na::approx_eq_eps(&s.eye, &k.eye, &1.0e-5));
+            assert!(na::approx_eq_eps(&s.at, &k.at, &1.0e-5));
+            assert!(na::approx_eq_eps(&s.fov, &k.fov, &1.0e-5));
+        }
+
+        // The time is clamped to the path extent.
+        assert_eq!(path.sample(-1.0), path.sample(0.0));
+        assert_eq!(path.sample(10.0), path.sample(2.0));
+
+        // Both keyframes look at the origin from a distance of 4.
+        let s = path.sample(0.5);
+        assert!(na::approx_eq_eps(&na::dist(&s.eye, &s.at), &4.0, &1.0e-5));
+        assert!(na::approx_eq_eps(&s.fov, &0.75, &1.0e-5));
+    }
+
+    #[test]
+    fn text_round_trip() {
+        let path     = path();
+        let mut text = Vec::new();
+
+        path.write(&mut text).unwrap();
+
+        let read = CameraPath::from_str(String::from_utf8(text).unwrap().as_slice()).unwrap();
+        assert_eq!(read.keyframes(), path.keyframes());
+
+        let e = CameraPath::from_str("# comment\n\n1 2 3 4 5 6").unwrap_err();
+        assert_eq!((e.line, e.reason.as_slice()), (3, "expected 7 numbers, found 6."));
+    }
+
+    #[test]
+    fn player_advances_by_its_speed() {
+        let mut player = PathPlayer::new(path());
+        player.set_speed(0.5);
+
+        player.advance();
+        assert_eq!(player.time(), 0.0);
+
+        player.advance();
+        assert_eq!(player.time(), 0.5);
+
+        player.set_looping(true);
+        player.set_time(2.5);
+        assert_eq!(player.time(), 0.5);
+    }
+}
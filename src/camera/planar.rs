@@ -0,0 +1,214 @@
+use std::num::Float;
+use glfw::Action;
+use glfw;
+use glfw::WindowEvent;
+use na::{Pnt2, Pnt3, Vec2, Vec3, Mat4, Iso3};
+use na;
+use camera::{Camera, CameraBindings, MouseBinding};
+use camera::bindings;
+
+/// Depth of the region visible by a planar camera, on each side of the `xy` plane.
+static PLANAR_DEPTH: f32 = 1000.0;
+
+/// A camera looking at the `xy` plane with an orthographic projection.
+///
+/// This is the camera of the 2D scene layer. The following inputs are handled by default:
+///
+/// * Left button press + drag - translates the view
+/// * Scroll in/out - zoom in/out toward the cursor
+///
+/// They can be changed with `set_bindings`. Only the `pan`, `zoom` and `scroll_zoom` bindings
+/// are used.
+///
+/// Objects with a greater `z` coordinate are drawn in front of the others.
+#[deriving(Clone, Show)]
+pub struct Planar {
+    /// The point at the center of the screen.
+    at:   Pnt2<f32>,
+    /// The number of pixels per world unit.
+    zoom: f32,
+
+    /// Relative increment of the zoom per unit scrolling. The default value is 0.1.
+    zoom_step: f32,
+
+    width:           f32,
+    height:          f32,
+    proj_view:       Mat4<f32>,
+    inv_proj_view:   Mat4<f32>,
+    last_cursor_pos: Vec2<f32>,
+    bindings:        CameraBindings
+}
+
+impl Planar {
+    /// Creates a new planar camera centered on `at` and showing `zoom` pixels per world unit.
+    pub fn new(at: Pnt2<f32>, zoom: f32) -> Planar {
+        // Nothing rotates a planar camera: the left button pans it instead.
+        let mut bindings = CameraBindings::new();
+        bindings.rotate  = None;
+        bindings.pan     = Some(MouseBinding::new(glfw::MouseButtonLeft));
+
+        let mut res = Planar {
+            at:              at,
+            zoom:            zoom,
+            zoom_step:       0.1,
+            width:           800.0,
+            height:          600.0,
+            proj_view:       na::zero(),
+            inv_proj_view:   na::zero(),
+            last_cursor_pos: na::zero(),
+            bindings:        bindings
+        };
+
+        res.update_projviews();
+
+        res
+    }
+
+    /// The point at the center of the screen.
+    pub fn at(&self) -> Pnt2<f32> {
+        self.at
+    }
+
+    /// Sets the point at the center of the screen.
+    pub fn set_at(&mut self, at: Pnt2<f32>) {
+        self.at = at;
+        self.update_projviews();
+    }
+
+    /// The number of pixels per world unit.
+    pub fn zoom(&self) -> f32 {
+        self.zoom
+    }
+
+    /// Sets the number of pixels per world unit.
+    pub fn set_zoom(&mut self, zoom: f32) {
+        self.zoom = zoom;
+        self.update_restrictions();
+        self.update_projviews();
+    }
+
+    /// The mouse buttons and keys controlling this camera.
+    pub fn bindings(&self) -> &CameraBindings {
+        &self.bindings
+    }
+
+    /// Mutably borrows the mouse buttons and keys controlling this camera.
+    pub fn bindings_mut(&mut self) -> &mut CameraBindings {
+        &mut self.bindings
+    }
+
+    /// Sets the mouse buttons and keys controlling this camera.
+    pub fn set_bindings(&mut self, bindings: CameraBindings) {
+        self.bindings = bindings
+    }
+
+    /// Sets the size of the viewport, in pixels.
+    ///
+    /// This is done automatically when the camera is attached to a window.
+    pub fn set_size(&mut self, width: f32, height: f32) {
+        self.width  = width;
+        self.height = height;
+        self.update_projviews();
+    }
+
+    /// Converts a point in screen coordinates to a point on the `xy` plane.
+    pub fn screen_to_plane(&self, screen_pos: &Pnt2<f32>) -> Pnt2<f32> {
+        Pnt2::new(self.at.x + (screen_pos.x - self.width  / 2.0) / self.zoom,
+                  self.at.y - (screen_pos.y - self.height / 2.0) / self.zoom)
+    }
+
+    fn update_restrictions(&mut self) {
+        if self.zoom < 0.00001 {
+            self.zoom = 0.00001
+        }
+    }
+
+    fn handle_left_button_displacement(&mut self, dpos: &Vec2<f32>) {
+        self.at.x = self.at.x - dpos.x / self.zoom;
+        self.at.y = self.at.y + dpos.y / self.zoom;
+
+        self.update_projviews();
+    }
+
+    fn handle_scroll(&mut self, cursor: &Pnt2<f32>, off: f32) {
+        // Keep the point under the cursor still.
+        let before = self.screen_to_plane(cursor);
+
+        self.zoom = self.zoom * (1.0 + self.zoom_step).powf(off);
+        self.update_restrictions();
+
+        let after = self.screen_to_plane(cursor);
+
+        self.at = self.at + (before - after);
+        self.update_projviews();
+    }
+
+    fn update_projviews(&mut self) {
+        let sx = 2.0 * self.zoom / self.width;
+        let sy = 2.0 * self.zoom / self.height;
+
+        self.proj_view = Mat4::new(sx,  0.0, 0.0,                 -self.at.x * sx,
+                                   0.0, sy,  0.0,                 -self.at.y * sy,
+                                   0.0, 0.0, -1.0 / PLANAR_DEPTH, 0.0,
+                                   0.0, 0.0, 0.0,                 1.0);
+        self.inv_proj_view = na::inv(&self.proj_view).unwrap();
+    }
+}
+
+impl Camera for Planar {
+    fn clip_planes(&self) -> (f32, f32) {
+        (0.0, 2.0 * PLANAR_DEPTH)
+    }
+
+    fn view_transform(&self) -> Iso3<f32> {
+        let mut id: Iso3<f32> = na::one();
+        id.look_at_z(&self.eye(), &Pnt3::new(self.at.x, self.at.y, 0.0), &Vec3::y());
+
+        id
+    }
+
+    fn eye(&self) -> Pnt3<f32> {
+        Pnt3::new(self.at.x, self.at.y, PLANAR_DEPTH)
+    }
+
+    fn handle_event(&mut self, window: &glfw::Window, event: &WindowEvent) {
+        match *event {
+            WindowEvent::CursorPos(x, y) => {
+                let curr_pos = Vec2::new(x as f32, y as f32);
+                let dpos     = curr_pos - self.last_cursor_pos;
+
+                if bindings::is_dragging(window, &self.bindings.pan) {
+                    self.handle_left_button_displacement(&dpos)
+                }
+
+                if bindings::is_dragging(window, &self.bindings.zoom) {
+                    let cursor = Pnt2::new(x as f32, y as f32);
+                    self.handle_scroll(&cursor, -dpos.y / 10.0)
+                }
+
+                self.last_cursor_pos = curr_pos;
+            },
+            WindowEvent::MouseButton(_, Action::Press, _) => {
+                // The cursor may have moved while the events were sent to another camera.
+                let (x, y) = window.get_cursor_pos();
+                self.last_cursor_pos = Vec2::new(x as f32, y as f32);
+            },
+            WindowEvent::Scroll(_, off) if self.bindings.scroll_zoom => {
+                let (x, y) = window.get_cursor_pos();
+                self.handle_scroll(&Pnt2::new(x as f32, y as f32), off as f32)
+            },
+            WindowEvent::FramebufferSize(w, h) => self.set_size(w as f32, h as f32),
+            _ => { }
+        }
+    }
+
+    fn transformation(&self) -> Mat4<f32> {
+        self.proj_view
+    }
+
+    fn inv_transformation(&self) -> Mat4<f32> {
+        self.inv_proj_view
+    }
+
+    fn update(&mut self, _: &glfw::Window) { }
+}
@@ -0,0 +1,312 @@
+use std::num::{Float, FloatMath};
+use glfw::Action;
+use glfw;
+use glfw::WindowEvent;
+use na::{Pnt3, Vec2, Vec3, Mat4, Iso3, PerspMat3, UnitQuat};
+use na;
+use camera::{Camera, Projection, CameraBindings};
+use camera::bindings;
+
+/// Trackball camera mode.
+///
+/// A trackball camera rotates freely around a focus point and always looks at it. Unlike the
+/// `ArcBall`, its orientation is not restricted: the model can be rolled and seen upside down.
+/// Rotations follow the virtual trackball of Shoemake and Bell. The following inputs are handled
+/// by default:
+///
+/// * Left button press + drag - rotates the camera around the focus point
+/// * Right button press + drag - translates the focus point on the plane orthogonal to the view
+/// direction
+/// * Scroll in/out - zoom in/out
+/// * Enter key - set the focus point to the origin
+///
+/// They can be changed with `set_bindings`.
+#[deriving(Clone, Show)]
+pub struct Trackball {
+    /// The focus point.
+    at:          Pnt3<f32>,
+    /// Distance from the camera to the `at` focus point.
+    dist:        f32,
+    /// Rotation from the camera local frame to the world frame. The camera looks along the local
+    /// `-z` axis, with the local `y` axis pointing up.
+    orientation: UnitQuat<f32>,
+
+    /// Increment of the distance per unit scrolling. The default value is 40.0.
+    dist_step:   f32,
+
+    projection:      PerspMat3<f32>,
+    projection_mode: Projection,
+    width:           f32,
+    height:          f32,
+    proj_view:       Mat4<f32>,
+    inv_proj_view:   Mat4<f32>,
+    last_cursor_pos: Vec2<f32>,
+    bindings:        CameraBindings
+}
+
+impl Trackball {
+    /// Create a new trackball camera.
+    pub fn new(eye: Pnt3<f32>, at: Pnt3<f32>) -> Trackball {
+        Trackball::new_with_frustrum(45.0f32.to_radians(), 0.1, 1024.0, eye, at)
+    }
+
+    /// Creates a new trackball camera with default sensitivity values.
+    pub fn new_with_frustrum(fov:    f32,
+                             znear:  f32,
+                             zfar:   f32,
+                             eye:    Pnt3<f32>,
+                             at:     Pnt3<f32>) -> Trackball {
+        let mut res = Trackball {
+            at:              Pnt3::new(0.0, 0.0, 0.0),
+            dist:            0.0,
+            orientation:     na::one(),
+            dist_step:       40.0,
+            projection:      PerspMat3::new(800.0 / 600.0, fov, znear, zfar),
+            projection_mode: Projection::Perspective,
+            width:           800.0,
+            height:          600.0,
+            proj_view:       na::zero(),
+            inv_proj_view:   na::zero(),
+            last_cursor_pos: na::zero(),
+            bindings:        CameraBindings::new()
+        };
+
+        res.look_at_z(eye, at);
+
+        res
+    }
+
+    /// The point the trackball is looking at.
+    pub fn at(&self) -> Pnt3<f32> {
+        self.at
+    }
+
+    /// Moves the focus point, keeping the orientation and the distance of the camera.
+    pub fn set_at(&mut self, at: Pnt3<f32>) {
+        self.at = at;
+        self.update_projviews();
+    }
+
+    /// The distance from the camera position to its view point.
+    pub fn dist(&self) -> f32 {
+        self.dist
+    }
+
+    /// Move the camera such that it is at a given distance from the view point.
+    pub fn set_dist(&mut self, dist: f32) {
+        self.dist = dist;
+
+        self.update_restrictions();
+        self.update_projviews();
+    }
+
+    /// The rotation from the camera local frame to the world frame.
+    ///
+    /// The camera looks along its local `-z` axis, with its local `y` axis pointing up.
+    pub fn orientation(&self) -> UnitQuat<f32> {
+        self.orientation
+    }
+
+    /// Sets the rotation from the camera local frame to the world frame.
+    pub fn set_orientation(&mut self, orientation: UnitQuat<f32>) {
+        self.orientation = orientation;
+        self.update_projviews();
+    }
+
+    /// The direction pointing up on the screen.
+    pub fn up(&self) -> Vec3<f32> {
+        na::rotate(&self.orientation, &Vec3::y())
+    }
+
+    /// Sets the width-to-height ratio of the viewport.
+    ///
+    /// This is done automatically when the camera is attached to a window.
+    pub fn set_aspect(&mut self, aspect: f32) {
+        self.projection.set_aspect(aspect);
+        self.update_projviews();
+    }
+
+    /// The vertical field of view of the perspective projection, in radians.
+    pub fn fov(&self) -> f32 {
+        self.projection.fov()
+    }
+
+    /// The mouse buttons and keys controlling this camera.
+    pub fn bindings(&self) -> &CameraBindings {
+        &self.bindings
+    }
+
+    /// Mutably borrows the mouse buttons and keys controlling this camera.
+    pub fn bindings_mut(&mut self) -> &mut CameraBindings {
+        &mut self.bindings
+    }
+
+    /// Sets the mouse buttons and keys controlling this camera.
+    pub fn set_bindings(&mut self, bindings: CameraBindings) {
+        self.bindings = bindings
+    }
+
+    /// The kind of projection of this camera.
+    pub fn projection_mode(&self) -> Projection {
+        self.projection_mode
+    }
+
+    /// Switches between the perspective and the orthographic projection.
+    ///
+    /// The objects at the focus point keep the same size on screen.
+    pub fn set_projection_mode(&mut self, mode: Projection) {
+        self.projection_mode = mode;
+        self.update_projviews();
+    }
+
+    /// The height of the region visible at the focus point.
+    pub fn view_height(&self) -> f32 {
+        Projection::view_height(&self.projection, self.dist)
+    }
+
+    /// Move and orient the camera such that it looks at a specific point, with the `y` axis
+    /// pointing up.
+    pub fn look_at_z(&mut self, eye: Pnt3<f32>, at: Pnt3<f32>) {
+        let dist = na::norm(&(eye - at));
+        let back = if dist > 0.0 { (eye - at) / dist } else { Vec3::z() };
+
+        // Tilt the local `z` axis toward `y`, then turn it around `y`.
+        let pitch = UnitQuat::new(Vec3::x() * -back.y.max(-1.0).min(1.0).asin());
+        let yaw   = UnitQuat::new(Vec3::y() * back.x.atan2(back.z));
+
+        self.at          = at;
+        self.dist        = dist;
+        self.orientation = yaw * pitch;
+        self.update_restrictions();
+        self.update_projviews();
+    }
+
+    fn update_restrictions(&mut self) {
+        if self.dist < 0.00001 {
+            self.dist = 0.00001
+        }
+    }
+
+    // Projects a point of the screen on the virtual trackball, in the camera local frame.
+    //
+    // Points far from the center are projected on a hyperbolic sheet instead of the sphere so
+    // that the rotation stays smooth (Bell's trackball).
+    fn project_on_trackball(&self, pos: &Vec2<f32>) -> Vec3<f32> {
+        let size = self.width.min(self.height);
+        let x    = (2.0 * pos.x - self.width) / size;
+        let y    = (self.height - 2.0 * pos.y) / size;
+        let d2   = x * x + y * y;
+
+        if d2 <= 0.5 {
+            Vec3::new(x, y, (1.0 - d2).sqrt())
+        }
+        else {
+            Vec3::new(x, y, 0.5 / d2.sqrt())
+        }
+    }
+
+    fn handle_left_button_displacement(&mut self, from: &Vec2<f32>, to: &Vec2<f32>) {
+        let p0   = na::normalize(&self.project_on_trackball(from));
+        let p1   = na::normalize(&self.project_on_trackball(to));
+        let axis = na::cross(&p0, &p1);
+        let sin  = na::norm(&axis);
+
+        if sin < 1.0e-7 {
+            return
+        }
+
+        let angle      = sin.atan2(na::dot(&p0, &p1));
+        let world_axis = na::rotate(&self.orientation, &(axis / sin));
+
+        // The model follows the cursor, so the camera turns the other way.
+        self.orientation = UnitQuat::new(world_axis * -angle) * self.orientation;
+        self.update_projviews();
+    }
+
+    fn handle_right_button_displacement(&mut self, dpos: &Vec2<f32>) {
+        let eye       = self.eye();
+        let dir       = na::normalize(&(self.at - eye));
+        let tangent   = na::normalize(&na::cross(&self.up(), &dir));
+        let bitangent = na::cross(&dir, &tangent);
+        let mult      = self.dist / 1000.0;
+
+        self.at = self.at + tangent * (dpos.x * mult) + bitangent * (dpos.y * mult);
+        self.update_projviews();
+    }
+
+    fn handle_scroll(&mut self, off: f32) {
+        self.dist = self.dist + self.dist_step * (off) / 120.0;
+        self.update_restrictions();
+        self.update_projviews();
+    }
+
+    fn update_projviews(&mut self) {
+        let projection = self.projection_mode.to_mat(&self.projection, self.dist);
+
+        self.proj_view     = projection * na::to_homogeneous(&na::inv(&self.view_transform()).unwrap());
+        self.inv_proj_view = na::inv(&self.proj_view).unwrap();
+    }
+}
+
+impl Camera for Trackball {
+    fn clip_planes(&self) -> (f32, f32) {
+        (self.projection.znear(), self.projection.zfar())
+    }
+
+    fn view_transform(&self) -> Iso3<f32> {
+        let mut id: Iso3<f32> = na::one();
+        id.look_at_z(&self.eye(), &self.at, &self.up());
+
+        id
+    }
+
+    fn eye(&self) -> Pnt3<f32> {
+        self.at + na::rotate(&self.orientation, &Vec3::z()) * self.dist
+    }
+
+    fn handle_event(&mut self, window: &glfw::Window, event: &WindowEvent) {
+        match *event {
+            WindowEvent::CursorPos(x, y) => {
+                let curr_pos = Vec2::new(x as f32, y as f32);
+                let dpos     = curr_pos - self.last_cursor_pos;
+
+                if bindings::is_dragging(window, &self.bindings.rotate) {
+                    let last_pos = self.last_cursor_pos;
+                    self.handle_left_button_displacement(&last_pos, &curr_pos)
+                }
+
+                if bindings::is_dragging(window, &self.bindings.pan) {
+                    self.handle_right_button_displacement(&dpos)
+                }
+
+                if bindings::is_dragging(window, &self.bindings.zoom) {
+                    self.handle_scroll(dpos.y)
+                }
+
+                self.last_cursor_pos = curr_pos;
+            },
+            WindowEvent::Key(key, _, Action::Press, _) if Some(key) == self.bindings.reset => {
+                self.at = na::orig();
+                self.update_projviews();
+            },
+            WindowEvent::Scroll(_, off) if self.bindings.scroll_zoom => self.handle_scroll(off as f32),
+            WindowEvent::FramebufferSize(w, h) => {
+                self.width  = w as f32;
+                self.height = h as f32;
+                self.projection.set_aspect(w as f32 / h as f32);
+                self.update_projviews();
+            },
+            _ => { }
+        }
+    }
+
+    fn transformation(&self) -> Mat4<f32> {
+        self.proj_view
+    }
+
+    fn inv_transformation(&self) -> Mat4<f32> {
+        self.inv_proj_view
+    }
+
+    fn update(&mut self, _: &glfw::Window) { }
+}
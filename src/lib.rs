@@ -20,12 +20,34 @@ with one-liners.
 Most features are one-liners.
 
 * open a window with a default arc-ball camera and a point light.
+* add directional, point and spot lights with their own color and intensity.
+* enable shadows cast by the first light.
 * a first-person camera is available too and user-defined cameras are possible.
+* switch the arc-ball camera between perspective and orthographic projections.
+* a trackball camera rotates freely to see models from any side, even upside down.
+* smoothly move the arc-ball camera, or frame an object so it fits the view.
+* configure the camera mouse buttons and keys, with Blender and Maya presets.
+* record camera moves to a file and replay them smoothly.
 * display boxes, spheres, cones, cylinders, quads and lines.
-* change an object color or texture.
+* draw rectangles, circles, convex polygons and polylines on a 2D layer with a pan and zoom camera.
+* draw thousands of copies of a mesh with instancing.
+* draw large polylines and point clouds kept on the GPU between frames.
+* draw thick, anti-aliased and dashed lines with a width in pixels.
+* draw point sprites with a size in pixels or world units, round or sphere-shaped, and textured.
+* draw Unicode text with kerning and fallback fonts.
+* measure, align and wrap text, with a background to keep it readable.
+* draw sharp text at any size with signed distance field fonts, with outlines and shadows.
+* attach text labels to points of the scene.
+* load obj, glTF 2.0, STL and PLY models, and save obj, STL and PLY files.
+* change an object color, opacity or texture.
+* repeat and filter textures with mipmaps and anisotropy, or create them from memory.
+* find the object under the mouse cursor.
 * change an object transform (we use the [nalgebra](http://nalgebra.org) library
   to do that).  An object cannot be scaled though.
 * create basic post-processing effects.
+* save screenshots to png (requires the `screenshot` feature) or ppm files.
+* render off-screen without any display server, e.g., for regression tests on a continuous
+  integration server (requires the `headless` feature and OSMesa).
 
 As an example, having a red, rotating cube with the light attached to the camera is as simple as:
 
@@ -62,7 +84,7 @@ Some controls are handled by default by the engine (they can be overridden by th
 * `scroll`: zoom in / zoom out.
 * `left click + drag`: look around.
 * `right click + drag`: translate the view point.
-* `enter`: look at the origin (0.0, 0.0, 0.0).
+* `enter`: frame the whole scene, or look at the origin (0.0, 0.0, 0.0) if it is empty.
 
 ## Compilation
 You will need the last nightly build of the [rust compiler](http://www.rust-lang.org)
@@ -102,6 +124,9 @@ extern crate "nalgebra" as na;
 extern crate ncollide;
 extern crate stb_image;
 extern crate freetype;
+#[cfg(feature = "screenshot")]
+extern crate image;
+extern crate serialize;
 
 mod error;
 pub mod window;
@@ -111,6 +136,8 @@ pub mod light;
 pub mod loader;
 pub mod line_renderer;
 pub mod point_renderer;
+pub mod thick_line_renderer;
+pub mod shadow_map;
 pub mod builtin;
 pub mod post_processing;
 pub mod resource;
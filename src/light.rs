@@ -1,9 +1,11 @@
 //! Lights.
 
-use na::Pnt3;
+use na::{Pnt3, Vec3};
+use na;
 use gl::types::GLfloat;
+use camera::Camera;
 
-/// The light configuration.
+/// The position of a light.
 #[deriving(Clone)]
 pub enum Light {
     /// A light with an absolute world position.
@@ -12,3 +14,109 @@ pub enum Light {
     StickToCamera
 }
 
+/// The kind of a light source.
+#[deriving(Clone, PartialEq, Show)]
+pub enum LightKind {
+    /// A light infinitely far away, lighting every object from the same direction.
+    Directional,
+    /// A light emitting uniformly in every direction from a single point.
+    Point,
+    /// A light emitting from a single point, restricted to a cone.
+    Spot
+}
+
+/// A light source.
+///
+/// Unused parameters are ignored depending on the light kind: directional lights have no
+/// `position` nor `attenuation`, point lights have no `direction` and only spot lights use
+/// `cutoff` and `falloff`.
+#[deriving(Clone)]
+pub struct LightSource {
+    /// The kind of this light.
+    pub kind:        LightKind,
+    /// The position of this light.
+    pub position:    Light,
+    /// The direction the light travels toward. If the light sticks to the camera, this is ignored
+    /// and the camera view direction is used instead.
+    pub direction:   Vec3<GLfloat>,
+    /// The color of this light.
+    pub color:       Pnt3<GLfloat>,
+    /// Factor multiplied to this light color.
+    pub intensity:   GLfloat,
+    /// The constant, linear, and quadratic attenuation factors.
+    pub attenuation: Vec3<GLfloat>,
+    /// Half-angle of the spot light cone, in radians.
+    pub cutoff:      GLfloat,
+    /// Exponent controlling how fast the spot light intensity decreases away from its axis.
+    pub falloff:     GLfloat
+}
+
+impl LightSource {
+    /// Creates a new white light with every parameter explicitly given.
+    fn new(kind: LightKind, position: Light, direction: Vec3<GLfloat>, cutoff: GLfloat, falloff: GLfloat) -> LightSource {
+        LightSource {
+            kind:        kind,
+            position:    position,
+            direction:   direction,
+            color:       Pnt3::new(1.0, 1.0, 1.0),
+            intensity:   1.0,
+            attenuation: Vec3::new(1.0, 0.0, 0.0),
+            cutoff:      cutoff,
+            falloff:     falloff
+        }
+    }
+
+    /// Creates a new white directional light.
+    ///
+    /// # Arguments
+    /// * `direction` - the direction the light travels toward.
+    pub fn new_directional(direction: Vec3<GLfloat>) -> LightSource {
+        LightSource::new(LightKind::Directional, Light::Absolute(na::orig()), na::normalize(&direction), 0.0, 0.0)
+    }
+
+    /// Creates a new white point light without attenuation.
+    pub fn new_point(position: Light) -> LightSource {
+        LightSource::new(LightKind::Point, position, -Vec3::y(), 0.0, 0.0)
+    }
+
+    /// Creates a new white spot light without attenuation.
+    ///
+    /// # Arguments
+    /// * `position`  - the position of the light.
+    /// * `direction` - the axis of the light cone.
+    /// * `cutoff`    - the half-angle of the light cone, in radians.
+    /// * `falloff`   - exponent of the intensity decrease from the cone axis to its border.
+    pub fn new_spot(position: Light, direction: Vec3<GLfloat>, cutoff: GLfloat, falloff: GLfloat) -> LightSource {
+        LightSource::new(LightKind::Spot, position, na::normalize(&direction), cutoff, falloff)
+    }
+
+    /// The world-space position and direction of this light, given the current camera.
+    pub fn position_and_direction(&self, camera: &Camera) -> (Pnt3<GLfloat>, Vec3<GLfloat>) {
+        match self.position {
+            Light::Absolute(ref p) => (p.clone(), self.direction),
+            Light::StickToCamera   => (camera.eye(), na::rotate(&camera.view_transform(), &Vec3::z()))
+        }
+    }
+
+    /// Sets the color of this light.
+    ///
+    /// Colors components must be on the range `[0.0, 1.0]`.
+    #[inline]
+    pub fn set_color(&mut self, r: GLfloat, g: GLfloat, b: GLfloat) {
+        self.color = Pnt3::new(r, g, b)
+    }
+
+    /// Sets the factor multiplied to this light color.
+    #[inline]
+    pub fn set_intensity(&mut self, intensity: GLfloat) {
+        self.intensity = intensity
+    }
+
+    /// Sets the attenuation of this light.
+    ///
+    /// The light intensity at a distance `d` is divided by `constant + linear * d + quadratic * d * d`.
+    #[inline]
+    pub fn set_attenuation(&mut self, constant: GLfloat, linear: GLfloat, quadratic: GLfloat) {
+        self.attenuation = Vec3::new(constant, linear, quadratic)
+    }
+}
@@ -0,0 +1,977 @@
+//! Simplistic glTF 2.0 loader.
+//!
+//! Both the JSON (`.gltf`) and the binary (`.glb`) formats are supported. Only triangle meshes,
+//! the node hierarchy, and the base color and alpha mode of materials are loaded. Skins,
+//! animations, cameras and sparse accessors are ignored.
+
+use std::mem;
+use std::num::Float;
+use std::rc::Rc;
+use std::cell::RefCell;
+use std::io::fs::File;
+use std::io::Reader;
+use serialize::json::Json;
+use serialize::base64::FromBase64;
+use na::{Pnt2, Pnt3, Vec3, Iso3};
+use na;
+use resource::{Mesh, TextureOptions, TextureWrap, TextureFilter};
+use loader::{LoadError, ParseError};
+
+const GLB_MAGIC:      u32 = 0x46546C67; // "glTF"
+const GLB_CHUNK_JSON: u32 = 0x4E4F534A; // "JSON"
+const GLB_CHUNK_BIN:  u32 = 0x004E4942; // "BIN\0"
+
+const BYTE:           uint = 5120;
+const UNSIGNED_BYTE:  uint = 5121;
+const SHORT:          uint = 5122;
+const UNSIGNED_SHORT: uint = 5123;
+const UNSIGNED_INT:   uint = 5125;
+const FLOAT:          uint = 5126;
+
+const TRIANGLES:      uint = 4;
+
+const NEAREST:                uint = 9728;
+const LINEAR:                 uint = 9729;
+const NEAREST_MIPMAP_NEAREST: uint = 9984;
+const LINEAR_MIPMAP_NEAREST:  uint = 9985;
+const NEAREST_MIPMAP_LINEAR:  uint = 9986;
+const LINEAR_MIPMAP_LINEAR:   uint = 9987;
+const CLAMP_TO_EDGE:          uint = 33071;
+const MIRRORED_REPEAT:        uint = 33648;
+const REPEAT:                 uint = 10497;
+
+/// An image referenced by a glTF file.
+pub enum GltfImage {
+    /// An image stored on an external file.
+    File(Path),
+    /// An encoded image (png, jpg, etc.) stored in memory.
+    Memory(Vec<u8>)
+}
+
+/// A texture of a glTF file: an image and the way it is sampled.
+#[deriving(Clone)]
+pub struct GltfTexture {
+    /// Index of the image on the document images.
+    pub image:   uint,
+    /// The wrap modes and filters of the texture sampler.
+    pub options: TextureOptions
+}
+
+/// Material informations read from a glTF file.
+#[deriving(Clone)]
+pub struct GltfMaterial {
+    /// Name of the material.
+    pub name:               String,
+    /// The base color.
+    pub base_color:         Pnt3<f32>,
+    /// The opacity. This is always 1.0 if the material alpha mode is `OPAQUE`.
+    pub alpha:              f32,
+    /// The opacity under which fragments are discarded if the material alpha mode is `MASK`.
+    pub alpha_cutoff:       Option<f32>,
+    /// Index of the base color texture on the document textures.
+    pub base_color_texture: Option<uint>
+}
+
+/// A part of a glTF mesh with a single material.
+pub struct GltfPrimitive {
+    /// The geometry of this primitive.
+    pub mesh:     Rc<RefCell<Mesh>>,
+    /// Index of the material of this primitive on the document materials.
+    pub material: Option<uint>
+}
+
+/// A node of the glTF scene graph.
+pub struct GltfNode {
+    /// Name of the node.
+    pub name:      String,
+    /// The node local translation and rotation.
+    pub transform: Iso3<f32>,
+    /// The node local scale.
+    pub scale:     Vec3<f32>,
+    /// Index of the node mesh on the document meshes.
+    pub mesh:      Option<uint>,
+    /// Indices of the node children on the document nodes.
+    pub children:  Vec<uint>
+}
+
+/// The content of a glTF file.
+pub struct GltfDocument {
+    /// The nodes.
+    pub nodes:     Vec<GltfNode>,
+    /// The meshes, each made of one or several primitives.
+    pub meshes:    Vec<Vec<GltfPrimitive>>,
+    /// The materials.
+    pub materials: Vec<GltfMaterial>,
+    /// The images.
+    pub images:    Vec<GltfImage>,
+    /// The textures.
+    pub textures:  Vec<GltfTexture>,
+    /// Indices of the root nodes of the default scene.
+    pub roots:     Vec<uint>
+}
+
+fn error<T>(tag: &str, reason: String) -> Result<T, ParseError> {
+    Err(ParseError::new(0, tag, reason))
+}
+
+/// Parses a `.gltf` or `.glb` file.
+///
+/// Returns the document and the list of warnings about ignored data.
+pub fn parse_file(path: &Path) -> Result<(GltfDocument, Vec<ParseError>), LoadError> {
+    match File::open(path).and_then(|mut file| file.read_to_end()) {
+        Ok(data) => parse(data.as_slice(), &path.dir_path()).map_err(|e| LoadError::Parse(e)),
+        Err(e)   => Err(LoadError::Io(e))
+    }
+}
+
+/// Parses the content of a `.gltf` or `.glb` file.
+///
+/// External buffers and images are looked for relatively to `base_dir`. Returns the document
+/// and the list of warnings about ignored data.
+pub fn parse(data: &[u8], base_dir: &Path) -> Result<(GltfDocument, Vec<ParseError>), ParseError> {
+    let mut warnings = Vec::new();
+
+    let (json, bin) = if data.len() >= 12 && read_u32(data) == GLB_MAGIC {
+        try!(parse_glb(data))
+    }
+    else {
+        (data, None)
+    };
+
+    let json = match String::from_utf8(json.to_vec()) {
+        Ok(s)  => s,
+        Err(_) => return error("", "the JSON content is not valid UTF-8.".to_string())
+    };
+
+    let json = match Json::from_str(json.as_slice()) {
+        Ok(j)  => j,
+        Err(e) => return error("", format!("invalid JSON: {}", e))
+    };
+
+    let buffers   = try!(load_buffers(&json, base_dir, bin));
+    let images    = try!(load_images(&json, base_dir, buffers.as_slice()));
+    let textures  = try!(load_textures(&json, images.len()));
+    let materials = try!(load_materials(&json, textures.len()));
+    let meshes    = try!(load_meshes(&json, buffers.as_slice(), materials.len(), &mut warnings));
+    let nodes     = try!(load_nodes(&json, meshes.len()));
+    let roots     = try!(load_roots(&json, nodes.as_slice()));
+
+    let document = GltfDocument {
+        nodes:     nodes,
+        meshes:    meshes,
+        materials: materials,
+        images:    images,
+        textures:  textures,
+        roots:     roots
+    };
+
+    Ok((document, warnings))
+}
+
+/*
+ *
+ * Binary container.
+ *
+ */
+fn read_u16(b: &[u8]) -> u16 {
+    b[0] as u16 | (b[1] as u16 << 8)
+}
+
+fn read_u32(b: &[u8]) -> u32 {
+    b[0] as u32 | (b[1] as u32 << 8) | (b[2] as u32 << 16) | (b[3] as u32 << 24)
+}
+
+fn parse_glb<'a>(data: &'a [u8]) -> Result<(&'a [u8], Option<Vec<u8>>), ParseError> {
+    let version = read_u32(data.slice_from(4));
+    let length  = read_u32(data.slice_from(8)) as uint;
+
+    if version != 2 {
+        return error("glb", format!("unsupported binary glTF version: {}.", version));
+    }
+
+    if length > data.len() {
+        return error("glb", "truncated file.".to_string());
+    }
+
+    let mut json   = None;
+    let mut bin    = None;
+    let mut offset = 12;
+
+    while offset + 8 <= length {
+        let chunk_len  = read_u32(data.slice_from(offset)) as uint;
+        let chunk_type = read_u32(data.slice_from(offset + 4));
+        let begin      = offset + 8;
+
+        if begin + chunk_len > length {
+            return error("glb", "truncated chunk.".to_string());
+        }
+
+        let chunk = data.slice(begin, begin + chunk_len);
+
+        if chunk_type == GLB_CHUNK_JSON && json.is_none() {
+            json = Some(chunk)
+        }
+        else if chunk_type == GLB_CHUNK_BIN && bin.is_none() {
+            bin = Some(chunk.to_vec())
+        }
+
+        // Chunks are aligned to 4 bytes.
+        offset = begin + (chunk_len + 3) / 4 * 4;
+    }
+
+    match json {
+        Some(json) => Ok((json, bin)),
+        None       => error("glb", "missing JSON chunk.".to_string())
+    }
+}
+
+/*
+ *
+ * JSON helpers.
+ *
+ */
+fn get_array<'a>(json: &'a Json, key: &str) -> &'a [Json] {
+    match json.find(key).and_then(|a| a.as_array()) {
+        Some(a) => a.as_slice(),
+        None    => &[]
+    }
+}
+
+fn get_uint(json: &Json, key: &str) -> Option<uint> {
+    json.find(key).and_then(|v| v.as_u64()).map(|v| v as uint)
+}
+
+fn get_f32s(json: &Json, key: &str) -> Option<Vec<f32>> {
+    json.find(key).and_then(|a| a.as_array()).map(|a| {
+        a.iter().map(|v| v.as_f64().unwrap_or(0.0) as f32).collect()
+    })
+}
+
+fn get_string(json: &Json, key: &str) -> String {
+    json.find(key).and_then(|s| s.as_string()).unwrap_or("").to_string()
+}
+
+fn get_element<'a>(json: &'a Json, key: &str, i: uint) -> Result<&'a Json, ParseError> {
+    match get_array(json, key).get(i) {
+        Some(elt) => Ok(elt),
+        None      => error(key, format!("index {} out of bounds.", i))
+    }
+}
+
+/*
+ *
+ * Buffers and images.
+ *
+ */
+fn load_uri(uri: &str, base_dir: &Path, tag: &str) -> Result<Vec<u8>, ParseError> {
+    if uri.starts_with("data:") {
+        match uri.find_str(";base64,") {
+            Some(i) => uri.slice_from(i + 8).from_base64().map_err(|e| {
+                ParseError::new(0, tag, format!("invalid base64 data: {}", e))
+            }),
+            None => error(tag, "only base64 data URIs are supported.".to_string())
+        }
+    }
+    else {
+        let mut path = base_dir.clone();
+        path.push(uri);
+
+        File::open(&path).and_then(|mut f| f.read_to_end()).map_err(|e| {
+            ParseError::new(0, tag, format!("{}: {}", path.display(), e))
+        })
+    }
+}
+
+fn load_buffers(json: &Json, base_dir: &Path, bin: Option<Vec<u8>>) -> Result<Vec<Vec<u8>>, ParseError> {
+    let mut bin = bin;
+    let mut res = Vec::new();
+
+    for (i, buffer) in get_array(json, "buffers").iter().enumerate() {
+        match buffer.find("uri").and_then(|u| u.as_string()) {
+            Some(uri) => res.push(try!(load_uri(uri, base_dir, "buffers"))),
+            None      => {
+                match bin.take() {
+                    Some(bin) => res.push(bin),
+                    None      => return error("buffers", format!("the buffer {} has no data.", i))
+                }
+            }
+        }
+    }
+
+    Ok(res)
+}
+
+fn buffer_view<'a>(json: &Json, buffers: &'a [Vec<u8>], id: uint) -> Result<(&'a [u8], Option<uint>), ParseError> {
+    let view   = try!(get_element(json, "bufferViews", id));
+    let buffer = match get_uint(view, "buffer").and_then(|b| buffers.get(b)) {
+        Some(b) => b,
+        None    => return error("bufferViews", format!("the buffer view {} has an invalid buffer.", id))
+    };
+    let offset = get_uint(view, "byteOffset").unwrap_or(0);
+    let length = get_uint(view, "byteLength").unwrap_or(0);
+
+    if offset + length > buffer.len() {
+        return error("bufferViews", format!("the buffer view {} is out of bounds.", id));
+    }
+
+    Ok((buffer.slice(offset, offset + length), get_uint(view, "byteStride")))
+}
+
+fn load_images(json: &Json, base_dir: &Path, buffers: &[Vec<u8>]) -> Result<Vec<GltfImage>, ParseError> {
+    let mut res = Vec::new();
+
+    for (i, image) in get_array(json, "images").iter().enumerate() {
+        match (image.find("uri").and_then(|u| u.as_string()), get_uint(image, "bufferView")) {
+            (Some(uri), _) => {
+                if uri.starts_with("data:") {
+                    res.push(GltfImage::Memory(try!(load_uri(uri, base_dir, "images"))))
+                }
+                else {
+                    let mut path = base_dir.clone();
+                    path.push(uri);
+                    res.push(GltfImage::File(path))
+                }
+            },
+            (None, Some(view)) => {
+                let (data, _) = try!(buffer_view(json, buffers, view));
+                res.push(GltfImage::Memory(data.to_vec()))
+            },
+            (None, None) => return error("images", format!("the image {} has no data.", i))
+        }
+    }
+
+    Ok(res)
+}
+
+/*
+ *
+ * Textures and materials.
+ *
+ */
+fn wrap_mode(sampler: Option<&Json>, key: &str) -> Result<TextureWrap, ParseError> {
+    match sampler.and_then(|s| get_uint(s, key)) {
+        None | Some(REPEAT)   => Ok(TextureWrap::Repeat),
+        Some(MIRRORED_REPEAT) => Ok(TextureWrap::MirroredRepeat),
+        Some(CLAMP_TO_EDGE)   => Ok(TextureWrap::ClampToEdge),
+        Some(mode)            => error("samplers", format!("unknown wrap mode: {}.", mode))
+    }
+}
+
+fn load_textures(json: &Json, num_images: uint) -> Result<Vec<GltfTexture>, ParseError> {
+    let mut res = Vec::new();
+
+    for (t, texture) in get_array(json, "textures").iter().enumerate() {
+        let image = match get_uint(texture, "source") {
+            Some(i) if i < num_images => i,
+            Some(_) => return error("textures", "texture source out of bounds.".to_string()),
+            None    => return error("textures", format!("the texture {} has no source.", t))
+        };
+
+        let sampler = match get_uint(texture, "sampler") {
+            Some(s) => Some(try!(get_element(json, "samplers", s))),
+            None    => None
+        };
+
+        let mag_filter = match sampler.and_then(|s| get_uint(s, "magFilter")) {
+            Some(NEAREST) => TextureFilter::Nearest,
+            _             => TextureFilter::Linear
+        };
+
+        // Without a minification filter, let the mipmaps smooth out distant textures.
+        let (min_filter, mipmaps) = match sampler.and_then(|s| get_uint(s, "minFilter")) {
+            Some(NEAREST)                                              => (TextureFilter::Nearest, false),
+            Some(LINEAR)                                               => (TextureFilter::Linear,  false),
+            Some(NEAREST_MIPMAP_NEAREST) | Some(NEAREST_MIPMAP_LINEAR) => (TextureFilter::Nearest, true),
+            Some(LINEAR_MIPMAP_NEAREST) | Some(LINEAR_MIPMAP_LINEAR)   => (TextureFilter::Linear,  true),
+            _                                                          => (TextureFilter::Linear,  true)
+        };
+
+        res.push(GltfTexture {
+            image:   image,
+            options: TextureOptions {
+                wrap_u:     try!(wrap_mode(sampler, "wrapS")),
+                wrap_v:     try!(wrap_mode(sampler, "wrapT")),
+                min_filter: min_filter,
+                mag_filter: mag_filter,
+                mipmaps:    mipmaps,
+                anisotropy: 1.0
+            }
+        })
+    }
+
+    Ok(res)
+}
+
+fn load_materials(json: &Json, num_textures: uint) -> Result<Vec<GltfMaterial>, ParseError> {
+    let mut res = Vec::new();
+
+    for material in get_array(json, "materials").iter() {
+        let pbr     = material.find("pbrMetallicRoughness");
+        let factor  = pbr.and_then(|p| get_f32s(p, "baseColorFactor")).unwrap_or(vec!(1.0, 1.0, 1.0, 1.0));
+        let texture = pbr.and_then(|p| p.find("baseColorTexture")).and_then(|t| get_uint(t, "index"));
+
+        if factor.len() != 4 {
+            return error("materials", "the base color factor must have 4 components.".to_string());
+        }
+
+        if texture.map(|t| t >= num_textures).unwrap_or(false) {
+            return error("materials", "base color texture index out of bounds.".to_string());
+        }
+
+        let (alpha, alpha_cutoff) = match material.find("alphaMode").and_then(|m| m.as_string()) {
+            None | Some("OPAQUE") => (1.0, None),
+            Some("BLEND")         => (factor[3], None),
+            Some("MASK")          => {
+                let cutoff = material.find("alphaCutoff").and_then(|c| c.as_f64()).unwrap_or(0.5);
+
+                (factor[3], Some(cutoff as f32))
+            },
+            Some(mode) => return error("materials", format!("unknown alpha mode: {}.", mode))
+        };
+
+        res.push(GltfMaterial {
+            name:               get_string(material, "name"),
+            base_color:         Pnt3::new(factor[0], factor[1], factor[2]),
+            alpha:              alpha,
+            alpha_cutoff:       alpha_cutoff,
+            base_color_texture: texture
+        })
+    }
+
+    Ok(res)
+}
+
+/*
+ *
+ * Meshes.
+ *
+ */
+struct Accessor<'a> {
+    data:       &'a [u8],
+    count:      uint,
+    ncomps:     uint,
+    comp_type:  uint,
+    comp_size:  uint,
+    stride:     uint,
+    normalized: bool
+}
+
+impl<'a> Accessor<'a> {
+    fn new(json: &Json, buffers: &'a [Vec<u8>], id: uint) -> Result<Accessor<'a>, ParseError> {
+        let accessor = try!(get_element(json, "accessors", id));
+
+        let view = match get_uint(accessor, "bufferView") {
+            Some(v) => v,
+            None    => return error("accessors", format!("the accessor {} has no buffer view.", id))
+        };
+
+        let (data, stride) = try!(buffer_view(json, buffers, view));
+
+        let ncomps = match accessor.find("type").and_then(|t| t.as_string()) {
+            Some("SCALAR") => 1,
+            Some("VEC2")   => 2,
+            Some("VEC3")   => 3,
+            Some("VEC4")   => 4,
+            _              => return error("accessors", format!("the accessor {} has an unsupported type.", id))
+        };
+
+        let comp_type = get_uint(accessor, "componentType").unwrap_or(0);
+        let comp_size = match comp_type {
+            BYTE | UNSIGNED_BYTE   => 1,
+            SHORT | UNSIGNED_SHORT => 2,
+            UNSIGNED_INT | FLOAT   => 4,
+            _                      => return error("accessors", format!("the accessor {} has an unsupported component type.", id))
+        };
+
+        let offset = get_uint(accessor, "byteOffset").unwrap_or(0);
+        let count  = get_uint(accessor, "count").unwrap_or(0);
+        let stride = stride.unwrap_or(ncomps * comp_size);
+
+        if count != 0 && offset + stride * (count - 1) + ncomps * comp_size > data.len() {
+            return error("accessors", format!("the accessor {} is out of bounds.", id));
+        }
+
+        Ok(Accessor {
+            data:       data.slice_from(cmp_min(offset, data.len())),
+            count:      count,
+            ncomps:     ncomps,
+            comp_type:  comp_type,
+            comp_size:  comp_size,
+            stride:     stride,
+            normalized: accessor.find("normalized").and_then(|n| n.as_boolean()).unwrap_or(false)
+        })
+    }
+
+    fn get(&self, i: uint, c: uint) -> f32 {
+        let b = self.data.slice_from(i * self.stride + c * self.comp_size);
+
+        let (val, max) = match self.comp_type {
+            BYTE           => (b[0] as i8 as f32, 127.0),
+            UNSIGNED_BYTE  => (b[0] as f32, 255.0),
+            SHORT          => (read_u16(b) as i16 as f32, 32767.0),
+            UNSIGNED_SHORT => (read_u16(b) as f32, 65535.0),
+            UNSIGNED_INT   => (read_u32(b) as f32, 4294967295.0),
+            _              => (unsafe { mem::transmute::<u32, f32>(read_u32(b)) }, 1.0)
+        };
+
+        if self.normalized {
+            (val / max).max(-1.0)
+        }
+        else {
+            val
+        }
+    }
+
+    fn get_index(&self, i: uint) -> Option<u32> {
+        let b = self.data.slice_from(i * self.stride);
+
+        match self.comp_type {
+            UNSIGNED_BYTE  => Some(b[0] as u32),
+            UNSIGNED_SHORT => Some(read_u16(b) as u32),
+            UNSIGNED_INT   => Some(read_u32(b)),
+            _              => None
+        }
+    }
+}
+
+fn cmp_min(a: uint, b: uint) -> uint {
+    if a < b { a } else { b }
+}
+
+fn load_meshes(json:          &Json,
+               buffers:       &[Vec<u8>],
+               num_materials: uint,
+               warnings:      &mut Vec<ParseError>)
+               -> Result<Vec<Vec<GltfPrimitive>>, ParseError> {
+    let mut res = Vec::new();
+
+    for (m, mesh) in get_array(json, "meshes").iter().enumerate() {
+        let mut primitives = Vec::new();
+
+        for primitive in get_array(mesh, "primitives").iter() {
+            if get_uint(primitive, "mode").unwrap_or(TRIANGLES) != TRIANGLES {
+                warnings.push(ParseError::new(0, "primitives", format!("non-triangle primitive of the mesh {} ignored.", m)));
+                continue;
+            }
+
+            let attribute = |name: &str| primitive.find("attributes").and_then(|a| get_uint(a, name));
+
+            let positions = match attribute("POSITION") {
+                Some(p) => try!(Accessor::new(json, buffers, p)),
+                None    => {
+                    warnings.push(ParseError::new(0, "primitives", format!("primitive without positions of the mesh {} ignored.", m)));
+                    continue;
+                }
+            };
+
+            if positions.ncomps != 3 {
+                return error("primitives", "positions must have 3 components.".to_string());
+            }
+
+            let count = positions.count;
+            let coords: Vec<Pnt3<f32>> = range(0u, count).map(|i| {
+                Pnt3::new(positions.get(i, 0), positions.get(i, 1), positions.get(i, 2))
+            }).collect();
+
+            let normals = match attribute("NORMAL") {
+                Some(n) => {
+                    let normals = try!(Accessor::new(json, buffers, n));
+
+                    if normals.ncomps != 3 || normals.count != count {
+                        warnings.push(ParseError::new(0, "primitives", format!("invalid normals of the mesh {} ignored.", m)));
+                        None
+                    }
+                    else {
+                        Some(range(0u, count).map(|i| {
+                            Vec3::new(normals.get(i, 0), normals.get(i, 1), normals.get(i, 2))
+                        }).collect())
+                    }
+                },
+                None => None
+            };
+
+            let uvs = match attribute("TEXCOORD_0") {
+                Some(t) => {
+                    let uvs = try!(Accessor::new(json, buffers, t));
+
+                    if uvs.ncomps != 2 || uvs.count != count {
+                        warnings.push(ParseError::new(0, "primitives", format!("invalid texture coordinates of the mesh {} ignored.", m)));
+                        None
+                    }
+                    else {
+                        // glTF texture coordinates have their origin at the top-left corner.
+                        Some(range(0u, count).map(|i| Pnt2::new(uvs.get(i, 0), 1.0 - uvs.get(i, 1))).collect())
+                    }
+                },
+                None => None
+            };
+
+            let indices: Vec<u32> = match get_uint(primitive, "indices") {
+                Some(i) => {
+                    let indices = try!(Accessor::new(json, buffers, i));
+                    let mut ids = Vec::with_capacity(indices.count);
+
+                    for i in range(0u, indices.count) {
+                        match indices.get_index(i) {
+                            Some(id) if (id as uint) < count => ids.push(id),
+                            _ => return error("primitives", format!("invalid vertex index on the mesh {}.", m))
+                        }
+                    }
+
+                    ids
+                },
+                None => range(0u, count).map(|i| i as u32).collect()
+            };
+
+            if indices.len() % 3 != 0 {
+                return error("primitives", format!("the number of indices of the mesh {} is not a multiple of 3.", m));
+            }
+
+            let faces = indices.as_slice().chunks(3).map(|f| Vec3::new(f[0], f[1], f[2])).collect();
+
+            let material = get_uint(primitive, "material");
+
+            if material.map(|i| i >= num_materials).unwrap_or(false) {
+                return error("primitives", format!("material index out of bounds on the mesh {}.", m));
+            }
+
+            primitives.push(GltfPrimitive {
+                mesh:     Rc::new(RefCell::new(Mesh::new(coords, faces, normals, uvs, false))),
+                material: material
+            })
+        }
+
+        res.push(primitives)
+    }
+
+    Ok(res)
+}
+
+/*
+ *
+ * Nodes.
+ *
+ */
+fn rotation_from_quaternion(x: f32, y: f32, z: f32, w: f32) -> Vec3<f32> {
+    let norm = (x * x + y * y + z * z + w * w).sqrt();
+
+    if norm == 0.0 {
+        return na::zero();
+    }
+
+    // Take the shortest path.
+    let sign       = if w < 0.0 { -1.0 } else { 1.0 };
+    let (x, y, z)  = (x * sign / norm, y * sign / norm, z * sign / norm);
+    let w          = w * sign / norm;
+    let sin_half   = (x * x + y * y + z * z).sqrt();
+
+    if sin_half < 1.0e-7 {
+        na::zero()
+    }
+    else {
+        let angle = 2.0 * sin_half.atan2(w);
+
+        Vec3::new(x, y, z) * (angle / sin_half)
+    }
+}
+
+fn decompose_matrix(m: &[f32]) -> (Iso3<f32>, Vec3<f32>) {
+    // Column-major.
+    let col   = |i: uint| Vec3::new(m[i * 4], m[i * 4 + 1], m[i * 4 + 2]);
+    let scale = Vec3::new(na::norm(&col(0)), na::norm(&col(1)), na::norm(&col(2)));
+
+    let r = |row: uint, c: uint| {
+        let s = if c == 0 { scale.x } else if c == 1 { scale.y } else { scale.z };
+
+        if s == 0.0 { 0.0 } else { m[c * 4 + row] / s }
+    };
+
+    let trace = r(0, 0) + r(1, 1) + r(2, 2);
+
+    let (x, y, z, w) = if trace > 0.0 {
+        let s = (trace + 1.0).sqrt() * 2.0;
+        ((r(2, 1) - r(1, 2)) / s, (r(0, 2) - r(2, 0)) / s, (r(1, 0) - r(0, 1)) / s, 0.25 * s)
+    }
+    else if r(0, 0) > r(1, 1) && r(0, 0) > r(2, 2) {
+        let s = (1.0 + r(0, 0) - r(1, 1) - r(2, 2)).sqrt() * 2.0;
+        (0.25 * s, (r(0, 1) + r(1, 0)) / s, (r(0, 2) + r(2, 0)) / s, (r(2, 1) - r(1, 2)) / s)
+    }
+    else if r(1, 1) > r(2, 2) {
+        let s = (1.0 + r(1, 1) - r(0, 0) - r(2, 2)).sqrt() * 2.0;
+        ((r(0, 1) + r(1, 0)) / s, 0.25 * s, (r(1, 2) + r(2, 1)) / s, (r(0, 2) - r(2, 0)) / s)
+    }
+    else {
+        let s = (1.0 + r(2, 2) - r(0, 0) - r(1, 1)).sqrt() * 2.0;
+        ((r(0, 2) + r(2, 0)) / s, (r(1, 2) + r(2, 1)) / s, 0.25 * s, (r(1, 0) - r(0, 1)) / s)
+    };
+
+    let translation = Vec3::new(m[12], m[13], m[14]);
+
+    (Iso3::new(translation, rotation_from_quaternion(x, y, z, w)), scale)
+}
+
+fn load_nodes(json: &Json, num_meshes: uint) -> Result<Vec<GltfNode>, ParseError> {
+    let all     = get_array(json, "nodes");
+    let mut res = Vec::new();
+
+    for node in all.iter() {
+        let (transform, scale) = match get_f32s(node, "matrix") {
+            Some(ref m) if m.len() == 16 => decompose_matrix(m.as_slice()),
+            _ => {
+                let t = get_f32s(node, "translation").unwrap_or(vec!(0.0, 0.0, 0.0));
+                let r = get_f32s(node, "rotation").unwrap_or(vec!(0.0, 0.0, 0.0, 1.0));
+                let s = get_f32s(node, "scale").unwrap_or(vec!(1.0, 1.0, 1.0));
+
+                if t.len() != 3 || r.len() != 4 || s.len() != 3 {
+                    return error("nodes", "invalid node transformation.".to_string());
+                }
+
+                (Iso3::new(Vec3::new(t[0], t[1], t[2]), rotation_from_quaternion(r[0], r[1], r[2], r[3])),
+                 Vec3::new(s[0], s[1], s[2]))
+            }
+        };
+
+        let mesh     = get_uint(node, "mesh");
+        let children: Vec<uint> = get_array(node, "children").iter().filter_map(|c| c.as_u64()).map(|c| c as uint).collect();
+
+        if mesh.map(|m| m >= num_meshes).unwrap_or(false) {
+            return error("nodes", "mesh index out of bounds.".to_string());
+        }
+
+        if children.iter().any(|c| *c >= all.len()) {
+            return error("nodes", "child index out of bounds.".to_string());
+        }
+
+        res.push(GltfNode {
+            name:      get_string(node, "name"),
+            transform: transform,
+            scale:     scale,
+            mesh:      mesh,
+            children:  children
+        })
+    }
+
+    Ok(res)
+}
+
+fn load_roots(json: &Json, nodes: &[GltfNode]) -> Result<Vec<uint>, ParseError> {
+    let scene = get_uint(json, "scene").unwrap_or(0);
+
+    match get_array(json, "scenes").get(scene) {
+        Some(scene) => {
+            let roots: Vec<uint> = get_array(scene, "nodes").iter().filter_map(|n| n.as_u64()).map(|n| n as uint).collect();
+
+            if roots.iter().any(|r| *r >= nodes.len()) {
+                return error("scenes", "node index out of bounds.".to_string());
+            }
+
+            Ok(roots)
+        },
+        None => {
+            // No scene: every node without parent is a root.
+            let mut is_child = Vec::from_elem(nodes.len(), false);
+
+            for node in nodes.iter() {
+                for c in node.children.iter() {
+                    is_child[*c] = true;
+                }
+            }
+
+            Ok(range(0u, nodes.len()).filter(|i| !is_child[*i]).collect())
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::mem;
+    use serialize::base64::{ToBase64, STANDARD};
+    use na::{Pnt2, Pnt3};
+    use loader::ParseError;
+    use resource::{TextureWrap, TextureFilter};
+    use super::{parse, GltfDocument};
+
+    fn push_u32(out: &mut Vec<u8>, val: u32) {
+        for i in range(0u, 4) {
+            out.push((val >> (8 * i)) as u8)
+        }
+    }
+
+    fn push_f32s(out: &mut Vec<u8>, vals: &[f32]) {
+        for v in vals.iter() {
+            push_u32(out, unsafe { mem::transmute::<f32, u32>(*v) })
+        }
+    }
+
+    // Four padding bytes, then three vertices with interleaved positions and texture coordinates.
+    fn interleaved() -> Vec<u8> {
+        let mut res = vec!(0u8, 0, 0, 0);
+
+        push_f32s(&mut res, &[0.0, 0.0, 0.0, 0.0, 0.0]);
+        push_f32s(&mut res, &[1.0, 0.0, 0.0, 1.0, 0.0]);
+        push_f32s(&mut res, &[0.0, 1.0, 0.0, 0.0, 1.0]);
+
+        res
+    }
+
+    fn interleaved_gltf(uv_count: uint) -> String {
+        format!(r#"{{
+            "buffers":     [ {{ "uri": "data:application/octet-stream;base64,{}", "byteLength": 64 }} ],
+            "bufferViews": [ {{ "buffer": 0, "byteOffset": 4, "byteLength": 60, "byteStride": 20 }} ],
+            "accessors":   [ {{ "bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3" }},
+                             {{ "bufferView": 0, "byteOffset": 12, "componentType": 5126, "count": {}, "type": "VEC2" }} ],
+            "meshes":      [ {{ "primitives": [ {{ "attributes": {{ "POSITION": 0, "TEXCOORD_0": 1 }} }} ] }} ],
+            "nodes":       [ {{ "mesh": 0 }} ]
+        }}"#, interleaved().as_slice().to_base64(STANDARD), uv_count)
+    }
+
+    fn glb(json: &str, bin: &[u8]) -> Vec<u8> {
+        let json_len = (json.len() + 3) / 4 * 4;
+        let mut res  = Vec::new();
+
+        push_u32(&mut res, 0x46546C67);
+        push_u32(&mut res, 2);
+        push_u32(&mut res, (12 + 8 + json_len + 8 + bin.len()) as u32);
+        push_u32(&mut res, json_len as u32);
+        push_u32(&mut res, 0x4E4F534A);
+        res.push_all(json.as_bytes());
+
+        for _ in range(json.len(), json_len) {
+            res.push(b' ')
+        }
+
+        push_u32(&mut res, bin.len() as u32);
+        push_u32(&mut res, 0x004E4942);
+        res.push_all(bin);
+
+        res
+    }
+
+    fn document(data: &[u8]) -> GltfDocument {
+        match parse(data, &Path::new(".")) {
+            Ok((doc, _)) => doc,
+            Err(e)       => panic!("the glTF file should have been accepted: {}", e.reason)
+        }
+    }
+
+    fn parse_error(data: &[u8]) -> ParseError {
+        match parse(data, &Path::new(".")) {
+            Ok(_)  => panic!("the glTF file should have been rejected."),
+            Err(e) => e
+        }
+    }
+
+    #[test]
+    fn interleaved_accessors() {
+        let doc  = document(interleaved_gltf(3).as_bytes());
+        let mesh = doc.meshes[0][0].mesh.borrow();
+
+        let coords = mesh.coords().read().unwrap();
+        let uvs    = mesh.uvs().read().unwrap();
+
+        assert_eq!(doc.roots, vec!(0u));
+        assert_eq!(coords.data().as_ref().unwrap().as_slice(),
+                   [ Pnt3::new(0.0f32, 0.0, 0.0), Pnt3::new(1.0, 0.0, 0.0), Pnt3::new(0.0, 1.0, 0.0) ].as_slice());
+        // The v texture coordinates are flipped.
+        assert_eq!(uvs.data().as_ref().unwrap().as_slice(),
+                   [ Pnt2::new(0.0f32, 1.0), Pnt2::new(1.0, 1.0), Pnt2::new(0.0, 0.0) ].as_slice());
+    }
+
+    #[test]
+    fn accessor_out_of_bounds() {
+        let e = parse_error(interleaved_gltf(4).as_bytes());
+
+        assert_eq!(e.tag.as_slice(), "accessors");
+        assert_eq!(e.reason.as_slice(), "the accessor 1 is out of bounds.");
+
+        let json = interleaved_gltf(3).replace(r#""byteOffset": 4, "byteLength": 60"#, r#""byteOffset": 8, "byteLength": 60"#);
+        let e    = parse_error(json.as_bytes());
+
+        assert_eq!(e.tag.as_slice(), "bufferViews");
+        assert_eq!(e.reason.as_slice(), "the buffer view 0 is out of bounds.");
+    }
+
+    #[test]
+    fn glb_chunks() {
+        let mut bin = Vec::new();
+        push_f32s(&mut bin, &[0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0]);
+
+        let json = r#"{
+            "buffers":     [ { "byteLength": 36 } ],
+            "bufferViews": [ { "buffer": 0, "byteLength": 36 } ],
+            "accessors":   [ { "bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3" } ],
+            "meshes":      [ { "primitives": [ { "attributes": { "POSITION": 0 } } ] } ],
+            "nodes":       [ { "mesh": 0 } ]
+        }"#;
+
+        let doc = document(glb(json, bin.as_slice()).as_slice());
+        assert_eq!(doc.meshes[0][0].mesh.borrow().num_pts(), 3);
+    }
+
+    #[test]
+    fn malformed_glb() {
+        let valid = glb("{}", &[]);
+
+        let mut version = valid.clone();
+        version[4] = 1;
+        assert_eq!(parse_error(version.as_slice()).reason.as_slice(), "unsupported binary glTF version: 1.");
+
+        let truncated = valid.slice_to(valid.len() - 1);
+        assert_eq!(parse_error(truncated).reason.as_slice(), "truncated file.");
+
+        let mut chunk = valid.clone();
+        chunk[12] = 200;
+        assert_eq!(parse_error(chunk.as_slice()).reason.as_slice(), "truncated chunk.");
+
+        let mut no_json = valid.clone();
+        no_json[16] = 0;
+        assert_eq!(parse_error(no_json.as_slice()).reason.as_slice(), "missing JSON chunk.");
+    }
+
+    #[test]
+    fn materials_and_samplers() {
+        let json = r#"{
+            "images":    [ { "uri": "image.png" } ],
+            "samplers":  [ { "wrapS": 33071, "wrapT": 33648, "magFilter": 9728, "minFilter": 9729 } ],
+            "textures":  [ { "source": 0 }, { "source": 0, "sampler": 0 } ],
+            "materials": [
+                { "alphaMode": "MASK", "alphaCutoff": 0.25, "pbrMetallicRoughness": { "baseColorTexture": { "index": 0 } } },
+                { "alphaMode": "MASK", "pbrMetallicRoughness": { "baseColorFactor": [ 1.0, 0.5, 0.0, 0.75 ] } },
+                { "alphaMode": "BLEND", "pbrMetallicRoughness": { "baseColorFactor": [ 1.0, 1.0, 1.0, 0.75 ] } },
+                { "pbrMetallicRoughness": { "baseColorFactor": [ 1.0, 1.0, 1.0, 0.75 ] } }
+            ]
+        }"#;
+
+        let doc = document(json.as_bytes());
+
+        assert_eq!(doc.materials[0].alpha_cutoff, Some(0.25));
+        assert_eq!(doc.materials[0].base_color_texture, Some(0));
+        assert_eq!(doc.materials[1].alpha_cutoff, Some(0.5));
+        assert_eq!(doc.materials[1].alpha, 0.75);
+        assert_eq!(doc.materials[1].base_color, Pnt3::new(1.0, 0.5, 0.0));
+        assert_eq!((doc.materials[2].alpha, doc.materials[2].alpha_cutoff), (0.75, None));
+        assert_eq!((doc.materials[3].alpha, doc.materials[3].alpha_cutoff), (1.0, None));
+
+        // glTF textures repeat by default.
+        let default = doc.textures[0].options;
+        assert_eq!((default.wrap_u, default.wrap_v), (TextureWrap::Repeat, TextureWrap::Repeat));
+        assert!(default.mipmaps);
+
+        let sampled = doc.textures[1].options;
+        assert_eq!((sampled.wrap_u, sampled.wrap_v), (TextureWrap::ClampToEdge, TextureWrap::MirroredRepeat));
+        assert_eq!((sampled.min_filter, sampled.mag_filter), (TextureFilter::Linear, TextureFilter::Nearest));
+        assert!(!sampled.mipmaps);
+    }
+
+    #[test]
+    fn invalid_textures() {
+        let e = parse_error(r#"{ "images": [ { "uri": "image.png" } ], "textures": [ { "source": 1 } ] }"#.as_bytes());
+        assert_eq!(e.reason.as_slice(), "texture source out of bounds.");
+
+        let e = parse_error(r#"{ "images": [ { "uri": "image.png" } ], "textures": [ { "source": 0, "sampler": 0 } ] }"#.as_bytes());
+        assert_eq!((e.tag.as_slice(), e.reason.as_slice()), ("samplers", "index 0 out of bounds."));
+
+        let e = parse_error(r#"{ "materials": [ { "pbrMetallicRoughness": { "baseColorTexture": { "index": 0 } } } ] }"#.as_bytes());
+        assert_eq!(e.reason.as_slice(), "base color texture index out of bounds.");
+    }
+}
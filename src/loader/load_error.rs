@@ -0,0 +1,49 @@
+//! Errors reported by the file loaders.
+
+use std::fmt;
+use std::io::IoError;
+
+/// A problem found at a specific line of a parsed file.
+///
+/// This is used both for fatal errors and for warnings.
+#[deriving(Clone, PartialEq)]
+pub struct ParseError {
+    /// The line where the problem was found, starting at 1. This is 0 if the problem is not
+    /// related to a specific line.
+    pub line:   uint,
+    /// The tag of the faulty line, e.g. `v` or `usemtl`.
+    pub tag:    String,
+    /// A description of the problem.
+    pub reason: String
+}
+
+impl ParseError {
+    /// Creates a new parse error.
+    pub fn new(line: uint, tag: &str, reason: String) -> ParseError {
+        ParseError {
+            line:   line,
+            tag:    tag.to_string(),
+            reason: reason
+        }
+    }
+}
+
+impl fmt::Show for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.tag.is_empty() {
+            write!(f, "line {}: {}", self.line, self.reason)
+        }
+        else {
+            write!(f, "line {}: `{}': {}", self.line, self.tag, self.reason)
+        }
+    }
+}
+
+/// An error preventing a file from being loaded.
+#[deriving(Show)]
+pub enum LoadError {
+    /// The file could not be read.
+    Io(IoError),
+    /// The file content is invalid.
+    Parse(ParseError)
+}
@@ -1,4 +1,10 @@
 //! File loading.
 
+pub use loader::load_error::{LoadError, ParseError};
+
 pub mod obj;
 pub mod mtl;
+pub mod gltf;
+pub mod stl;
+pub mod ply;
+mod load_error;
@@ -2,29 +2,63 @@
 
 use std::mem;
 use std::io::fs::File;
-use std::io::{IoResult, Reader};
+use std::io::{Reader, Writer, IoResult};
 use std::str::Words;
 use std::str::FromStr;
 use na::Vec3;
+use loader::{LoadError, ParseError};
 
-fn error(line: uint, err: &str) -> ! {
-    panic!("At line {}: {}", line, err)
+fn error<T>(line: uint, tag: &str, reason: String) -> Result<T, ParseError> {
+    Err(ParseError::new(line, tag, reason))
 }
 
 /// Parses a mtl file.
-pub fn parse_file(path: &Path) -> IoResult<Vec<MtlMaterial>> {
-    match File::open(path) {
-        Ok(mut file) => file.read_to_string().map(|mtl| parse(mtl.as_slice())),
-        Err(e)       => Err(e)
+///
+/// Returns the materials and the list of warnings about ignored lines.
+pub fn parse_file(path: &Path) -> Result<(Vec<MtlMaterial>, Vec<ParseError>), LoadError> {
+    match File::open(path).and_then(|mut file| file.read_to_string()) {
+        Ok(mtl) => parse(mtl.as_slice()).map_err(|e| LoadError::Parse(e)),
+        Err(e)  => Err(LoadError::Io(e))
     }
 }
 
+/// Writes materials with the mtl format.
+pub fn write(w: &mut Writer, materials: &[MtlMaterial]) -> IoResult<()> {
+    for m in materials.iter() {
+        try!(w.write_line(format!("newmtl {}", m.name).as_slice()));
+        try!(w.write_line(format!("Ka {} {} {}", m.ambiant.x, m.ambiant.y, m.ambiant.z).as_slice()));
+        try!(w.write_line(format!("Kd {} {} {}", m.diffuse.x, m.diffuse.y, m.diffuse.z).as_slice()));
+        try!(w.write_line(format!("Ks {} {} {}", m.specular.x, m.specular.y, m.specular.z).as_slice()));
+        try!(w.write_line(format!("Ns {}", m.shininess).as_slice()));
+        try!(w.write_line(format!("d {}", m.alpha).as_slice()));
+
+        let maps = [ ("map_Ka", &m.ambiant_texture),
+                     ("map_Kd", &m.diffuse_texture),
+                     ("map_Ks", &m.specular_texture),
+                     ("map_d",  &m.opacity_map) ];
+
+        for &(tag, map) in maps.iter() {
+            for map in map.iter() {
+                try!(w.write_line(format!("{} {}", tag, map).as_slice()));
+            }
+        }
+
+        try!(w.write_line(""));
+    }
+
+    w.flush()
+}
+
 /// Parses a string representing a mtl file.
-pub fn parse(string: &str) -> Vec<MtlMaterial> {
+///
+/// Returns the materials and the list of warnings about ignored lines.
+pub fn parse(string: &str) -> Result<(Vec<MtlMaterial>, Vec<ParseError>), ParseError> {
     let mut res           = Vec::new();
+    let mut warnings      = Vec::new();
     let mut curr_material = MtlMaterial::new_default("".to_string());
 
     for (l, line) in string.lines_any().enumerate() {
+        let l         = l + 1;
         let mut words = line.words();
         let tag       = words.next();
 
@@ -49,26 +83,28 @@ pub fn parse(string: &str) -> Vec<MtlMaterial> {
                             }
                         },
                         // ambiant color
-                            "Ka"          => curr_material.ambiant = parse_color(l, words),
-                            // diffuse color
-                            "Kd"          => curr_material.diffuse = parse_color(l, words),
-                            // specular color
-                            "Ks"          => curr_material.specular = parse_color(l, words),
-                            // shininess
-                            "Ns"          => curr_material.shininess = parse_scalar(l, words),
-                            // alpha
-                            "d"           => curr_material.alpha = parse_scalar(l, words),
-                            // ambiant map
-                            "map_Ka"      => curr_material.ambiant_texture = Some(parse_name(l, words)),
-                            // diffuse texture map
-                            "map_Kd"      => curr_material.diffuse_texture = Some(parse_name(l, words)),
-                            // specular texture map
-                            "map_Ks"      => curr_material.specular_texture = Some(parse_name(l, words)),
-                            // specular texture map
-                            "map_d" | "map_opacity" => curr_material.opacity_map = Some(parse_name(l, words)),
-                            _     => {
-                                println!("Warning: unknown line {} ignored: `{}'", l, line);
-                            }
+                        "Ka"          => curr_material.ambiant = try!(parse_color(l, w, words)),
+                        // diffuse color
+                        "Kd"          => curr_material.diffuse = try!(parse_color(l, w, words)),
+                        // specular color
+                        "Ks"          => curr_material.specular = try!(parse_color(l, w, words)),
+                        // shininess
+                        "Ns"          => curr_material.shininess = try!(parse_scalar(l, w, words)),
+                        // alpha
+                        "d"           => curr_material.alpha = try!(parse_scalar(l, w, words)),
+                        // transparency, i-e, one minus alpha
+                        "Tr"          => curr_material.alpha = 1.0 - try!(parse_scalar(l, w, words)),
+                        // ambiant map
+                        "map_Ka"      => curr_material.ambiant_texture = Some(parse_name(l, words)),
+                        // diffuse texture map
+                        "map_Kd"      => curr_material.diffuse_texture = Some(parse_name(l, words)),
+                        // specular texture map
+                        "map_Ks"      => curr_material.specular_texture = Some(parse_name(l, words)),
+                        // specular texture map
+                        "map_d" | "map_opacity" => curr_material.opacity_map = Some(parse_name(l, words)),
+                        _     => {
+                            warnings.push(ParseError::new(l, w, "unknown tag, line ignored.".to_string()));
+                        }
                     }
                 }
             }
@@ -79,7 +115,7 @@ pub fn parse(string: &str) -> Vec<MtlMaterial> {
         res.push(curr_material);
     }
 
-    res
+    Ok((res, warnings))
 }
 
 fn parse_name<'a>(_: uint, ws: Words<'a>) -> String {
@@ -87,30 +123,33 @@ fn parse_name<'a>(_: uint, ws: Words<'a>) -> String {
     res.connect(" ")
 }
 
-fn parse_color<'a>(l: uint, mut ws: Words<'a>) -> Vec3<f32> {
-    let sx = ws.next().unwrap_or_else(|| error(l, "3 components were expected, found 0."));
-    let sy = ws.next().unwrap_or_else(|| error(l, "3 components were expected, found 1."));
-    let sz = ws.next().unwrap_or_else(|| error(l, "3 components were expected, found 2."));
-
-    let x: Option<f32> = FromStr::from_str(sx);
-    let y: Option<f32> = FromStr::from_str(sy);
-    let z: Option<f32> = FromStr::from_str(sz);
-
-    let x = x.unwrap_or_else(|| error(l, format!("failed to parse `{}' as a f32.", sx).as_slice()));
-    let y = y.unwrap_or_else(|| error(l, format!("failed to parse `{}' as a f32.", sy).as_slice()));
-    let z = z.unwrap_or_else(|| error(l, format!("failed to parse `{}' as a f32.", sz).as_slice()));
+fn parse_f32(l: uint, tag: &str, s: &str) -> Result<f32, ParseError> {
+    let x: Option<f32> = FromStr::from_str(s);
 
-    Vec3::new(x, y, z)
+    match x {
+        Some(x) => Ok(x),
+        None    => error(l, tag, format!("failed to parse `{}' as a f32.", s))
+    }
 }
 
-fn parse_scalar<'a>(l: uint, mut ws: Words<'a>) -> f32 {
-    let sx = ws.next().unwrap_or_else(|| error(l, "1 component was expected, found 0."));
+fn parse_color<'a>(l: uint, tag: &str, mut ws: Words<'a>) -> Result<Vec3<f32>, ParseError> {
+    let mut comps = [ 0.0f32, ..3 ];
 
-    let x: Option<f32> = FromStr::from_str(sx);
+    for i in range(0u, 3) {
+        match ws.next() {
+            Some(s) => comps[i] = try!(parse_f32(l, tag, s)),
+            None    => return error(l, tag, format!("3 components were expected, found {}.", i))
+        }
+    }
 
-    let x = x.unwrap_or_else(|| error(l, format!("failed to parse `{}' as a f32.", sx).as_slice()));
+    Ok(Vec3::new(comps[0], comps[1], comps[2]))
+}
 
-    x
+fn parse_scalar<'a>(l: uint, tag: &str, mut ws: Words<'a>) -> Result<f32, ParseError> {
+    match ws.next() {
+        Some(s) => parse_f32(l, tag, s),
+        None    => error(l, tag, "1 component was expected, found 0.".to_string())
+    }
 }
 
 /// Material informations read from a `.mtl` file.
@@ -181,3 +220,55 @@ impl MtlMaterial {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use na::Vec3;
+    use loader::ParseError;
+    use super::parse;
+
+    fn parse_error(mtl: &str) -> ParseError {
+        match parse(mtl) {
+            Ok(_)  => panic!("the mtl file should have been rejected."),
+            Err(e) => e
+        }
+    }
+
+    #[test]
+    fn materials() {
+        let (ms, warnings) = parse("# two materials\nnewmtl red\nKd 1 0 0\nd 0.5\nmap_Kd red.png\n\nnewmtl glass\nTr 0.75\n").unwrap();
+
+        assert!(warnings.is_empty());
+        assert_eq!(ms.len(), 2);
+        assert_eq!(ms[0].name.as_slice(), "red");
+        assert_eq!(ms[0].diffuse, Vec3::new(1.0, 0.0, 0.0));
+        assert_eq!(ms[0].alpha, 0.5);
+        assert_eq!(ms[0].diffuse_texture, Some("red.png".to_string()));
+        assert_eq!(ms[1].name.as_slice(), "glass");
+        assert_eq!(ms[1].alpha, 0.25);
+    }
+
+    #[test]
+    fn malformed_values() {
+        let e = parse_error("newmtl red\nKd 1 x 0");
+
+        assert_eq!(e.line, 2);
+        assert_eq!(e.tag.as_slice(), "Kd");
+        assert_eq!(e.reason.as_slice(), "failed to parse `x' as a f32.");
+
+        let e = parse_error("newmtl red\nKs 1 0");
+        assert_eq!(e.reason.as_slice(), "3 components were expected, found 2.");
+    }
+
+    #[test]
+    fn unknown_tags() {
+        let (ms, warnings) = parse("newmtl red\nillum 2\nKd 1 0 0\nPr 0.5").unwrap();
+
+        assert_eq!(ms.len(), 1);
+        assert_eq!(ms[0].diffuse, Vec3::new(1.0, 0.0, 0.0));
+        assert_eq!(warnings.len(), 2);
+        assert_eq!((warnings[0].line, warnings[0].tag.as_slice()), (2, "illum"));
+        assert_eq!((warnings[1].line, warnings[1].tag.as_slice()), (4, "Pr"));
+        assert_eq!(warnings[0].reason.as_slice(), "unknown tag, line ignored.");
+    }
+}
@@ -1,20 +1,21 @@
 //! Simplistic obj loader.
 
 use std::io::fs::File;
-use std::io::Reader;
+use std::io::{Reader, Writer, BufferedWriter, IoResult};
 use std::str::Words;
 use std::str::FromStr;
-use std::io::IoResult;
 use std::iter::repeat;
 use std::collections::HashMap;
 use std::collections::hash_map::Entry;
 use std::sync::{Arc, RWLock};
 use gl::types::GLfloat;
-use na::{Vec3, Pnt2, Pnt3, Indexable, Bounded};
+use na::{Vec3, Pnt2, Pnt3, Iso3, Indexable, Bounded};
 use na;
-use resource::{BufferType, AllocationType, Mesh};
+use resource::{BufferType, AllocationType, Mesh, TextureManager};
+use scene::{Object, SceneNode};
 use loader::mtl::MtlMaterial;
 use loader::mtl;
+use loader::{LoadError, ParseError};
 use resource::GPUVector;
 
 /// The type of vertex coordinates.
@@ -24,24 +25,26 @@ pub type Normal = Vec3<GLfloat>;
 /// The type of texture coordinates.
 pub type UV     = Pnt2<GLfloat>;
 
-fn error(line: uint, err: &str) -> ! {
-    panic!("At line {}: {}", line, err)
-}
-
-fn warn(line: uint, err: &str) {
-    println!("At line {}: {}", line, err)
+fn error<T>(line: uint, tag: &str, reason: String) -> Result<T, ParseError> {
+    Err(ParseError::new(line, tag, reason))
 }
 
 /// Parses an obj file.
-pub fn parse_file(path: &Path, mtl_base_dir: &Path, basename: &str) -> IoResult<Vec<(String, Mesh, Option<MtlMaterial>)>> {
-    match File::open(path) {
-        Ok(mut file) => file.read_to_string().map(|obj| parse(obj.as_slice(), mtl_base_dir, basename)),
-        Err(e)       => Err(e)
+///
+/// Returns the named meshes with their materials, and the list of warnings about ignored data.
+pub fn parse_file(path: &Path, mtl_base_dir: &Path, basename: &str)
+                  -> Result<(Vec<(String, Mesh, Option<MtlMaterial>)>, Vec<ParseError>), LoadError> {
+    match File::open(path).and_then(|mut file| file.read_to_string()) {
+        Ok(obj) => parse(obj.as_slice(), mtl_base_dir, basename).map_err(|e| LoadError::Parse(e)),
+        Err(e)  => Err(LoadError::Io(e))
     }
 }
 
 /// Parses a string representing an obj file.
-pub fn parse(string: &str, mtl_base_dir: &Path, basename: &str) -> Vec<(String, Mesh, Option<MtlMaterial>)> {
+///
+/// Returns the named meshes with their materials, and the list of warnings about ignored data.
+pub fn parse(string: &str, mtl_base_dir: &Path, basename: &str)
+             -> Result<(Vec<(String, Mesh, Option<MtlMaterial>)>, Vec<ParseError>), ParseError> {
     let mut coords:     Vec<Coord>            = Vec::new();
     let mut normals:    Vec<Normal>           = Vec::new();
     let mut uvs:        Vec<UV>               = Vec::new();
@@ -53,11 +56,13 @@ pub fn parse(string: &str, mtl_base_dir: &Path, basename: &str) -> Vec<(String,
     let mut mtllib                            = HashMap::new();
     let mut group2mtl                         = HashMap::new();
     let mut curr_mtl                          = None::<MtlMaterial>;
+    let mut warnings                          = Vec::new();
 
     groups_ids.push(Vec::new());
     let _ = groups.insert(basename.to_string(), 0);
 
     for (l, line) in string.lines_any().enumerate() {
+        let l         = l + 1;
         let mut words = line.words();
         let tag = words.next();
         match tag {
@@ -65,18 +70,18 @@ pub fn parse(string: &str, mtl_base_dir: &Path, basename: &str) -> Vec<(String,
             Some(w) => {
                 if w.len() != 0 && w.as_bytes()[0] != ('#' as u8) {
                     match w {
-                        "v"      => coords.push(na::orig::<Pnt3<f32>>() + parse_v_or_vn(l, words)),
-                        "vn"     => if !ignore_normals { normals.push(parse_v_or_vn(l, words)) },
-                        "f"      => parse_f(l, words, coords.as_slice(), uvs.as_slice(), normals.as_slice(), &mut ignore_uvs, &mut ignore_normals, &mut groups_ids, curr_group),
-                        "vt"     => if !ignore_uvs { uvs.push(parse_vt(l, words)) },
+                        "v"      => coords.push(na::orig::<Pnt3<f32>>() + try!(parse_v_or_vn(l, w, words))),
+                        "vn"     => if !ignore_normals { normals.push(try!(parse_v_or_vn(l, w, words))) },
+                        "f"      => try!(parse_f(l, words, coords.as_slice(), uvs.as_slice(), normals.as_slice(), &mut ignore_uvs, &mut ignore_normals, &mut groups_ids, curr_group)),
+                        "vt"     => if !ignore_uvs { uvs.push(try!(parse_vt(l, w, words))) },
                         "g"      => {
                             curr_group = parse_g(l, words, basename, &mut groups, &mut groups_ids);
                             let _ = curr_mtl.as_ref().map(|mtl| group2mtl.insert(curr_group, mtl.clone()));
                         },
-                        "mtllib" => parse_mtllib(l, words, mtl_base_dir, &mut mtllib),
-                        "usemtl" => curr_group = parse_usemtl(l, words, curr_group, &mtllib, &mut group2mtl, &mut groups, &mut groups_ids, &mut curr_mtl),
+                        "mtllib" => parse_mtllib(l, words, mtl_base_dir, &mut mtllib, &mut warnings),
+                        "usemtl" => curr_group = parse_usemtl(l, words, curr_group, &mtllib, &mut group2mtl, &mut groups, &mut groups_ids, &mut curr_mtl, &mut warnings),
                         _         => {
-                            println!("Warning: unknown line {} ignored: `{}'", l, line);
+                            warnings.push(ParseError::new(l, w, "unknown tag, line ignored.".to_string()));
                         }
                     }
                 }
@@ -85,20 +90,22 @@ pub fn parse(string: &str, mtl_base_dir: &Path, basename: &str) -> Vec<(String,
     }
 
     if !uvs.is_empty() && ignore_uvs {
-        println!("Warning: some texture coordinates are missing. Dropping texture coordinates infos for every vertex.");
+        warnings.push(ParseError::new(0, "vt", "some texture coordinates are missing. Dropping texture coordinates infos for every vertex.".to_string()));
     }
 
     if !normals.is_empty() && ignore_normals {
-        println!("Warning: some normals are missing. Dropping normals infos for every vertex.");
+        warnings.push(ParseError::new(0, "vn", "some normals are missing. Dropping normals infos for every vertex.".to_string()));
     }
 
-    reformat(
+    let meshes = reformat(
         coords,
         if ignore_normals { None } else { Some(normals) },
         if ignore_uvs { None } else { Some(uvs) },
         groups_ids,
         groups,
-        group2mtl)
+        group2mtl);
+
+    Ok((meshes, warnings))
 }
 
 fn parse_usemtl<'a>(l:          uint,
@@ -108,7 +115,8 @@ fn parse_usemtl<'a>(l:          uint,
                     group2mtl:  &mut HashMap<uint, MtlMaterial>,
                     groups:     &mut HashMap<String, uint>,
                     groups_ids: &mut Vec<Vec<Vec3<u32>>>,
-                    curr_mtl:   &mut Option<MtlMaterial>)
+                    curr_mtl:   &mut Option<MtlMaterial>,
+                    warnings:   &mut Vec<ParseError>)
                     -> uint {
     let mname: Vec<&'a str> = ws.collect();
     let mname = mname.connect(" ");
@@ -117,7 +125,7 @@ fn parse_usemtl<'a>(l:          uint,
         match mtllib.get(&mname) {
             None    => {
                 *curr_mtl = None;
-                warn(l, format!("could not find the material {}", mname).as_slice());
+                warnings.push(ParseError::new(l, "usemtl", format!("could not find the material {}.", mname)));
 
                 curr_group
             },
@@ -152,7 +160,8 @@ fn parse_usemtl<'a>(l:          uint,
 fn parse_mtllib<'a>(l:            uint,
                     ws:           Words<'a>,
                     mtl_base_dir: &Path,
-                    mtllib:       &mut HashMap<String, MtlMaterial>) {
+                    mtllib:       &mut HashMap<String, MtlMaterial>,
+                    warnings:     &mut Vec<ParseError>) {
     let filename: Vec<&'a str> = ws.collect();
     let filename = filename.connect(" ");
 
@@ -160,31 +169,52 @@ fn parse_mtllib<'a>(l:            uint,
 
     path.push(filename);
 
-    let ms = mtl::parse_file(&path);
-
-    match ms {
-        Ok(ms) =>
+    match mtl::parse_file(&path) {
+        Ok((ms, mtl_warnings)) => {
             for m in ms.into_iter() {
                 let _ = mtllib.insert(m.name.to_string(), m);
-            },
-        Err(err) => warn(l, format!("{}", err).as_slice())
+            }
+
+            for w in mtl_warnings.into_iter() {
+                warnings.push(ParseError::new(l, "mtllib", format!("{}: {}", path.display(), w)));
+            }
+        },
+        Err(err) => warnings.push(ParseError::new(l, "mtllib", format!("{}: {}", path.display(), err)))
     }
 }
 
-fn parse_v_or_vn<'a>(l: uint, mut ws: Words<'a>) -> Vec3<f32> {
-    let sx = ws.next().unwrap_or_else(|| error(l, "3 components were expected, found 0."));
-    let sy = ws.next().unwrap_or_else(|| error(l, "3 components were expected, found 1."));
-    let sz = ws.next().unwrap_or_else(|| error(l, "3 components were expected, found 2."));
+fn parse_f32(l: uint, tag: &str, s: &str) -> Result<f32, ParseError> {
+    let x: Option<f32> = FromStr::from_str(s);
+
+    match x {
+        Some(x) => Ok(x),
+        None    => error(l, tag, format!("failed to parse `{}' as a f32.", s))
+    }
+}
 
-    let x: Option<f32> = FromStr::from_str(sx);
-    let y: Option<f32> = FromStr::from_str(sy);
-    let z: Option<f32> = FromStr::from_str(sz);
+fn parse_v_or_vn<'a>(l: uint, tag: &str, mut ws: Words<'a>) -> Result<Vec3<f32>, ParseError> {
+    let mut comps = [ 0.0f32, ..3 ];
 
-    let x = x.unwrap_or_else(|| error(l, format!("failed to parse `{}' as a f32.", sx).as_slice()));
-    let y = y.unwrap_or_else(|| error(l, format!("failed to parse `{}' as a f32.", sy).as_slice()));
-    let z = z.unwrap_or_else(|| error(l, format!("failed to parse `{}' as a f32.", sz).as_slice()));
+    for i in range(0u, 3) {
+        match ws.next() {
+            Some(s) => comps[i] = try!(parse_f32(l, tag, s)),
+            None    => return error(l, tag, format!("3 components were expected, found {}.", i))
+        }
+    }
 
-    Vec3::new(x, y, z)
+    Ok(Vec3::new(comps[0], comps[1], comps[2]))
+}
+
+// Converts a one-based, possibly relative, obj index to a zero-based index.
+fn resolve_index(l: uint, id: i32, len: uint, what: &str) -> Result<u32, ParseError> {
+    let res = if id < 0 { len as i32 + id + 1 } else { id };
+
+    if res < 0 || res as uint >= len {
+        error(l, "f", format!("{} index {} out of bounds.", what, id + 1))
+    }
+    else {
+        Ok(res as u32)
+    }
 }
 
 fn parse_f<'a>(l:              uint,
@@ -195,18 +225,23 @@ fn parse_f<'a>(l:              uint,
                ignore_uvs:     &mut bool,
                ignore_normals: &mut bool,
                groups_ids:     &mut Vec<Vec<Vec3<u32>>>,
-               curr_group:     uint) {
+               curr_group:     uint)
+               -> Result<(), ParseError> {
     // Four formats possible: v   v/t   v//n   v/t/n
     let mut i = 0;
     for word in ws {
         let mut curr_ids: Vec3<i32> = Bounded::max_value();
 
         for (i, w) in word.split('/').enumerate() {
+            if i > 2 {
+                return error(l, "f", format!("at most 3 indices per vertex were expected, found `{}'.", word));
+            }
+
             if i == 0 || w.len() != 0 {
                 let idx: Option<i32> = FromStr::from_str(w);
                 match idx {
                     Some(id) => curr_ids[i] = id - 1,
-                    None     => error(l, format!("failed to parse `{}' as a i32.", w).as_slice())
+                    None     => return error(l, "f", format!("failed to parse `{}' as a i32.", w))
                 }
             }
         }
@@ -229,29 +264,22 @@ fn parse_f<'a>(l:              uint,
         }
 
         // Handle relatives indice
-        let x;
+        let x = try!(resolve_index(l, curr_ids.x, coords.len(), "vertex"));
         let y;
         let z;
 
-        if curr_ids.x < 0 {
-            x = (coords.len() as i32 + curr_ids.x + 1) as u32;
-        }
-        else {
-            x = curr_ids.x as u32;
-        }
-
-        if curr_ids.y < 0 {
-            y = (uvs.len() as i32 + curr_ids.y + 1) as u32;
+        if *ignore_uvs {
+            y = 0;
         }
         else {
-            y = curr_ids.y as u32;
+            y = try!(resolve_index(l, curr_ids.y, uvs.len(), "texture coordinate"));
         }
 
-        if curr_ids.z < 0 {
-            z = (normals.len() as i32 + curr_ids.z + 1) as u32;
+        if *ignore_normals {
+            z = 0;
         }
         else {
-            z = curr_ids.z as u32;
+            z = try!(resolve_index(l, curr_ids.z, normals.len(), "normal"));
         }
 
         groups_ids[curr_group].push(Vec3::new(x, y, z));
@@ -259,30 +287,32 @@ fn parse_f<'a>(l:              uint,
         i = i + 1;
     }
 
+    if i == 0 {
+        return error(l, "f", "a face must have at least one vertex.".to_string());
+    }
+
     // there is not enough vertex to form a triangle. Complete it.
-    if i < 2 {
+    if i < 3 {
         for _ in range(0u, 3 - i) {
             let last = (*groups_ids)[curr_group].last().unwrap().clone();
             groups_ids[curr_group].push(last);
         }
     }
-}
 
-fn parse_vt<'a>(l: uint, mut ws: Words<'a>) -> UV {
-    let _0 = "0.0";
-    let sx  = ws.next().unwrap_or_else(|| error(l, "at least 2 components were expected, found 0."));
-    let sy  = ws.next().unwrap_or_else(|| error(l, "at least 2 components were expected, found 1."));
-    // let sz  = ws.next().unwrap_or(_0);
+    Ok(())
+}
 
-    let x: Option<f32> = FromStr::from_str(sx);
-    let y: Option<f32> = FromStr::from_str(sy);
-    // let z: Option<f32> = FromStr::from_str(sz);
+fn parse_vt<'a>(l: uint, tag: &str, mut ws: Words<'a>) -> Result<UV, ParseError> {
+    let mut comps = [ 0.0f32, ..2 ];
 
-    let x = x.unwrap_or_else(|| error(l, format!("failed to parse `{}' as a f32.", sx).as_slice()));
-    let y = y.unwrap_or_else(|| error(l, format!("failed to parse `{}' as a f32.", sy).as_slice()));
-    // let z = z.unwrap_or_else(|| error(l, "failed to parse `" + sz + "' as a f32."));
+    for i in range(0u, 2) {
+        match ws.next() {
+            Some(s) => comps[i] = try!(parse_f32(l, tag, s)),
+            None    => return error(l, tag, format!("at least 2 components were expected, found {}.", i))
+        }
+    }
 
-    Pnt2::new(x, y)
+    Ok(Pnt2::new(comps[0], comps[1]))
 }
 
 fn parse_g<'a>(_:          uint,
@@ -377,3 +407,310 @@ fn reformat(coords:     Vec<Coord>,
 
     meshes
 }
+
+/// Writes the objects of a scene node and its children to an obj file.
+///
+/// The materials are written to a mtl file with the same name and the `mtl` extension.
+pub fn write_file(node: &SceneNode, path: &Path) -> IoResult<()> {
+    let mtl_path  = path.with_extension("mtl");
+    let mut file  = BufferedWriter::new(try!(File::create(path)));
+    let materials = try!(write(&mut file, node, mtl_path.filename_str().unwrap_or("")));
+    let mut file  = BufferedWriter::new(try!(File::create(&mtl_path)));
+
+    mtl::write(&mut file, materials.as_slice())
+}
+
+/// Writes the objects of a scene node and its children with the obj format.
+///
+/// Each object is written as a separate group with its own material. The world transformation and
+/// scale of each object are applied to its vertices.
+///
+/// # Arguments
+/// * `mtllib` - name of the mtl file the returned materials are meant to be written to with
+/// `mtl::write`.
+pub fn write(w: &mut Writer, node: &SceneNode, mtllib: &str) -> IoResult<Vec<MtlMaterial>> {
+    let mut materials = Vec::new();
+    let mut offsets   = Vec3::new(1u, 1, 1); // obj indices start at 1.
+    let mut res       = Ok(());
+
+    try!(w.write_line("# written by kiss3d"));
+    try!(w.write_line(format!("mtllib {}", mtllib).as_slice()));
+
+    node.apply_to_objects_with_world_transformation(&mut |o, transform, scale| {
+        if res.is_ok() {
+            let material = object_material(o, format!("material{}", materials.len()));
+
+            res = write_object(w, o, transform, scale, materials.len(), material.name.as_slice(), &mut offsets);
+            materials.push(material);
+        }
+    });
+
+    try!(res);
+    try!(w.flush());
+
+    Ok(materials)
+}
+
+fn object_material(o: &Object, name: String) -> MtlMaterial {
+    let data        = o.data();
+    let mut res     = MtlMaterial::new_default(name);
+    let color       = data.color();
+
+    res.diffuse         = Vec3::new(color.x, color.y, color.z);
+    res.alpha           = data.alpha();
+    res.diffuse_texture = TextureManager::get_global_manager(|tm| tm.get_name(&**data.texture()));
+    res.opacity_map     = data.opacity_texture().and_then(|t| TextureManager::get_global_manager(|tm| tm.get_name(&**t)));
+
+    res
+}
+
+fn write_object(w:         &mut Writer,
+                o:         &Object,
+                transform: &Iso3<f32>,
+                scale:     &Vec3<f32>,
+                id:        uint,
+                material:  &str,
+                offsets:   &mut Vec3<uint>)
+                -> IoResult<()> {
+    let mesh    = o.mesh().borrow();
+    let coords  = mesh.coords().read().unwrap();
+    let uvs     = mesh.uvs().read().unwrap();
+    let normals = mesh.normals().read().unwrap();
+    let faces   = mesh.faces().read().unwrap();
+
+    write_mesh(w,
+               coords.data().as_ref().map(|cs| cs.as_slice()).unwrap_or(&[]),
+               uvs.data().as_ref().map(|ts| ts.as_slice()),
+               normals.data().as_ref().map(|ns| ns.as_slice()),
+               faces.data().as_ref().map(|fs| fs.as_slice()).unwrap_or(&[]),
+               transform,
+               scale,
+               id,
+               material,
+               offsets)
+}
+
+fn write_mesh(w:         &mut Writer,
+              coords:    &[Coord],
+              uvs:       Option<&[UV]>,
+              normals:   Option<&[Normal]>,
+              faces:     &[Vec3<u32>],
+              transform: &Iso3<f32>,
+              scale:     &Vec3<f32>,
+              id:        uint,
+              material:  &str,
+              offsets:   &mut Vec3<uint>)
+              -> IoResult<()> {
+    let nvertices = coords.len();
+    let uvs       = uvs.and_then(|ts| if ts.len() == nvertices { Some(ts) } else { None });
+    let normals   = normals.and_then(|ns| if ns.len() == nvertices { Some(ns) } else { None });
+
+    try!(w.write_line(format!("g object{}", id).as_slice()));
+    try!(w.write_line(format!("usemtl {}", material).as_slice()));
+
+    for v in coords.iter() {
+        let v = *transform * Pnt3::new(v.x * scale.x, v.y * scale.y, v.z * scale.z);
+
+        try!(w.write_line(format!("v {} {} {}", v.x, v.y, v.z).as_slice()));
+    }
+
+    for t in uvs.unwrap_or(&[]).iter() {
+        try!(w.write_line(format!("vt {} {}", t.x, t.y).as_slice()));
+    }
+
+    for n in normals.unwrap_or(&[]).iter() {
+        // Normals are transformed by the inverse transpose of the scaling.
+        let n = Vec3::new(n.x / scale.x, n.y / scale.y, n.z / scale.z);
+        let n = na::normalize(&na::rotate(transform, &n));
+
+        try!(w.write_line(format!("vn {} {} {}", n.x, n.y, n.z).as_slice()));
+    }
+
+    for f in faces.iter() {
+        let mut line = "f".to_string();
+
+        for i in [ f.x, f.y, f.z ].iter() {
+            let i = *i as uint;
+
+            line.push_str(match (uvs.is_some(), normals.is_some()) {
+                (true,  true)  => format!(" {}/{}/{}", i + offsets.x, i + offsets.y, i + offsets.z),
+                (true,  false) => format!(" {}/{}", i + offsets.x, i + offsets.y),
+                (false, true)  => format!(" {}//{}", i + offsets.x, i + offsets.z),
+                (false, false) => format!(" {}", i + offsets.x)
+            }.as_slice());
+        }
+
+        try!(w.write_line(line.as_slice()));
+    }
+
+    offsets.x = offsets.x + nvertices;
+
+    if uvs.is_some() {
+        offsets.y = offsets.y + nvertices;
+    }
+
+    if normals.is_some() {
+        offsets.z = offsets.z + nvertices;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::TempDir;
+    use std::io::fs::File;
+    use na::{Pnt2, Pnt3, Vec3, Iso3};
+    use na;
+    use loader::ParseError;
+    use loader::mtl::MtlMaterial;
+    use loader::mtl;
+    use super::{parse, write_mesh};
+
+    static TRIANGLE: &'static str = "v 0 0 0\nv 1 0 0\nv 0 1 0\n";
+
+    fn parse_error(obj: &str) -> ParseError {
+        match parse(obj, &Path::new("."), "test") {
+            Ok(_)  => panic!("the obj file should have been rejected."),
+            Err(e) => e
+        }
+    }
+
+    fn warnings(obj: &str) -> Vec<ParseError> {
+        parse(obj, &Path::new("."), "test").unwrap().1
+    }
+
+    #[test]
+    fn malformed_face() {
+        let e = parse_error(format!("{}f 1 2 x", TRIANGLE).as_slice());
+
+        assert_eq!(e.line, 4);
+        assert_eq!(e.tag.as_slice(), "f");
+        assert_eq!(e.reason.as_slice(), "failed to parse `x' as a i32.");
+
+        let e = parse_error(format!("{}f 1/1/1/1 2 3", TRIANGLE).as_slice());
+        assert_eq!(e.reason.as_slice(), "at most 3 indices per vertex were expected, found `1/1/1/1'.");
+
+        let e = parse_error(format!("{}f", TRIANGLE).as_slice());
+        assert_eq!(e.reason.as_slice(), "a face must have at least one vertex.");
+    }
+
+    #[test]
+    fn malformed_vertex() {
+        let e = parse_error("v 0 0 0\nv 1 0");
+
+        assert_eq!(e.line, 2);
+        assert_eq!(e.tag.as_slice(), "v");
+        assert_eq!(e.reason.as_slice(), "3 components were expected, found 2.");
+    }
+
+    #[test]
+    fn index_zero() {
+        let e = parse_error(format!("{}f 0 1 2", TRIANGLE).as_slice());
+
+        assert_eq!(e.line, 4);
+        assert_eq!(e.reason.as_slice(), "vertex index 0 out of bounds.");
+    }
+
+    #[test]
+    fn out_of_range_indices() {
+        let e = parse_error(format!("{}f 1 2 4", TRIANGLE).as_slice());
+        assert_eq!(e.reason.as_slice(), "vertex index 4 out of bounds.");
+
+        let e = parse_error(format!("{}f 1 2 -4", TRIANGLE).as_slice());
+        assert_eq!(e.reason.as_slice(), "vertex index -4 out of bounds.");
+
+        let e = parse_error(format!("{}vn 0 0 1\nf 1//1 2//2 3//1", TRIANGLE).as_slice());
+        assert_eq!(e.line, 5);
+        assert_eq!(e.reason.as_slice(), "normal index 2 out of bounds.");
+    }
+
+    #[test]
+    fn relative_indices() {
+        let (meshes, warnings) = parse(format!("{}f -3 -2 -1", TRIANGLE).as_slice(), &Path::new("."), "test").unwrap();
+
+        assert!(warnings.is_empty());
+        assert_eq!(meshes.len(), 1);
+        assert_eq!(meshes[0].1.num_pts(), 3);
+    }
+
+    #[test]
+    fn unknown_tags() {
+        let ws = warnings(format!("{}o object\nf 1 2 3\ns off", TRIANGLE).as_slice());
+
+        assert_eq!(ws.len(), 2);
+        assert_eq!((ws[0].line, ws[0].tag.as_slice()), (4, "o"));
+        assert_eq!((ws[1].line, ws[1].tag.as_slice()), (6, "s"));
+        assert_eq!(ws[0].reason.as_slice(), "unknown tag, line ignored.");
+    }
+
+    #[test]
+    fn missing_materials() {
+        let ws = warnings(format!("mtllib missing.mtl\n{}usemtl red\nf 1 2 3", TRIANGLE).as_slice());
+
+        assert_eq!(ws.len(), 2);
+        assert_eq!((ws[0].line, ws[0].tag.as_slice()), (1, "mtllib"));
+        assert_eq!((ws[1].line, ws[1].tag.as_slice()), (5, "usemtl"));
+        assert_eq!(ws[1].reason.as_slice(), "could not find the material red.");
+    }
+
+    #[test]
+    fn missing_normals() {
+        let ws = warnings(format!("{}vn 0 0 1\nf 1//1 2//1 3", TRIANGLE).as_slice());
+
+        assert_eq!(ws.len(), 1);
+        assert_eq!((ws[0].line, ws[0].tag.as_slice()), (0, "vn"));
+    }
+
+    #[test]
+    fn write_round_trip() {
+        let dir     = TempDir::new("kiss3d-obj").unwrap();
+        let coords  = [ Pnt3::new(0.0f32, 0.0, 0.0), Pnt3::new(1.0, 0.0, 0.0), Pnt3::new(1.0, 1.0, 0.0), Pnt3::new(0.0, 1.0, 0.0) ];
+        let uvs     = [ Pnt2::new(0.0f32, 0.0), Pnt2::new(1.0, 0.0), Pnt2::new(1.0, 1.0), Pnt2::new(0.0, 0.5) ];
+        let normals = [ Vec3::z(), Vec3::z(), Vec3::z(), Vec3::z() ];
+        let faces   = [ Vec3::new(0u32, 1, 2), Vec3::new(0, 2, 3) ];
+
+        let mut material = MtlMaterial::new_default("material0".to_string());
+        material.diffuse         = Vec3::new(1.0, 0.5, 0.25);
+        material.alpha           = 0.5;
+        material.diffuse_texture = Some("texture.png".to_string());
+
+        let mut file = File::create(&dir.path().join("test.mtl")).unwrap();
+        mtl::write(&mut file, &[ material ]).unwrap();
+
+        let mut obj     = b"mtllib test.mtl\n".to_vec();
+        let mut offsets = Vec3::new(1u, 1, 1);
+        let transform   = Iso3::new(Vec3::new(1.0f32, 2.0, 3.0), na::zero());
+        let scale       = Vec3::new(2.0f32, 2.0, 2.0);
+
+        write_mesh(&mut obj, coords.as_slice(), Some(uvs.as_slice()), Some(normals.as_slice()), faces.as_slice(),
+                   &transform, &scale, 0, "material0", &mut offsets).unwrap();
+        assert_eq!(offsets, Vec3::new(5u, 5, 5));
+
+        let obj = String::from_utf8(obj).unwrap();
+        let (meshes, warnings) = parse(obj.as_slice(), dir.path(), "test").unwrap();
+
+        assert!(warnings.is_empty());
+        assert_eq!(meshes.len(), 1);
+
+        let (ref name, ref mesh, ref material) = meshes[0];
+        let material = material.as_ref().unwrap();
+
+        assert_eq!(name.as_slice(), "object0");
+        assert_eq!(material.name.as_slice(), "material0");
+        assert_eq!(material.diffuse, Vec3::new(1.0, 0.5, 0.25));
+        assert_eq!(material.alpha, 0.5);
+        assert_eq!(material.diffuse_texture, Some("texture.png".to_string()));
+
+        let read_coords  = mesh.coords().read().unwrap();
+        let read_uvs     = mesh.uvs().read().unwrap();
+        let read_normals = mesh.normals().read().unwrap();
+        let read_faces   = mesh.faces().read().unwrap();
+
+        assert_eq!(read_coords.data().as_ref().unwrap().as_slice(),
+                   [ Pnt3::new(1.0f32, 2.0, 3.0), Pnt3::new(3.0, 2.0, 3.0), Pnt3::new(3.0, 4.0, 3.0), Pnt3::new(1.0, 4.0, 3.0) ].as_slice());
+        assert_eq!(read_uvs.data().as_ref().unwrap().as_slice(), uvs.as_slice());
+        assert_eq!(read_normals.data().as_ref().unwrap().as_slice(), normals.as_slice());
+        assert_eq!(read_faces.data().as_ref().unwrap().as_slice(), faces.as_slice());
+    }
+}
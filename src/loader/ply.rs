@@ -0,0 +1,633 @@
+//! Simplistic PLY loader and writer.
+//!
+//! The ASCII, binary little-endian and binary big-endian formats are supported. Only the
+//! `vertex` and `face` elements are loaded: vertex positions, normals, texture coordinates and
+//! colors. Polygonal faces are triangulated as fans. Files without faces are loaded as point
+//! clouds.
+
+use std::str;
+use std::num::Float;
+use std::io::fs::File;
+use std::io::{BufReader, BufferedWriter, Reader, Writer, IoResult};
+use na::{Pnt2, Pnt3, Vec3};
+use ncollide::procedural::{TriMesh, TriMesh3, IndexBuffer};
+use scene::SceneNode;
+use loader::{LoadError, ParseError};
+
+/// The content of a PLY file.
+pub struct PlyMesh {
+    /// The vertex positions.
+    pub coords:  Vec<Pnt3<f32>>,
+    /// The vertex normals.
+    pub normals: Option<Vec<Vec3<f32>>>,
+    /// The vertex texture coordinates.
+    pub uvs:     Option<Vec<Pnt2<f32>>>,
+    /// The vertex colors, with components in `[0.0, 1.0]`.
+    pub colors:  Option<Vec<Pnt3<f32>>>,
+    /// The triangles. This is empty if the file is a point cloud.
+    pub faces:   Vec<Vec3<u32>>
+}
+
+impl PlyMesh {
+    /// Whether this file contains points only.
+    #[inline]
+    pub fn is_point_cloud(&self) -> bool {
+        self.faces.is_empty()
+    }
+
+    /// Converts this file content into a triangle mesh, dropping the vertex colors.
+    ///
+    /// Returns `None` if this is a point cloud.
+    pub fn to_trimesh(&self) -> Option<TriMesh3<f32>> {
+        if self.is_point_cloud() {
+            None
+        }
+        else {
+            Some(TriMesh::new(self.coords.clone(),
+                              self.normals.clone(),
+                              self.uvs.clone(),
+                              Some(IndexBuffer::Unified(self.faces.clone()))))
+        }
+    }
+}
+
+#[deriving(PartialEq)]
+enum Format {
+    Ascii,
+    BinaryLittleEndian,
+    BinaryBigEndian
+}
+
+#[deriving(Copy, Clone, PartialEq)]
+enum Scalar {
+    Char,
+    UChar,
+    Short,
+    UShort,
+    Int,
+    UInt,
+    Float,
+    Double
+}
+
+enum Property {
+    Value(String, Scalar),
+    List(String, Scalar, Scalar)
+}
+
+struct Element {
+    name:       String,
+    count:      uint,
+    properties: Vec<Property>
+}
+
+fn error<T>(line: uint, tag: &str, reason: String) -> Result<T, ParseError> {
+    Err(ParseError::new(line, tag, reason))
+}
+
+/// Parses a PLY file.
+///
+/// Returns the file content and the list of warnings about ignored data.
+pub fn parse_file(path: &Path) -> Result<(PlyMesh, Vec<ParseError>), LoadError> {
+    match File::open(path).and_then(|mut file| file.read_to_end()) {
+        Ok(data) => parse(data.as_slice()).map_err(|e| LoadError::Parse(e)),
+        Err(e)   => Err(LoadError::Io(e))
+    }
+}
+
+/// Parses the content of a PLY file.
+///
+/// Returns the file content and the list of warnings about ignored data.
+pub fn parse(data: &[u8]) -> Result<(PlyMesh, Vec<ParseError>), ParseError> {
+    let mut warnings = Vec::new();
+    let (format, elements, body, nlines) = try!(parse_header(data, &mut warnings));
+
+    let mut mesh = PlyMesh {
+        coords:  Vec::new(),
+        normals: None,
+        uvs:     None,
+        colors:  None,
+        faces:   Vec::new()
+    };
+
+    let mut ascii = match format {
+        Format::Ascii => {
+            match str::from_utf8(body) {
+                Some(s) => Some(s.lines_any().enumerate().map(|(l, line)| (l + nlines + 1, line)).filter(|&(_, line)| !line.trim().is_empty())),
+                None    => return error(nlines, "", "the ASCII body is not valid UTF-8.".to_string())
+            }
+        },
+        _ => None
+    };
+    let mut binary = BufReader::new(body);
+
+    for element in elements.iter() {
+        let is_vertex = element.name.as_slice() == "vertex";
+        let is_face   = element.name.as_slice() == "face";
+
+        if !is_vertex && !is_face {
+            warnings.push(ParseError::new(0, element.name.as_slice(), "unsupported element ignored.".to_string()));
+        }
+
+        let find = |names: &[&str]| {
+            element.properties.iter().position(|p| {
+                match *p {
+                    Property::Value(ref name, _) => names.contains(&name.as_slice()),
+                    _                            => false
+                }
+            })
+        };
+
+        let xyz    = [ find(&["x"]), find(&["y"]), find(&["z"]) ];
+        let nxyz   = [ find(&["nx"]), find(&["ny"]), find(&["nz"]) ];
+        let uv     = [ find(&["u", "s", "texture_u"]), find(&["v", "t", "texture_v"]) ];
+        let rgb    = [ find(&["red", "r", "diffuse_red"]), find(&["green", "g", "diffuse_green"]), find(&["blue", "b", "diffuse_blue"]) ];
+        let faceid = element.properties.iter().position(|p| {
+            match *p {
+                Property::List(ref name, _, _) => name.as_slice() == "vertex_indices" || name.as_slice() == "vertex_index",
+                _                              => false
+            }
+        });
+
+        if is_vertex {
+            if xyz.iter().any(|i| i.is_none()) {
+                return error(0, "vertex", "missing vertex position properties.".to_string());
+            }
+
+            // The element count is not trusted: vectors grow as the data is read, so that a bogus
+            // header fails with an unexpected end of file instead of a huge allocation.
+            if nxyz.iter().all(|i| i.is_some()) {
+                mesh.normals = Some(Vec::new())
+            }
+
+            if uv.iter().all(|i| i.is_some()) {
+                mesh.uvs = Some(Vec::new())
+            }
+
+            if rgb.iter().all(|i| i.is_some()) {
+                mesh.colors = Some(Vec::new())
+            }
+        }
+
+        if is_face && faceid.is_none() {
+            return error(0, "face", "missing the `vertex_indices` property.".to_string());
+        }
+
+        for _ in range(0u, element.count) {
+            let mut values = Vec::with_capacity(element.properties.len());
+            let mut list   = Vec::new();
+
+            // Read the whole element.
+            match ascii {
+                Some(ref mut lines) => {
+                    let (l, line) = match lines.next() {
+                        Some(line) => line,
+                        None       => return error(0, element.name.as_slice(), "unexpected end of file.".to_string())
+                    };
+                    let mut words = line.words();
+
+                    for (i, p) in element.properties.iter().enumerate() {
+                        let count = match *p {
+                            Property::Value(..) => None,
+                            Property::List(..)  => Some(try!(to_uint(l, element, try!(next_ascii(l, element, &mut words)), "list size")))
+                        };
+
+                        match count {
+                            None        => values.push(try!(next_ascii(l, element, &mut words))),
+                            Some(count) => {
+                                values.push(count as f64);
+
+                                for _ in range(0u, count) {
+                                    let v = try!(next_ascii(l, element, &mut words));
+
+                                    if Some(i) == faceid {
+                                        list.push(try!(to_uint(l, element, v, "vertex index")))
+                                    }
+                                }
+                            }
+                        }
+                    }
+                },
+                None => {
+                    for (i, p) in element.properties.iter().enumerate() {
+                        match *p {
+                            Property::Value(_, ty) => values.push(try!(next_binary(&mut binary, &format, ty, element))),
+                            Property::List(_, cty, ty) => {
+                                let count = try!(next_binary(&mut binary, &format, cty, element));
+                                let count = try!(to_uint(0, element, count, "list size"));
+                                values.push(count as f64);
+
+                                for _ in range(0u, count) {
+                                    let v = try!(next_binary(&mut binary, &format, ty, element));
+
+                                    if Some(i) == faceid {
+                                        list.push(try!(to_uint(0, element, v, "vertex index")))
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+
+            if is_vertex {
+                let get = |i: Option<uint>| values[i.unwrap()] as f32;
+
+                mesh.coords.push(Pnt3::new(get(xyz[0]), get(xyz[1]), get(xyz[2])));
+
+                for normals in mesh.normals.iter_mut() {
+                    normals.push(Vec3::new(get(nxyz[0]), get(nxyz[1]), get(nxyz[2])))
+                }
+
+                for uvs in mesh.uvs.iter_mut() {
+                    uvs.push(Pnt2::new(get(uv[0]), get(uv[1])))
+                }
+
+                for colors in mesh.colors.iter_mut() {
+                    // Integer colors range from 0 to 255.
+                    let color = |i: Option<uint>| {
+                        let i = i.unwrap();
+
+                        match element.properties[i] {
+                            Property::Value(_, Scalar::Float) | Property::Value(_, Scalar::Double) => values[i] as f32,
+                            _ => values[i] as f32 / 255.0
+                        }
+                    };
+
+                    colors.push(Pnt3::new(color(rgb[0]), color(rgb[1]), color(rgb[2])))
+                }
+            }
+            else if is_face && list.len() >= 3 {
+                // Triangulate as a fan.
+                for i in range(1u, list.len() - 1) {
+                    mesh.faces.push(Vec3::new(list[0] as u32, list[i] as u32, list[i + 1] as u32))
+                }
+            }
+        }
+    }
+
+    let nvertices = mesh.coords.len() as u32;
+
+    if mesh.faces.iter().any(|f| f.x >= nvertices || f.y >= nvertices || f.z >= nvertices) {
+        return error(0, "face", "vertex index out of bounds.".to_string());
+    }
+
+    Ok((mesh, warnings))
+}
+
+fn next_ascii<'a, I: Iterator<&'a str>>(l: uint, element: &Element, words: &mut I) -> Result<f64, ParseError> {
+    match words.next().and_then(|w| from_str::<f64>(w)) {
+        Some(v) => Ok(v),
+        None    => error(l, element.name.as_slice(), "missing or invalid property value.".to_string())
+    }
+}
+
+// Counts and indices are read as floating-point values: reject those that cannot be cast.
+fn to_uint(l: uint, element: &Element, v: f64, what: &str) -> Result<uint, ParseError> {
+    if v >= 0.0 && v <= 4294967295.0 && v.fract() == 0.0 {
+        Ok(v as uint)
+    }
+    else {
+        error(l, element.name.as_slice(), format!("invalid {}: {}.", what, v))
+    }
+}
+
+fn next_binary(reader: &mut BufReader, format: &Format, ty: Scalar, element: &Element) -> Result<f64, ParseError> {
+    let le  = *format == Format::BinaryLittleEndian;
+    let res = match ty {
+        Scalar::Char   => reader.read_i8().map(|v| v as f64),
+        Scalar::UChar  => reader.read_u8().map(|v| v as f64),
+        Scalar::Short  => if le { reader.read_le_i16() } else { reader.read_be_i16() }.map(|v| v as f64),
+        Scalar::UShort => if le { reader.read_le_u16() } else { reader.read_be_u16() }.map(|v| v as f64),
+        Scalar::Int    => if le { reader.read_le_i32() } else { reader.read_be_i32() }.map(|v| v as f64),
+        Scalar::UInt   => if le { reader.read_le_u32() } else { reader.read_be_u32() }.map(|v| v as f64),
+        Scalar::Float  => if le { reader.read_le_f32() } else { reader.read_be_f32() }.map(|v| v as f64),
+        Scalar::Double => if le { reader.read_le_f64() } else { reader.read_be_f64() }
+    };
+
+    res.map_err(|_| ParseError::new(0, element.name.as_slice(), "unexpected end of file.".to_string()))
+}
+
+fn parse_scalar(l: uint, name: Option<&str>) -> Result<Scalar, ParseError> {
+    match name {
+        Some("char")   | Some("int8")    => Ok(Scalar::Char),
+        Some("uchar")  | Some("uint8")   => Ok(Scalar::UChar),
+        Some("short")  | Some("int16")   => Ok(Scalar::Short),
+        Some("ushort") | Some("uint16")  => Ok(Scalar::UShort),
+        Some("int")    | Some("int32")   => Ok(Scalar::Int),
+        Some("uint")   | Some("uint32")  => Ok(Scalar::UInt),
+        Some("float")  | Some("float32") => Ok(Scalar::Float),
+        Some("double") | Some("float64") => Ok(Scalar::Double),
+        _ => error(l, "property", "unknown property type.".to_string())
+    }
+}
+
+// Returns the format, the elements, the data following the header, and the number of lines of
+// the header.
+fn parse_header<'a>(data: &'a [u8], warnings: &mut Vec<ParseError>)
+                    -> Result<(Format, Vec<Element>, &'a [u8], uint), ParseError> {
+    let mut format   = None;
+    let mut elements = Vec::new();
+    let mut begin    = 0;
+    let mut l        = 0;
+
+    loop {
+        let end = match data.slice_from(begin).iter().position(|c| *c == b'\n') {
+            Some(i) => begin + i,
+            None    => return error(l, "", "missing `end_header`.".to_string())
+        };
+
+        let line = match str::from_utf8(data.slice(begin, end)) {
+            Some(line) => line,
+            None       => return error(l + 1, "", "the header is not valid UTF-8.".to_string())
+        };
+
+        l     = l + 1;
+        begin = end + 1;
+
+        let mut words = line.words();
+
+        match words.next() {
+            Some("ply") if l == 1 => { },
+            _ if l == 1           => return error(l, "", "not a PLY file.".to_string()),
+            Some("format") => {
+                format = match words.next() {
+                    Some("ascii")                => Some(Format::Ascii),
+                    Some("binary_little_endian") => Some(Format::BinaryLittleEndian),
+                    Some("binary_big_endian")    => Some(Format::BinaryBigEndian),
+                    _                            => return error(l, "format", "unknown format.".to_string())
+                }
+            },
+            Some("element") => {
+                let name  = words.next();
+                let count = words.next().and_then(|c| from_str::<uint>(c));
+
+                match (name, count) {
+                    (Some(name), Some(count)) => elements.push(Element {
+                        name:       name.to_string(),
+                        count:      count,
+                        properties: Vec::new()
+                    }),
+                    _ => return error(l, "element", "expected a name and a count.".to_string())
+                }
+            },
+            Some("property") => {
+                let property = match words.next() {
+                    Some("list") => {
+                        let count_ty = try!(parse_scalar(l, words.next()));
+                        let ty       = try!(parse_scalar(l, words.next()));
+
+                        if count_ty == Scalar::Float || count_ty == Scalar::Double {
+                            return error(l, "property", "list counts must be integers.".to_string());
+                        }
+
+                        Property::List(words.next().unwrap_or("").to_string(), count_ty, ty)
+                    },
+                    ty => Property::Value(words.next().unwrap_or("").to_string(), try!(parse_scalar(l, ty)))
+                };
+
+                match elements.last_mut() {
+                    Some(element) => element.properties.push(property),
+                    None          => return error(l, "property", "property declared outside of an element.".to_string())
+                }
+            },
+            Some("end_header")                        => break,
+            None | Some("comment") | Some("obj_info") => { },
+            Some(w) => warnings.push(ParseError::new(l, w, "unknown tag, line ignored.".to_string()))
+        }
+    }
+
+    match format {
+        Some(format) => Ok((format, elements, data.slice_from(begin), l)),
+        None         => error(l, "format", "missing format.".to_string())
+    }
+}
+
+/// Writes the content of a PLY file.
+pub fn write_file(mesh: &PlyMesh, path: &Path, binary: bool) -> IoResult<()> {
+    let mut file = BufferedWriter::new(try!(File::create(path)));
+
+    write(&mut file, mesh, binary)
+}
+
+/// Writes the meshes of a scene node and its children to a PLY file.
+///
+/// The world transformation and scale of each object are applied to its vertices and normals. A
+/// file without any vertex is written if the node contains no object.
+pub fn write_node(node: &SceneNode, path: &Path, binary: bool) -> IoResult<()> {
+    let mesh = match node.to_world_trimesh() {
+        Some(mut mesh) => {
+            mesh.unify_index_buffer();
+
+            let TriMesh { coords, normals, uvs, indices } = mesh;
+
+            PlyMesh {
+                coords:  coords,
+                normals: normals,
+                uvs:     uvs,
+                colors:  None,
+                faces:   indices.unwrap_unified()
+            }
+        },
+        None => PlyMesh {
+            coords:  Vec::new(),
+            normals: None,
+            uvs:     None,
+            colors:  None,
+            faces:   Vec::new()
+        }
+    };
+
+    write_file(&mesh, path, binary)
+}
+
+/// Writes the content of a PLY file, with the ASCII or the binary little-endian format.
+///
+/// Colors are written as `uchar`. The `face` element is omitted for point clouds.
+pub fn write(w: &mut Writer, mesh: &PlyMesh, binary: bool) -> IoResult<()> {
+    try!(w.write_line("ply"));
+    try!(w.write_line(if binary { "format binary_little_endian 1.0" } else { "format ascii 1.0" }));
+    try!(w.write_line("comment written by kiss3d"));
+    try!(w.write_line(format!("element vertex {}", mesh.coords.len()).as_slice()));
+    try!(w.write_line("property float x\nproperty float y\nproperty float z"));
+
+    if mesh.normals.is_some() {
+        try!(w.write_line("property float nx\nproperty float ny\nproperty float nz"));
+    }
+
+    if mesh.uvs.is_some() {
+        try!(w.write_line("property float u\nproperty float v"));
+    }
+
+    if mesh.colors.is_some() {
+        try!(w.write_line("property uchar red\nproperty uchar green\nproperty uchar blue"));
+    }
+
+    if !mesh.is_point_cloud() {
+        try!(w.write_line(format!("element face {}", mesh.faces.len()).as_slice()));
+        try!(w.write_line("property list uchar uint vertex_indices"));
+    }
+
+    try!(w.write_line("end_header"));
+
+    for (i, p) in mesh.coords.iter().enumerate() {
+        let mut floats = vec!(p.x, p.y, p.z);
+        let mut bytes  = Vec::new();
+
+        for normals in mesh.normals.iter() {
+            floats.push_all(&[ normals[i].x, normals[i].y, normals[i].z ])
+        }
+
+        for uvs in mesh.uvs.iter() {
+            floats.push_all(&[ uvs[i].x, uvs[i].y ])
+        }
+
+        for colors in mesh.colors.iter() {
+            let c = |v: f32| (v.max(0.0).min(1.0) * 255.0).round() as u8;
+            bytes.push_all(&[ c(colors[i].x), c(colors[i].y), c(colors[i].z) ])
+        }
+
+        if binary {
+            for f in floats.iter() {
+                try!(w.write_le_f32(*f));
+            }
+
+            try!(w.write(bytes.as_slice()));
+        }
+        else {
+            let mut words: Vec<String> = floats.iter().map(|f| f.to_string()).collect();
+            words.extend(bytes.iter().map(|b| b.to_string()));
+
+            try!(w.write_line(words.connect(" ").as_slice()));
+        }
+    }
+
+    for f in mesh.faces.iter() {
+        if binary {
+            try!(w.write_u8(3));
+            try!(w.write_le_u32(f.x));
+            try!(w.write_le_u32(f.y));
+            try!(w.write_le_u32(f.z));
+        }
+        else {
+            try!(w.write_line(format!("3 {} {} {}", f.x, f.y, f.z).as_slice()));
+        }
+    }
+
+    w.flush()
+}
+
+#[cfg(test)]
+mod test {
+    use na::{Pnt2, Pnt3, Vec3};
+    use loader::ParseError;
+    use super::{parse, write, PlyMesh};
+
+    fn mesh() -> PlyMesh {
+        PlyMesh {
+            coords:  vec!(Pnt3::new(0.0f32, 0.0, 0.0), Pnt3::new(1.0, 0.0, 0.0), Pnt3::new(0.0, 1.0, 0.0), Pnt3::new(0.5, -2.0, 1.0)),
+            normals: Some(vec!(Vec3::z(), Vec3::z(), Vec3::z(), Vec3::y())),
+            uvs:     Some(vec!(Pnt2::new(0.0f32, 0.0), Pnt2::new(1.0, 0.0), Pnt2::new(0.0, 1.0), Pnt2::new(0.5, 0.25))),
+            colors:  None,
+            faces:   vec!(Vec3::new(0u32, 1, 2), Vec3::new(0, 3, 1))
+        }
+    }
+
+    fn point_cloud() -> PlyMesh {
+        PlyMesh {
+            coords:  vec!(Pnt3::new(0.0f32, 0.0, 0.0), Pnt3::new(1.0, -1.0, 0.5)),
+            normals: None,
+            uvs:     None,
+            colors:  Some(vec!(Pnt3::new(1.0f32, 0.0, 0.0), Pnt3::new(0.0, 1.0, 1.0))),
+            faces:   Vec::new()
+        }
+    }
+
+    fn round_trip(expected: &PlyMesh, binary: bool) {
+        let mut data = Vec::new();
+        write(&mut data, expected, binary).unwrap();
+
+        let (mesh, warnings) = parse(data.as_slice()).unwrap();
+
+        assert!(warnings.is_empty());
+        assert_eq!(mesh.coords, expected.coords);
+        assert_eq!(mesh.normals, expected.normals);
+        assert_eq!(mesh.uvs, expected.uvs);
+        assert_eq!(mesh.colors, expected.colors);
+        assert_eq!(mesh.faces, expected.faces);
+        assert_eq!(mesh.is_point_cloud(), expected.is_point_cloud());
+    }
+
+    #[test]
+    fn ascii_round_trip() {
+        round_trip(&mesh(), false);
+        round_trip(&point_cloud(), false);
+    }
+
+    #[test]
+    fn binary_round_trip() {
+        round_trip(&mesh(), true);
+        round_trip(&point_cloud(), true);
+    }
+
+    #[test]
+    fn polygons_are_triangulated() {
+        let data = b"ply\nformat ascii 1.0\nelement vertex 4\nproperty float x\nproperty float y\nproperty float z\n\
+                     element face 1\nproperty list uchar int vertex_indices\nend_header\n\
+                     0 0 0\n1 0 0\n1 1 0\n0 1 0\n4 0 1 2 3\n";
+        let (mesh, _) = parse(data).unwrap();
+
+        assert_eq!(mesh.faces, vec!(Vec3::new(0u32, 1, 2), Vec3::new(0, 2, 3)));
+    }
+
+    fn parse_error(data: &[u8]) -> ParseError {
+        match parse(data) {
+            Ok(_)  => panic!("the PLY file should have been rejected."),
+            Err(e) => e
+        }
+    }
+
+    #[test]
+    fn invalid_indices() {
+        let header = "ply\nformat ascii 1.0\nelement vertex 3\nproperty float x\nproperty float y\nproperty float z\n\
+                      element face 1\nproperty list uchar float vertex_indices\nend_header\n\
+                      0 0 0\n1 0 0\n0 1 0\n";
+
+        let e = parse_error(format!("{}3 0 1 -2\n", header).as_bytes());
+        assert_eq!((e.line, e.tag.as_slice()), (13, "face"));
+        assert!(e.reason.as_slice().starts_with("invalid vertex index"));
+
+        let e = parse_error(format!("{}3 0 1 1.5\n", header).as_bytes());
+        assert!(e.reason.as_slice().starts_with("invalid vertex index"));
+
+        let e = parse_error(format!("{}-3 0 1 2\n", header).as_bytes());
+        assert!(e.reason.as_slice().starts_with("invalid list size"));
+
+        let e = parse_error(format!("{}2.5 0 1 2\n", header).as_bytes());
+        assert!(e.reason.as_slice().starts_with("invalid list size"));
+    }
+
+    #[test]
+    fn invalid_binary_indices() {
+        let mut data = b"ply\nformat binary_little_endian 1.0\nelement vertex 3\nproperty float x\nproperty float y\n\
+                         property float z\nelement face 1\nproperty list uchar int vertex_indices\nend_header\n".to_vec();
+
+        for _ in range(0u, 9 * 4) {
+            data.push(0)
+        }
+
+        data.push_all(&[3, 0, 0, 0, 0, 1, 0, 0, 0, 0xFF, 0xFF, 0xFF, 0xFF]);
+
+        let e = parse_error(data.as_slice());
+        assert_eq!(e.tag.as_slice(), "face");
+        assert!(e.reason.as_slice().starts_with("invalid vertex index"));
+    }
+
+    #[test]
+    fn huge_element_count() {
+        let data = b"ply\nformat binary_little_endian 1.0\nelement vertex 4000000000\nproperty float x\n\
+                     property float y\nproperty float z\nend_header\n";
+
+        assert!(parse(data).is_err());
+    }
+}
@@ -0,0 +1,305 @@
+//! Simplistic STL loader and writer.
+//!
+//! Both the ASCII and the binary formats are supported. STL files store independent triangles so
+//! loaded meshes have three vertices per face, all sharing the face normal.
+
+use std::str;
+use std::io::fs::File;
+use std::io::{BufReader, BufferedWriter, Reader, Writer, IoResult};
+use na::{Pnt3, Vec3};
+use na;
+use ncollide::procedural::{TriMesh, TriMesh3, IndexBuffer};
+use scene::SceneNode;
+use loader::{LoadError, ParseError};
+
+fn error<T>(line: uint, tag: &str, reason: String) -> Result<T, ParseError> {
+    Err(ParseError::new(line, tag, reason))
+}
+
+/// Parses a STL file.
+///
+/// Returns the mesh and the list of warnings about ignored lines.
+pub fn parse_file(path: &Path) -> Result<(TriMesh3<f32>, Vec<ParseError>), LoadError> {
+    match File::open(path).and_then(|mut file| file.read_to_end()) {
+        Ok(data) => parse(data.as_slice()).map_err(|e| LoadError::Parse(e)),
+        Err(e)   => Err(LoadError::Io(e))
+    }
+}
+
+/// Parses the content of an ASCII or binary STL file.
+///
+/// Returns the mesh and the list of warnings about ignored lines.
+pub fn parse(data: &[u8]) -> Result<(TriMesh3<f32>, Vec<ParseError>), ParseError> {
+    // Some binary files start with `solid` too, so rely on the size announced by the header. Some
+    // exporters append data after the triangles: it is ignored. The text of an ASCII file read as
+    // a triangle count is always too large for this test to succeed.
+    let is_binary = data.len() >= 84 && {
+        let nfaces = BufReader::new(data.slice(80, 84)).read_le_u32().unwrap() as uint;
+        data.len() >= 84 + nfaces * 50
+    };
+
+    if is_binary {
+        parse_binary(data).map(|mesh| (mesh, Vec::new()))
+    }
+    else if data.starts_with(b"solid") {
+        match str::from_utf8(data) {
+            Some(s) => parse_ascii(s),
+            None    => error(0, "", "the ASCII file is not valid UTF-8.".to_string())
+        }
+    }
+    else {
+        error(0, "", "the file is too small for its number of triangles.".to_string())
+    }
+}
+
+fn parse_binary(data: &[u8]) -> Result<TriMesh3<f32>, ParseError> {
+    let mut reader  = BufReader::new(data.slice_from(80));
+    let nfaces      = reader.read_le_u32().unwrap() as uint;
+    let mut coords  = Vec::with_capacity(nfaces * 3);
+    let mut normals = Vec::with_capacity(nfaces * 3);
+
+    for _ in range(0u, nfaces) {
+        let mut v = [0.0f32, ..12];
+
+        for c in v.iter_mut() {
+            *c = reader.read_le_f32().unwrap()
+        }
+
+        // Attribute byte count.
+        let _ = reader.read_le_u16();
+
+        let a = Pnt3::new(v[3], v[4],  v[5]);
+        let b = Pnt3::new(v[6], v[7],  v[8]);
+        let c = Pnt3::new(v[9], v[10], v[11]);
+
+        push_triangle(&mut coords, &mut normals, Vec3::new(v[0], v[1], v[2]), a, b, c);
+    }
+
+    Ok(build_trimesh(coords, normals))
+}
+
+fn parse_ascii(string: &str) -> Result<(TriMesh3<f32>, Vec<ParseError>), ParseError> {
+    let mut coords   = Vec::new();
+    let mut normals  = Vec::new();
+    let mut warnings = Vec::new();
+    let mut normal   = na::zero::<Vec3<f32>>();
+    let mut facet    = Vec::new();
+
+    for (l, line) in string.lines_any().enumerate() {
+        let l         = l + 1;
+        let mut words = line.words();
+
+        match words.next() {
+            None                                   => { },
+            Some("solid") | Some("endsolid")       => { },
+            Some("outer") | Some("endloop")        => { },
+            Some("facet") => {
+                if words.next() != Some("normal") {
+                    return error(l, "facet", "expected `facet normal`.".to_string());
+                }
+
+                let n  = try!(parse_vec3(l, "facet", words));
+                normal = Vec3::new(n.x, n.y, n.z);
+                facet.clear();
+            },
+            Some("vertex") => {
+                if facet.len() == 3 {
+                    return error(l, "vertex", "only triangular facets are supported.".to_string());
+                }
+
+                facet.push(try!(parse_vec3(l, "vertex", words)))
+            },
+            Some("endfacet") => {
+                if facet.len() != 3 {
+                    return error(l, "endfacet", "a facet must have exactly 3 vertices.".to_string());
+                }
+
+                push_triangle(&mut coords, &mut normals, normal, facet[0], facet[1], facet[2]);
+                facet.clear();
+            },
+            Some(w) => warnings.push(ParseError::new(l, w, "unknown tag, line ignored.".to_string()))
+        }
+    }
+
+    Ok((build_trimesh(coords, normals), warnings))
+}
+
+fn parse_vec3<'a, I: Iterator<&'a str>>(l: uint, tag: &str, mut words: I) -> Result<Pnt3<f32>, ParseError> {
+    let mut res = [0.0f32, ..3];
+
+    for c in res.iter_mut() {
+        match words.next().and_then(|w| from_str::<f32>(w)) {
+            Some(v) => *c = v,
+            None    => return error(l, tag, "expected 3 numbers.".to_string())
+        }
+    }
+
+    Ok(Pnt3::new(res[0], res[1], res[2]))
+}
+
+fn push_triangle(coords:  &mut Vec<Pnt3<f32>>,
+                 normals: &mut Vec<Vec3<f32>>,
+                 normal:  Vec3<f32>,
+                 a:       Pnt3<f32>,
+                 b:       Pnt3<f32>,
+                 c:       Pnt3<f32>) {
+    // Many exporters do not bother writing the normals.
+    let normal = if na::sqnorm(&normal) == 0.0 {
+        let n = na::cross(&(b - a), &(c - a));
+
+        if na::sqnorm(&n) == 0.0 { n } else { na::normalize(&n) }
+    }
+    else {
+        na::normalize(&normal)
+    };
+
+    coords.push(a);
+    coords.push(b);
+    coords.push(c);
+    normals.push(normal);
+    normals.push(normal);
+    normals.push(normal);
+}
+
+fn build_trimesh(coords: Vec<Pnt3<f32>>, normals: Vec<Vec3<f32>>) -> TriMesh3<f32> {
+    let faces = range(0u, coords.len() / 3).map(|i| {
+        let i = (i * 3) as u32;
+        Vec3::new(i, i + 1, i + 2)
+    }).collect();
+
+    TriMesh::new(coords, Some(normals), None, Some(IndexBuffer::Unified(faces)))
+}
+
+/// Writes a triangle mesh to a STL file.
+///
+/// Face normals are computed from the vertex positions.
+pub fn write_file(mesh: &TriMesh3<f32>, path: &Path, binary: bool) -> IoResult<()> {
+    let mut file = BufferedWriter::new(try!(File::create(path)));
+
+    write(&mut file, mesh, binary)
+}
+
+/// Writes the meshes of a scene node and its children to a STL file.
+///
+/// The world transformation and scale of each object are applied to its vertices. An empty file
+/// is written if the node contains no object.
+pub fn write_node(node: &SceneNode, path: &Path, binary: bool) -> IoResult<()> {
+    let mesh = node.to_world_trimesh().unwrap_or_else(|| TriMesh::new(Vec::new(), None, None, None));
+
+    write_file(&mesh, path, binary)
+}
+
+/// Writes a triangle mesh with the ASCII or the binary STL format.
+///
+/// Face normals are computed from the vertex positions.
+pub fn write(w: &mut Writer, mesh: &TriMesh3<f32>, binary: bool) -> IoResult<()> {
+    let mut mesh = mesh.clone();
+    mesh.unify_index_buffer();
+
+    let TriMesh { coords, indices, .. } = mesh;
+    let faces = indices.unwrap_unified();
+
+    let triangles = faces.iter().map(|f| {
+        let a = coords[f.x as uint];
+        let b = coords[f.y as uint];
+        let c = coords[f.z as uint];
+        let n = na::cross(&(b - a), &(c - a));
+        let n = if na::sqnorm(&n) == 0.0 { n } else { na::normalize(&n) };
+
+        (n, a, b, c)
+    });
+
+    if binary {
+        try!(w.write([0u8, ..80].as_slice()));
+        try!(w.write_le_u32(faces.len() as u32));
+
+        for (n, a, b, c) in triangles {
+            for v in [n.x, n.y, n.z, a.x, a.y, a.z, b.x, b.y, b.z, c.x, c.y, c.z].iter() {
+                try!(w.write_le_f32(*v));
+            }
+
+            try!(w.write_le_u16(0));
+        }
+    }
+    else {
+        try!(w.write_line("solid kiss3d"));
+
+        for (n, a, b, c) in triangles {
+            try!(w.write_line(format!("facet normal {} {} {}", n.x, n.y, n.z).as_slice()));
+            try!(w.write_line("  outer loop"));
+
+            for p in [a, b, c].iter() {
+                try!(w.write_line(format!("    vertex {} {} {}", p.x, p.y, p.z).as_slice()));
+            }
+
+            try!(w.write_line("  endloop"));
+            try!(w.write_line("endfacet"));
+        }
+
+        try!(w.write_line("endsolid kiss3d"));
+    }
+
+    w.flush()
+}
+
+#[cfg(test)]
+mod test {
+    use na::{Pnt3, Vec3};
+    use ncollide::procedural::{TriMesh, TriMesh3, IndexBuffer};
+    use super::{parse, write};
+
+    fn triangles() -> TriMesh3<f32> {
+        let coords = vec!(Pnt3::new(0.0f32, 0.0, 0.0), Pnt3::new(1.0, 0.0, 0.0), Pnt3::new(0.0, 1.0, 0.0), Pnt3::new(0.0, 0.0, 1.0));
+        let faces  = vec!(Vec3::new(0u32, 1, 2), Vec3::new(0, 3, 1));
+
+        TriMesh::new(coords, None, None, Some(IndexBuffer::Unified(faces)))
+    }
+
+    fn round_trip(binary: bool) {
+        let mut data = Vec::new();
+        write(&mut data, &triangles(), binary).unwrap();
+
+        let (mesh, warnings) = parse(data.as_slice()).unwrap();
+        let TriMesh { coords, normals, indices, .. } = mesh;
+
+        assert!(warnings.is_empty());
+        assert_eq!(coords, vec!(Pnt3::new(0.0f32, 0.0, 0.0), Pnt3::new(1.0, 0.0, 0.0), Pnt3::new(0.0, 1.0, 0.0),
+                                Pnt3::new(0.0, 0.0, 0.0), Pnt3::new(0.0, 0.0, 1.0), Pnt3::new(1.0, 0.0, 0.0)));
+        assert_eq!(indices.unwrap_unified(), vec!(Vec3::new(0u32, 1, 2), Vec3::new(3, 4, 5)));
+        assert_eq!(normals.unwrap(), vec!(Vec3::z(), Vec3::z(), Vec3::z(), Vec3::y(), Vec3::y(), Vec3::y()));
+    }
+
+    #[test]
+    fn ascii_round_trip() {
+        round_trip(false)
+    }
+
+    #[test]
+    fn binary_round_trip() {
+        round_trip(true)
+    }
+
+    #[test]
+    fn truncated_binary() {
+        let mut data = Vec::new();
+        write(&mut data, &triangles(), true).unwrap();
+        data.pop();
+
+        match parse(data.as_slice()) {
+            Ok(_)  => panic!("the truncated file should have been rejected."),
+            Err(e) => assert_eq!(e.reason.as_slice(), "the file is too small for its number of triangles.")
+        }
+    }
+
+    #[test]
+    fn trailing_binary_data() {
+        let mut data = Vec::new();
+        write(&mut data, &triangles(), true).unwrap();
+        data.push_all(b"trailing data");
+
+        let (mesh, warnings) = parse(data.as_slice()).unwrap();
+
+        assert!(warnings.is_empty());
+        assert_eq!(mesh.coords.len(), 6);
+    }
+}
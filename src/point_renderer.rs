@@ -1,21 +1,139 @@
 //! A batched point renderer.
 
+use std::rc::Rc;
+use std::num::Float;
 use gl;
 use gl::types::*;
-use na::{Pnt3, Mat4};
-use resource::{BufferType, AllocationType, GPUVector, Shader, ShaderAttribute, ShaderUniform};
+use na::{Pnt3, Vec3, Mat4, Iso3};
+use na;
+use resource::{BufferType, AllocationType, GPUVector, Shader, ShaderAttribute, ShaderUniform, Texture};
+use builtin::SpritesShader;
 use camera::Camera;
 
 #[path = "error.rs"]
 mod error;
 
+/// The size of a sprite.
+#[deriving(Clone, Copy, PartialEq, Show)]
+pub enum PointSize {
+    /// A diameter in pixels, independent from the distance to the camera.
+    Pixels(f32),
+    /// A diameter in world units.
+    World(f32)
+}
+
+/// The shape of a sprite.
+#[deriving(Clone, Copy, PartialEq, Show)]
+pub enum PointShape {
+    /// A square facing the camera.
+    Square,
+    /// A disk facing the camera.
+    Round,
+    /// A disk shaded like a sphere lit from the camera.
+    Sphere
+}
+
+/// The appearance shared by a set of sprites.
+#[deriving(Clone)]
+pub struct PointSprite {
+    /// The shape of the sprites.
+    pub shape:   PointShape,
+    /// A texture mapped on each sprite and multiplied by its color. Transparent texels are not
+    /// drawn.
+    pub texture: Option<Rc<Texture>>
+}
+
+impl PointSprite {
+    /// Creates a sprite style without texture.
+    pub fn new(shape: PointShape) -> PointSprite {
+        PointSprite {
+            shape:   shape,
+            texture: None
+        }
+    }
+
+    fn same_as(&self, other: &PointSprite) -> bool {
+        self.shape == other.shape &&
+        self.texture.as_ref().map(|t| t.id()) == other.texture.as_ref().map(|t| t.id())
+    }
+}
+
+/// A set of sprites kept on the GPU.
+///
+/// Sprites are expanded into camera-facing quads, so their size does not depend on
+/// `glPointSize`.
+pub struct Sprites {
+    // Six vertices per sprite, each made of its center, its color and its corner with its size.
+    vertices: GPUVector<Pnt3<GLfloat>>
+}
+
+impl Sprites {
+    /// Creates an empty set of sprites.
+    pub fn new() -> Sprites {
+        Sprites {
+            vertices: GPUVector::new(Vec::new(), BufferType::Array, AllocationType::DynamicDraw)
+        }
+    }
+
+    /// The number of sprites.
+    pub fn len(&self) -> uint {
+        self.vertices.len() / 18
+    }
+
+    /// Removes every sprite.
+    pub fn clear(&mut self) {
+        for vertices in self.vertices.data_mut().iter_mut() {
+            vertices.clear()
+        }
+    }
+
+    /// Adds a sprite.
+    pub fn push(&mut self, pt: Pnt3<GLfloat>, color: Pnt3<GLfloat>, size: PointSize) {
+        // World sizes are stored as negative diameters.
+        let diameter = match size {
+            PointSize::Pixels(d) => d.abs(),
+            PointSize::World(d)  => -d.abs()
+        };
+
+        let corners = [ (-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, -1.0), (1.0, 1.0), (-1.0, 1.0) ];
+
+        for vertices in self.vertices.data_mut().iter_mut() {
+            for &(x, y) in corners.iter() {
+                vertices.push(pt);
+                vertices.push(color);
+                vertices.push(Pnt3::new(x, y, diameter));
+            }
+        }
+    }
+
+    /// Renders the sprites with the given style.
+    pub fn render(&mut self,
+                  sprite:    &PointSprite,
+                  transform: &Iso3<f32>,
+                  scale:     &Vec3<f32>,
+                  pass:      uint,
+                  camera:    &mut Camera) {
+        if self.len() == 0 {
+            return
+        }
+
+        SpritesShader::get_global_shader(|s| s.render(sprite, pass, transform, scale, camera, &mut self.vertices))
+    }
+}
+
 /// Structure which manages the display of short-living points.
+///
+/// Plain points are drawn as 1-pixel `GL_POINTS`. Sprites are drawn as camera-facing quads and
+/// batched by consecutive sprite styles.
 pub struct PointRenderer {
     shader:     Shader,
     pos:        ShaderAttribute<Pnt3<f32>>,
     color:      ShaderAttribute<Pnt3<f32>>,
     view:       ShaderUniform<Mat4<f32>>,
-    points:     GPUVector<Pnt3<GLfloat>>
+    points:     GPUVector<Pnt3<GLfloat>>,
+
+    sprites:      Vec<(PointSprite, Sprites)>,
+    used_sprites: uint
 }
 
 impl PointRenderer {
@@ -30,13 +148,16 @@ impl PointRenderer {
             pos:        shader.get_attrib::<Pnt3<f32>>("position").unwrap(),
             color:      shader.get_attrib::<Pnt3<f32>>("color").unwrap(),
             view:       shader.get_uniform::<Mat4<f32>>("view").unwrap(),
-            shader:     shader
+            shader:     shader,
+
+            sprites:      Vec::new(),
+            used_sprites: 0
         }
     }
  
     /// Indicates whether some points have to be drawn.
     pub fn needs_rendering(&self) -> bool {
-        self.points.len() != 0
+        self.points.len() != 0 || self.used_sprites != 0
     }
 
     /// Adds a line to be drawn during the next frame. Lines are not persistent between frames.
@@ -48,8 +169,30 @@ impl PointRenderer {
         }
     }
 
+    /// Adds a sprite to be drawn during the next frame. Sprites are not persistent between frames.
+    ///
+    /// Consecutive sprites sharing the same `sprite` style are drawn with a single draw call.
+    pub fn draw_sprite(&mut self, pt: Pnt3<GLfloat>, color: Pnt3<GLfloat>, size: PointSize, sprite: &PointSprite) {
+        if self.used_sprites == 0 || !self.sprites[self.used_sprites - 1].0.same_as(sprite) {
+            if self.used_sprites == self.sprites.len() {
+                self.sprites.push((sprite.clone(), Sprites::new()));
+            }
+            else {
+                self.sprites[self.used_sprites].0 = sprite.clone();
+            }
+
+            self.used_sprites = self.used_sprites + 1;
+        }
+
+        self.sprites[self.used_sprites - 1].1.push(pt, color, size)
+    }
+
     /// Actually draws the points.
     pub fn render(&mut self, pass: uint, camera: &mut Camera) {
+        if self.used_sprites != 0 {
+            self.render_sprites(pass, camera)
+        }
+
         if self.points.len() == 0 { return }
 
         self.shader.use_program();
@@ -70,6 +213,15 @@ impl PointRenderer {
             points.clear()
         }
     }
+
+    fn render_sprites(&mut self, pass: uint, camera: &mut Camera) {
+        for &mut (ref sprite, ref mut sprites) in self.sprites.slice_to_mut(self.used_sprites).iter_mut() {
+            sprites.render(sprite, &na::one(), &na::one(), pass, camera);
+            sprites.clear();
+        }
+
+        self.used_sprites = 0;
+    }
 }
 
 /// Vertex shader used by the material to display line.
@@ -0,0 +1,152 @@
+//! Per-instance data of objects drawn several times with a single draw call.
+
+use gl;
+use gl::types::*;
+use na::{Pnt3, Vec3, Iso3};
+use na;
+use resource::{GPUVector, BufferType, AllocationType, ShaderAttribute};
+
+/// The transformation, scale and color of each copy of an instanced object.
+///
+/// The data is kept on RAM and uploaded to the GPU as vertex attributes advancing once per
+/// instance. An instance vertex is `transformation * (scale ⊙ vertex)`, which is then
+/// transformed by the object world transformation and scale.
+///
+/// Instanced draws require OpenGL 3.3 or the `ARB_instanced_arrays` extension. Without them,
+/// materials draw the object once per instance with `set_constants` instead.
+pub struct Instances {
+    instances:    Vec<(Iso3<GLfloat>, Vec3<GLfloat>, Pnt3<GLfloat>)>,
+    translations: GPUVector<Vec3<GLfloat>>,
+    deformations: GPUVector<Vec3<GLfloat>>, // three columns per instance.
+    colors:       GPUVector<Pnt3<GLfloat>>
+}
+
+impl Instances {
+    /// Whether the current OpenGL context can draw every instance with a single draw call.
+    ///
+    /// This should be checked once, e.g., when a material is created.
+    pub fn is_supported() -> bool {
+        gl::DrawElementsInstanced::is_loaded() && gl::VertexAttribDivisor::is_loaded()
+    }
+
+    /// Creates a new set of instances.
+    pub fn new(instances: Vec<(Iso3<GLfloat>, Vec3<GLfloat>, Pnt3<GLfloat>)>) -> Instances {
+        let mut res = Instances {
+            instances:    Vec::new(),
+            translations: GPUVector::new(Vec::new(), BufferType::Array, AllocationType::DynamicDraw),
+            deformations: GPUVector::new(Vec::new(), BufferType::Array, AllocationType::DynamicDraw),
+            colors:       GPUVector::new(Vec::new(), BufferType::Array, AllocationType::DynamicDraw)
+        };
+
+        res.set(instances);
+
+        res
+    }
+
+    /// The number of instances.
+    #[inline]
+    pub fn len(&self) -> uint {
+        self.instances.len()
+    }
+
+    /// The transformation, scale and color of each instance.
+    #[inline]
+    pub fn instances<'a>(&'a self) -> &'a [(Iso3<GLfloat>, Vec3<GLfloat>, Pnt3<GLfloat>)] {
+        self.instances.as_slice()
+    }
+
+    /// Replaces every instance.
+    ///
+    /// The GPU buffers are reused, so this can be called at each frame.
+    pub fn set(&mut self, instances: Vec<(Iso3<GLfloat>, Vec3<GLfloat>, Pnt3<GLfloat>)>) {
+        {
+            let translations = self.translations.data_mut().as_mut().unwrap();
+            let deformations = self.deformations.data_mut().as_mut().unwrap();
+            let colors       = self.colors.data_mut().as_mut().unwrap();
+
+            translations.clear();
+            deformations.clear();
+            colors.clear();
+
+            for &(ref transform, ref scale, ref color) in instances.iter() {
+                translations.push(transform.translation);
+                deformations.push(na::rotate(transform, &Vec3::x()) * scale.x);
+                deformations.push(na::rotate(transform, &Vec3::y()) * scale.y);
+                deformations.push(na::rotate(transform, &Vec3::z()) * scale.z);
+                colors.push(*color);
+            }
+        }
+
+        self.instances = instances;
+    }
+
+    /// Binds the instances data to shader attributes.
+    ///
+    /// The colors are not bound if `color` is `None`. Nothing must be drawn if there is no
+    /// instance.
+    pub fn bind(&mut self,
+                translation: &mut ShaderAttribute<Vec3<GLfloat>>,
+                deformation: &mut [ShaderAttribute<Vec3<GLfloat>>],
+                color:       Option<&mut ShaderAttribute<Pnt3<GLfloat>>>) {
+        assert!(!self.instances.is_empty(), "Cannot bind an empty set of instances.");
+
+        translation.enable();
+        translation.bind(&mut self.translations);
+        translation.set_divisor(1);
+
+        for (i, d) in deformation.iter_mut().enumerate() {
+            d.enable();
+            d.bind_sub_buffer(&mut self.deformations, 2, i);
+            d.set_divisor(1);
+        }
+
+        for color in color.into_iter() {
+            color.enable();
+            color.bind(&mut self.colors);
+            color.set_divisor(1);
+        }
+    }
+
+    /// Sets the data of the instance `i` as constant values of disabled shader attributes.
+    ///
+    /// This is the fallback of `bind` for contexts without instanced draws: the object is then
+    /// drawn once per instance, after calling this method.
+    pub fn set_constants(&self,
+                         i:           uint,
+                         translation: &mut ShaderAttribute<Vec3<GLfloat>>,
+                         deformation: &mut [ShaderAttribute<Vec3<GLfloat>>],
+                         color:       Option<&mut ShaderAttribute<Pnt3<GLfloat>>>) {
+        let translations = self.translations.data().as_ref().unwrap();
+        let deformations = self.deformations.data().as_ref().unwrap();
+        let colors       = self.colors.data().as_ref().unwrap();
+
+        translation.set_constant(&translations[i]);
+
+        for (j, d) in deformation.iter_mut().enumerate() {
+            d.set_constant(&deformations[i * 3 + j]);
+        }
+
+        for color in color.into_iter() {
+            color.set_constant(&colors[i]);
+        }
+    }
+
+    /// Unbinds the instances data from shader attributes.
+    pub fn unbind(&mut self,
+                  translation: &mut ShaderAttribute<Vec3<GLfloat>>,
+                  deformation: &mut [ShaderAttribute<Vec3<GLfloat>>],
+                  color:       Option<&mut ShaderAttribute<Pnt3<GLfloat>>>) {
+        translation.set_divisor(0);
+        translation.disable();
+
+        for d in deformation.iter_mut() {
+            d.set_divisor(0);
+            d.disable();
+        }
+
+        for color in color.into_iter() {
+            color.set_divisor(0);
+            color.disable();
+        }
+    }
+}
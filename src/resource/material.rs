@@ -2,9 +2,10 @@
 
 use na::{Vec3, Iso3};
 use camera::Camera;
-use light::Light;
+use light::LightSource;
 use scene::ObjectData;
 use resource::Mesh;
+use shadow_map::ShadowMap;
 
 /// Trait implemented by materials.
 pub trait Material {
@@ -15,7 +16,14 @@ pub trait Material {
               transform: &Iso3<f32>,
               scale:     &Vec3<f32>,
               camera:    &mut Camera,    // FIXME: replace those two arguments by
-              light:     &Light,         // a structure with all environment datas
+              lights:    &[LightSource], // a structure with all environment datas
               data:      &ObjectData,
               mesh:      &mut Mesh);
+
+    /// Sets the shadow map to be used by the next calls to `render`.
+    ///
+    /// This is `None` when shadows are disabled. Materials that do not support shadows may ignore
+    /// this.
+    #[inline]
+    fn set_shadow_map(&mut self, _shadow_map: Option<&ShadowMap>) { }
 }
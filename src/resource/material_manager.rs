@@ -4,7 +4,7 @@ use std::rc::Rc;
 use std::cell::RefCell;
 use std::collections::HashMap;
 use resource::Material;
-use builtin::{ObjectMaterial, NormalsMaterial, UvsMaterial};
+use builtin::{ObjectMaterial, NormalsMaterial, UvsMaterial, FlatMaterial};
 
 thread_local!(static KEY_MATERIAL_MANAGER: RefCell<MaterialManager> = RefCell::new(MaterialManager::new()));
 
@@ -13,6 +13,7 @@ thread_local!(static KEY_MATERIAL_MANAGER: RefCell<MaterialManager> = RefCell::n
 /// Upon construction, it contains:
 /// * the `object` material, used as the default to render objects.
 /// * the `normals` material, used do display an object normals.
+/// * the `flat` material, used to display an object color and texture without lighting.
 ///
 /// It keeps a cache of already-loaded materials. Note that this is only a cache, nothing more.
 /// Thus, its usage is not required to load materials.
@@ -36,6 +37,9 @@ impl MaterialManager {
         let um = Rc::new(RefCell::new(box UvsMaterial::new() as Box<Material + 'static>));
         let _ = materials.insert("uvs".to_string(), um.clone());
 
+        let fm = Rc::new(RefCell::new(box FlatMaterial::new() as Box<Material + 'static>));
+        let _ = materials.insert("flat".to_string(), fm.clone());
+
         MaterialManager {
             default_material: om,
             materials:        materials
@@ -1,6 +1,5 @@
 //! A resource manager to load meshes.
 
-use std::io::IoResult;
 use std::rc::Rc;
 use std::cell::RefCell;
 use std::collections::HashMap;
@@ -9,6 +8,7 @@ use ncollide::procedural;
 use resource::Mesh;
 use loader::obj;
 use loader::mtl::MtlMaterial;
+use loader::{LoadError, ParseError};
 
 thread_local!(static KEY_MESH_MANAGER: RefCell<MeshManager> = RefCell::new(MeshManager::new()));
 
@@ -69,9 +69,11 @@ impl MeshManager {
 
     // FIXME: is this the right place to put this?
     /// Loads the meshes described by an obj file.
+    ///
+    /// Returns the meshes and the list of warnings about ignored data.
     pub fn load_obj(path: &Path, mtl_dir: &Path, geometry_name: &str)
-                    -> IoResult<Vec<(String, Rc<RefCell<Mesh>>, Option<MtlMaterial>)>> {
-        obj::parse_file(path, mtl_dir, geometry_name).map(|ms| {
+                    -> Result<(Vec<(String, Rc<RefCell<Mesh>>, Option<MtlMaterial>)>, Vec<ParseError>), LoadError> {
+        obj::parse_file(path, mtl_dir, geometry_name).map(|(ms, warnings)| {
             let mut res = Vec::new();
 
             for (n, m, mat) in ms.into_iter() {
@@ -80,7 +82,7 @@ impl MeshManager {
                 res.push((n, m, mat));
             }
 
-            res
+            (res, warnings)
         })
     }
 }
@@ -1,7 +1,7 @@
 //! GPU resource managers
 
 pub use resource::framebuffer_manager::{FramebufferManager, RenderTarget, OffscreenBuffers};
-pub use resource::texture_manager::{Texture, TextureManager};
+pub use resource::texture_manager::{Texture, TextureManager, TextureOptions, TextureWrap, TextureFilter};
 pub use resource::material::Material;
 pub use resource::material_manager::MaterialManager;
 pub use resource::mesh_manager::MeshManager;
@@ -9,6 +9,7 @@ pub use resource::shader::{Shader, ShaderAttribute, ShaderUniform};
 pub use resource::gpu_vector::{GPUVector, BufferType, AllocationType};
 pub use resource::gl_primitive::GLPrimitive;
 pub use resource::mesh::Mesh;
+pub use resource::instances::Instances;
 
 mod framebuffer_manager;
 mod texture_manager;
@@ -18,4 +19,5 @@ pub mod material;
 mod gpu_vector;
 mod gl_primitive;
 mod mesh;
+mod instances;
 mod shader;
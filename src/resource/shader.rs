@@ -125,6 +125,32 @@ impl<T: GLPrimitive> ShaderAttribute<T> {
         verify!(gl::EnableVertexAttribArray(self.id));
     }
 
+    /// Sets the number of instances drawn before this attribute advances to its next value.
+    ///
+    /// Zero, the default, means that this attribute advances at each vertex. This requires
+    /// OpenGL 3.3 or the `ARB_instanced_arrays` extension, see `Instances::is_supported`.
+    pub fn set_divisor(&mut self, divisor: GLuint) {
+        verify!(gl::VertexAttribDivisor(self.id, divisor));
+    }
+
+    /// Sets the value of this attribute for every vertex, while it is disabled.
+    ///
+    /// Only floating point attributes are supported.
+    pub fn set_constant(&mut self, value: &T) {
+        assert!(GLPrimitive::gl_type(None::<T>) == gl::FLOAT, "Only float attributes can be set to a constant.");
+
+        let value = value as *const T as *const GLfloat;
+
+        unsafe {
+            match GLPrimitive::size(None::<T>) {
+                1 => verify!(gl::VertexAttrib1fv(self.id, value)),
+                2 => verify!(gl::VertexAttrib2fv(self.id, value)),
+                3 => verify!(gl::VertexAttrib3fv(self.id, value)),
+                _ => verify!(gl::VertexAttrib4fv(self.id, value))
+            }
+        }
+    }
+
     /// Binds this attribute to a gpu vector.
     pub fn bind(&mut self, vector: &mut GPUVector<T>) {
         vector.bind();
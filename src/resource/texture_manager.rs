@@ -3,16 +3,98 @@
 use std::cell::RefCell;
 use std::mem;
 use std::rc::Rc;
+use std::num::Float;
 use std::collections::HashMap;
 use std::collections::hash_map::Entry;
+use std::io::{IoResult, IoError, InvalidInput};
 use gl;
 use gl::types::*;
-use stb_image::image::LoadResult;
+use stb_image::image::{Image, LoadResult};
 use stb_image::image;
 
 #[path = "../error.rs"]
 mod error;
 
+/// The `GL_TEXTURE_MAX_ANISOTROPY_EXT` texture parameter.
+static TEXTURE_MAX_ANISOTROPY:     GLenum = 0x84FE;
+/// The `GL_MAX_TEXTURE_MAX_ANISOTROPY_EXT` implementation limit.
+static MAX_TEXTURE_MAX_ANISOTROPY: GLenum = 0x84FF;
+
+/// How texture coordinates outside of `[0, 1]` are handled.
+#[deriving(Clone, Copy, PartialEq, Show)]
+pub enum TextureWrap {
+    /// The texture is tiled.
+    Repeat,
+    /// The texture is tiled, mirrored every other time.
+    MirroredRepeat,
+    /// The pixels of the borders are stretched.
+    ClampToEdge
+}
+
+impl TextureWrap {
+    fn to_gl(&self) -> GLint {
+        match *self {
+            TextureWrap::Repeat         => gl::REPEAT as GLint,
+            TextureWrap::MirroredRepeat => gl::MIRRORED_REPEAT as GLint,
+            TextureWrap::ClampToEdge    => gl::CLAMP_TO_EDGE as GLint
+        }
+    }
+}
+
+/// How the color of a texture is computed between its pixels.
+#[deriving(Clone, Copy, PartialEq, Show)]
+pub enum TextureFilter {
+    /// The color of the nearest pixel, for a pixelated look.
+    Nearest,
+    /// The interpolation of the colors of the four nearest pixels.
+    Linear
+}
+
+/// Options controlling how a texture is sampled.
+#[deriving(Clone, Copy, PartialEq, Show)]
+pub struct TextureOptions {
+    /// The wrap mode along the `u` texture coordinate.
+    pub wrap_u:     TextureWrap,
+    /// The wrap mode along the `v` texture coordinate.
+    pub wrap_v:     TextureWrap,
+    /// The filter used when the texture is displayed smaller than its size.
+    pub min_filter: TextureFilter,
+    /// The filter used when the texture is displayed larger than its size.
+    pub mag_filter: TextureFilter,
+    /// Whether mipmaps are generated. They remove the aliasing of textures seen from far away.
+    pub mipmaps:    bool,
+    /// The maximum anisotropy of the filtering. Values greater than 1.0 keep textures seen at
+    /// grazing angles sharp. It is clamped to what the graphics card supports.
+    pub anisotropy: f32
+}
+
+impl TextureOptions {
+    /// The default options: clamped to the edges, with linear filtering and no mipmaps.
+    pub fn new() -> TextureOptions {
+        TextureOptions {
+            wrap_u:     TextureWrap::ClampToEdge,
+            wrap_v:     TextureWrap::ClampToEdge,
+            min_filter: TextureFilter::Linear,
+            mag_filter: TextureFilter::Linear,
+            mipmaps:    false,
+            anisotropy: 1.0
+        }
+    }
+
+    /// Options for textures tiled over large surfaces like floors: repeated, with mipmaps and
+    /// anisotropic filtering.
+    pub fn tiled() -> TextureOptions {
+        TextureOptions {
+            wrap_u:     TextureWrap::Repeat,
+            wrap_v:     TextureWrap::Repeat,
+            min_filter: TextureFilter::Linear,
+            mag_filter: TextureFilter::Linear,
+            mipmaps:    true,
+            anisotropy: 16.0
+        }
+    }
+}
+
 /// A gpu texture. It contains the texture id provided by opengl and is automatically released.
 pub struct Texture {
     id: GLuint
@@ -32,6 +114,44 @@ impl Texture {
     pub fn id(&self) -> GLuint {
         self.id
     }
+
+    /// Sets the wrap modes and the filtering of this texture, and generates its mipmaps if
+    /// required.
+    ///
+    /// The pixels of the texture must have been uploaded for the mipmaps to be generated.
+    pub fn set_options(&self, options: &TextureOptions) {
+        let min_filter = match (options.min_filter, options.mipmaps) {
+            (TextureFilter::Nearest, false) => gl::NEAREST,
+            (TextureFilter::Linear,  false) => gl::LINEAR,
+            (TextureFilter::Nearest, true)  => gl::NEAREST_MIPMAP_NEAREST,
+            (TextureFilter::Linear,  true)  => gl::LINEAR_MIPMAP_LINEAR
+        };
+
+        let mag_filter = match options.mag_filter {
+            TextureFilter::Nearest => gl::NEAREST,
+            TextureFilter::Linear  => gl::LINEAR
+        };
+
+        verify!(gl::ActiveTexture(gl::TEXTURE0));
+        verify!(gl::BindTexture(gl::TEXTURE_2D, self.id));
+        verify!(gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, options.wrap_u.to_gl()));
+        verify!(gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, options.wrap_v.to_gl()));
+        verify!(gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, min_filter as GLint));
+        verify!(gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, mag_filter as GLint));
+
+        if options.mipmaps {
+            verify!(gl::GenerateMipmap(gl::TEXTURE_2D));
+        }
+
+        let max_anisotropy = max_anisotropy();
+
+        // Do not touch the parameter if the extension is not available.
+        if max_anisotropy > 1.0 {
+            let anisotropy = options.anisotropy.max(1.0).min(max_anisotropy);
+
+            verify!(gl::TexParameterf(gl::TEXTURE_2D, TEXTURE_MAX_ANISOTROPY, anisotropy));
+        }
+    }
 }
 
 impl Drop for Texture {
@@ -90,6 +210,12 @@ impl TextureManager {
         self.textures.get(&name.to_string()).map(|t| t.clone())
     }
 
+    /// Gets the name a texture is registered with. Returns `None` if the texture is not
+    /// registered, e.g., for the default texture.
+    pub fn get_name(&self, texture: &Texture) -> Option<String> {
+        self.textures.iter().find(|&(_, t)| t.id() == texture.id()).map(|(name, _)| name.clone())
+    }
+
     /// Allocates a new texture that is not yet configured.
     ///
     /// If a texture with same name exists, nothing is created and the old texture is returned.
@@ -102,59 +228,188 @@ impl TextureManager {
 
     /// Allocates a new texture read from a file. If a texture with same name exists, nothing is
     /// created and the old texture is returned.
-    pub fn add(&mut self, path: &Path, name: &str) -> Rc<Texture> {
-        let tex = match self.textures.entry(name.to_string()) {
-            Entry::Occupied(entry) => entry.into_mut(),
-            Entry::Vacant(entry)   => entry.set(Texture::new())
-        };
+    ///
+    /// The texture is clamped to its edges, with linear filtering and no mipmaps. Returns an
+    /// error if the image cannot be read or decoded.
+    pub fn add(&mut self, path: &Path, name: &str) -> IoResult<Rc<Texture>> {
+        self.add_with_options(path, name, &TextureOptions::new())
+    }
+
+    /// Allocates a new texture read from a file, sampled as specified by `options`. If a
+    /// texture with same name exists, nothing is created and the old texture is returned.
+    ///
+    /// Returns an error if the image cannot be read or decoded.
+    pub fn add_with_options(&mut self, path: &Path, name: &str, options: &TextureOptions) -> IoResult<Rc<Texture>> {
+        match self.textures.get(&name.to_string()) {
+            Some(t) => return Ok(t.clone()),
+            None    => { }
+        }
+
+        let image = try!(decoded_image(image::load(path), path.display().to_string()));
+
+        Ok(self.add_decoded(image, name, options))
+    }
+
+    /// Allocates a new texture from an encoded image (png, jpg, etc.) stored in memory. If a
+    /// texture with same name exists, nothing is created and the old texture is returned.
+    ///
+    /// The texture is clamped to its edges, with linear filtering and no mipmaps. Returns an
+    /// error if the image cannot be decoded.
+    pub fn add_from_memory(&mut self, data: &[u8], name: &str) -> IoResult<Rc<Texture>> {
+        self.add_from_memory_with_options(data, name, &TextureOptions::new())
+    }
+
+    /// Allocates a new texture from an encoded image (png, jpg, etc.) stored in memory, sampled
+    /// as specified by `options`. If a texture with same name exists, nothing is created and the
+    /// old texture is returned.
+    ///
+    /// Returns an error if the image cannot be decoded.
+    pub fn add_from_memory_with_options(&mut self, data: &[u8], name: &str, options: &TextureOptions)
+                                        -> IoResult<Rc<Texture>> {
+        match self.textures.get(&name.to_string()) {
+            Some(t) => return Ok(t.clone()),
+            None    => { }
+        }
+
+        let image = try!(decoded_image(image::load_from_memory(data), name.to_string()));
+
+        Ok(self.add_decoded(image, name, options))
+    }
+
+    /// Allocates a new texture from raw RGBA pixels, with 8 bits per channel, sampled as
+    /// specified by `options`. If a texture with same name exists, nothing is created and the old
+    /// texture is returned.
+    ///
+    /// The pixels are given row by row, starting with the top row of the image.
+    ///
+    /// # Failures:
+    /// Fails if `width` or `height` is zero, or if `pixels` does not contain exactly
+    /// `width * height * 4` values.
+    pub fn add_rgba(&mut self, pixels: &[u8], width: uint, height: uint, name: &str, options: &TextureOptions)
+                    -> Rc<Texture> {
+        assert!(width != 0 && height != 0,
+                "The dimensions of the texture must not be zero, found: {}x{}.", width, height);
+        assert!(pixels.len() == width * height * 4,
+                "The number of pixels does not match the dimensions of the texture.");
+
+        match self.textures.get(&name.to_string()) {
+            Some(t) => return t.clone(),
+            None    => { }
+        }
+
+        self.add_decoded(Image::new(width, height, 4, pixels.to_vec()), name, options)
+    }
+
+    fn add_decoded(&mut self, mut image: Image<u8>, name: &str, options: &TextureOptions) -> Rc<Texture> {
+        let tex = Texture::new();
 
-        // FIXME: dont re-load the texture if it already exists!
         unsafe {
-            match image::load(path) {
-                LoadResult::ImageU8(mut image) => {
-                    verify!(gl::ActiveTexture(gl::TEXTURE0));
-                    verify!(gl::BindTexture(gl::TEXTURE_2D, tex.id()));
-
-                    // Flip the y axis
-                    let elt_per_row = image.width * image.depth;
-                    for j in range(0u, image.height / 2) {
-                        for i in range(0u, elt_per_row) {
-                            image.data.as_mut_slice().swap(
-                                (image.height - j - 1) * elt_per_row + i, 
-                                j * elt_per_row + i)
-                        }
-                    }
-
-                    if image.depth == 3 {
-                        verify!(gl::TexImage2D(
-                                gl::TEXTURE_2D, 0,
-                                gl::RGB as GLint,
-                                image.width as GLsizei,
-                                image.height as GLsizei,
-                                0, gl::RGB, gl::UNSIGNED_BYTE,
-                                mem::transmute(&image.data[0])));
-                    }
-                    else {
-                        verify!(gl::TexImage2D(
-                                gl::TEXTURE_2D, 0,
-                                gl::RGBA as GLint,
-                                image.width as GLsizei,
-                                image.height as GLsizei,
-                                0, gl::RGBA, gl::UNSIGNED_BYTE,
-                                mem::transmute(&image.data[0])));
-                    }
-
-                    verify!(gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as GLint));
-                    verify!(gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as GLint));
-                    verify!(gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as GLint));
-                    verify!(gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as GLint));
-                }
-                _ => {
-                    panic!("Failed to load texture {}", path.as_str().unwrap());
+            verify!(gl::ActiveTexture(gl::TEXTURE0));
+            verify!(gl::BindTexture(gl::TEXTURE_2D, tex.id()));
+
+            // Flip the y axis
+            let elt_per_row = image.width * image.depth;
+            for j in range(0u, image.height / 2) {
+                for i in range(0u, elt_per_row) {
+                    image.data.as_mut_slice().swap(
+                        (image.height - j - 1) * elt_per_row + i, 
+                        j * elt_per_row + i)
                 }
             }
+
+            // Grayscale images, with or without alpha, are expanded to RGBA.
+            if image.depth == 1 || image.depth == 2 {
+                image.data  = gray_to_rgba(image.data.as_slice(), image.depth);
+                image.depth = 4;
+            }
+
+            if image.depth == 3 {
+                verify!(gl::TexImage2D(
+                        gl::TEXTURE_2D, 0,
+                        gl::RGB as GLint,
+                        image.width as GLsizei,
+                        image.height as GLsizei,
+                        0, gl::RGB, gl::UNSIGNED_BYTE,
+                        mem::transmute(&image.data[0])));
+            }
+            else {
+                verify!(gl::TexImage2D(
+                        gl::TEXTURE_2D, 0,
+                        gl::RGBA as GLint,
+                        image.width as GLsizei,
+                        image.height as GLsizei,
+                        0, gl::RGBA, gl::UNSIGNED_BYTE,
+                        mem::transmute(&image.data[0])));
+            }
         }
 
-        tex.clone()
+        tex.set_options(options);
+
+        let _ = self.textures.insert(name.to_string(), tex.clone());
+
+        tex
+    }
+}
+
+// Converts the pixels of a grayscale image to RGBA. Each pixel has `depth` components: the
+// luminance, followed by the alpha if `depth` is 2.
+fn gray_to_rgba(data: &[u8], depth: uint) -> Vec<u8> {
+    let mut res = Vec::with_capacity(data.len() / depth * 4);
+
+    for pixel in data.chunks(depth) {
+        let alpha = if depth == 2 { pixel[1] } else { 255 };
+
+        res.push_all(&[ pixel[0], pixel[0], pixel[0], alpha ]);
+    }
+
+    res
+}
+
+// The maximum anisotropy supported by the graphics card, or 1.0 if anisotropic filtering is not
+// available.
+fn max_anisotropy() -> f32 {
+    let mut res = 1.0f32;
+
+    unsafe {
+        gl::GetFloatv(MAX_TEXTURE_MAX_ANISOTROPY, &mut res);
+
+        // The query fails if the extension is missing.
+        if gl::GetError() != 0 {
+            return 1.0
+        }
+    }
+
+    res
+}
+
+fn decoded_image(result: LoadResult, source: String) -> IoResult<Image<u8>> {
+    match result {
+        LoadResult::ImageU8(image) => Ok(image),
+        LoadResult::ImageF32(_)    => Err(load_error(source, "floating-point images are not supported.".to_string())),
+        LoadResult::Error(e)       => Err(load_error(source, e))
+    }
+}
+
+fn load_error(source: String, reason: String) -> IoError {
+    IoError {
+        kind:   InvalidInput,
+        desc:   "failed to load the texture",
+        detail: Some(format!("{}: {}", source, reason))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::gray_to_rgba;
+
+    #[test]
+    fn gray_is_opaque() {
+        assert!(gray_to_rgba(&[ 0, 128, 255 ], 1) ==
+                vec!(0, 0, 0, 255, 128, 128, 128, 255, 255, 255, 255, 255));
+    }
+
+    #[test]
+    fn gray_alpha_keeps_its_alpha() {
+        assert!(gray_to_rgba(&[ 10, 0, 20, 128 ], 2) == vec!(10, 10, 10, 0, 20, 20, 20, 128));
     }
 }
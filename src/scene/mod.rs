@@ -2,6 +2,12 @@
 
 pub use scene::scene_node::{SceneNode, SceneNodeData};
 pub use scene::object::{Object, ObjectData};
+pub use scene::pick::PickResult;
+pub use scene::polyline::Polyline;
+pub use scene::point_cloud::PointCloud;
 
 mod object;
 mod scene_node;
+mod pick;
+mod polyline;
+mod point_cloud;
@@ -3,11 +3,12 @@
 use std::any::Any;
 use std::cell::RefCell;
 use std::rc::Rc;
+use std::io::IoResult;
 use gl::types::*;
 use na::{Pnt3, Pnt2, Vec3, Iso3};
-use resource::{Texture, TextureManager, Material, Mesh};
+use resource::{Texture, TextureManager, Material, Mesh, Instances};
 use camera::Camera;
-use light::Light;
+use light::LightSource;
 
 #[path = "../error.rs"]
 mod error;
@@ -16,11 +17,17 @@ mod error;
 pub struct ObjectData {
     material:     Rc<RefCell<Box<Material + 'static>>>,
     texture:      Rc<Texture>,
+    opacity:      Option<Rc<Texture>>,
     color:        Pnt3<f32>,
+    alpha:        f32,
+    alpha_cutoff: Option<f32>,
     wlines:       f32,
     wpoints:      f32,
     draw_surface: bool,
     cull:         bool,
+    cast_shadows: bool,
+    recv_shadows: bool,
+    instances:    Option<RefCell<Instances>>,
     user_data:    Box<Any + 'static>
 }
 
@@ -31,12 +38,38 @@ impl ObjectData {
         &self.texture
     }
 
+    /// The opacity map of this object.
+    #[inline]
+    pub fn opacity_texture<'a>(&'a self) -> Option<&'a Rc<Texture>> {
+        self.opacity.as_ref()
+    }
+
     /// The color of this object.
     #[inline]
     pub fn color<'a>(&'a self) -> &'a Pnt3<f32> {
         &self.color
     }
 
+    /// The opacity of this object.
+    #[inline]
+    pub fn alpha(&self) -> f32 {
+        self.alpha
+    }
+
+    /// The opacity under which the fragments of this object are discarded, if any.
+    #[inline]
+    pub fn alpha_cutoff(&self) -> Option<f32> {
+        self.alpha_cutoff
+    }
+
+    /// Whether this object has to be rendered with alpha blending.
+    ///
+    /// Objects with an alpha cutoff are alpha-tested instead, and rendered with the opaque ones.
+    #[inline]
+    pub fn is_transparent(&self) -> bool {
+        self.alpha_cutoff.is_none() && (self.alpha < 1.0 || self.opacity.is_some())
+    }
+
     /// The width of the lines draw for this object.
     #[inline]
     pub fn lines_width(&self) -> f32 {
@@ -61,6 +94,24 @@ impl ObjectData {
         self.cull
     }
 
+    /// Whether this object is rendered into the shadow map.
+    #[inline]
+    pub fn casts_shadows(&self) -> bool {
+        self.cast_shadows
+    }
+
+    /// Whether shadows are drawn on this object.
+    #[inline]
+    pub fn receives_shadows(&self) -> bool {
+        self.recv_shadows
+    }
+
+    /// The instances of this object, if it is drawn several times at once.
+    #[inline]
+    pub fn instances<'a>(&'a self) -> Option<&'a RefCell<Instances>> {
+        self.instances.as_ref()
+    }
+
     /// An user-defined data.
     ///
     /// Use dynamic typing capabilities of the `Any` type to recover the actual data.
@@ -91,11 +142,17 @@ impl Object {
         let user_data = ();
         let data = ObjectData {
             color:        Pnt3::new(r, g, b),
+            alpha:        1.0,
+            alpha_cutoff: None,
             texture:      texture,
+            opacity:      None,
             wlines:       0.0,
             wpoints:      0.0,
             draw_surface: true,
             cull:         true,
+            cast_shadows: true,
+            recv_shadows: true,
+            instances:    None,
             material:     material,
             user_data:    box user_data as Box<Any>
         };
@@ -112,13 +169,31 @@ impl Object {
                   scale:     &Vec3<f32>,
                   pass:      uint,
                   camera:    &mut Camera,
-                  light:     &Light) {
+                  lights:    &[LightSource]) {
         self.data.material.borrow_mut().render(
             pass,
             transform,
             scale,
             camera,
-            light,
+            lights,
+            &self.data,
+            self.mesh.borrow_mut().deref_mut());
+    }
+
+    #[doc(hidden)]
+    pub fn render_with_material(&self,
+                                material:  &mut Material,
+                                transform: &Iso3<f32>,
+                                scale:     &Vec3<f32>,
+                                pass:      uint,
+                                camera:    &mut Camera,
+                                lights:    &[LightSource]) {
+        material.render(
+            pass,
+            transform,
+            scale,
+            camera,
+            lights,
             &self.data,
             self.mesh.borrow_mut().deref_mut());
     }
@@ -141,6 +216,37 @@ impl Object {
         self.data.cull = active;
     }
 
+    /// Sets whether this object casts shadows.
+    #[inline]
+    pub fn set_cast_shadows(&mut self, active: bool) {
+        self.data.cast_shadows = active;
+    }
+
+    /// Sets whether shadows are drawn on this object.
+    #[inline]
+    pub fn set_receive_shadows(&mut self, active: bool) {
+        self.data.recv_shadows = active;
+    }
+
+    /// Draws this object once per instance, with the given transformations, scales and colors.
+    ///
+    /// The instance colors are multiplied with the object color. The GPU buffers are reused if
+    /// this object is already instanced, so this can be called at each frame.
+    pub fn set_instances(&mut self, instances: Vec<(Iso3<f32>, Vec3<f32>, Pnt3<f32>)>) {
+        if self.data.instances.is_none() {
+            self.data.instances = Some(RefCell::new(Instances::new(instances)))
+        }
+        else {
+            self.data.instances.as_ref().unwrap().borrow_mut().set(instances)
+        }
+    }
+
+    /// Draws this object only once, using its own transformation.
+    #[inline]
+    pub fn remove_instances(&mut self) {
+        self.data.instances = None;
+    }
+
     /// Attaches user-defined data to this object.
     #[inline]
     pub fn set_user_data(&mut self, user_data: Box<Any + 'static>) {
@@ -266,17 +372,57 @@ impl Object {
         self.data.color.z = b;
     }
 
+    /// Sets the opacity of the object.
+    ///
+    /// The opacity must be on the range `[0.0, 1.0]`. Objects with an opacity smaller than `1.0`
+    /// are rendered after every opaque object, from the farthest to the closest.
+    #[inline]
+    pub fn set_alpha(&mut self, alpha: f32) {
+        self.data.alpha = alpha;
+    }
+
+    /// Sets the opacity under which the fragments of this object are discarded.
+    ///
+    /// Fragments with an opacity greater than or equal to the cutoff are drawn fully opaque, so
+    /// an alpha-tested object is rendered with the opaque objects even if its opacity is smaller
+    /// than `1.0`. Set to `None` to disable the test.
+    #[inline]
+    pub fn set_alpha_cutoff(&mut self, cutoff: Option<f32>) {
+        self.data.alpha_cutoff = cutoff;
+    }
+
+    /// Sets the color and the opacity of the object.
+    ///
+    /// Colors components must be on the range `[0.0, 1.0]`.
+    #[inline]
+    pub fn set_color_with_alpha(&mut self, r: f32, g: f32, b: f32, a: f32) {
+        self.set_color(r, g, b);
+        self.set_alpha(a);
+    }
+
+    /// Sets the opacity map of the object.
+    ///
+    /// The red channel of the opacity map is multiplied to the object opacity.
+    #[inline]
+    pub fn set_opacity_texture(&mut self, texture: Option<Rc<Texture>>) {
+        self.data.opacity = texture
+    }
+
     /// Sets the texture of the object.
     ///
     /// The texture is loaded from a file and registered by the global `TextureManager`.
     ///
     /// # Arguments
     ///   * `path` - relative path of the texture on the disk
+    ///
+    /// Returns an error if the texture cannot be loaded. In that case, the texture is unchanged.
     #[inline]
-    pub fn set_texture_from_file(&mut self, path: &Path, name: &str) {
-        let texture = TextureManager::get_global_manager(|tm| tm.add(path, name));
+    pub fn set_texture_from_file(&mut self, path: &Path, name: &str) -> IoResult<()> {
+        let texture = try!(TextureManager::get_global_manager(|tm| tm.add(path, name)));
 
-        self.set_texture(texture)
+        self.set_texture(texture);
+
+        Ok(())
     }
 
     /// Sets the texture of the object.
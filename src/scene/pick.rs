@@ -0,0 +1,258 @@
+//! Ray casting on the objects of the scene.
+
+use na::{Pnt3, Vec3, Iso3, Transform, Rotate};
+use na;
+use ncollide::ray::{Ray, LocalRayCast};
+use ncollide::shape::Triangle;
+use ncollide::bounding_volume::AABB;
+use scene::{Object, SceneNode};
+
+/// The result of a successful ray cast on the scene.
+pub struct PickResult {
+    /// The node containing the object hit by the ray.
+    pub node:     SceneNode,
+    /// The world-space point hit by the ray.
+    pub point:    Pnt3<f32>,
+    /// The world-space unit normal of the surface at the hit point, facing the ray origin.
+    pub normal:   Vec3<f32>,
+    /// The index of the triangle hit by the ray, on the object mesh.
+    pub triangle: uint,
+    /// The index of the instance hit by the ray, if the object is instanced.
+    pub instance: Option<uint>,
+    /// The time of impact: the hit point is `origin + dir * toi`.
+    pub toi:      f32
+}
+
+/// Casts a ray on an object, given its world transformation and scale.
+///
+/// Returns the time of impact, the world-space normal, the index of the closest hit triangle, and
+/// the index of the hit instance if the object is instanced.
+pub fn cast_ray_on_object(object:    &Object,
+                          transform: &Iso3<f32>,
+                          scale:     &Vec3<f32>,
+                          orig:      &Pnt3<f32>,
+                          dir:       &Vec3<f32>)
+                          -> Option<(f32, Vec3<f32>, uint, Option<uint>)> {
+    if has_zero(scale) {
+        return None;
+    }
+
+    // Express the ray in the object local space, before the scaling.
+    let (lorig, ldir) = to_local(transform, scale, orig, dir);
+
+    let mut best: Option<(f32, Vec3<f32>, uint, Option<uint>)> = None;
+
+    // The mesh is read in place: picking must not copy every mesh of the scene.
+    object.read_vertices(&mut |coords| {
+        let (mins, maxs) = match local_aabb(coords) {
+            Some(aabb) => aabb,
+            None       => return
+        };
+
+        match object.data().instances() {
+            None => {
+                let (wmins, wmaxs) = transform_aabb(&mins, &maxs, transform, scale);
+
+                if ray_hits_aabb(orig, dir, &wmins, &wmaxs) {
+                    object.read_faces(&mut |faces| {
+                        best = cast_ray_on_triangles(coords, faces, &lorig, &ldir).map(|(toi, n, i)| (toi, n, i, None))
+                    })
+                }
+            },
+            Some(instances) => {
+                object.read_faces(&mut |faces| {
+                    for (id, &(ref itransform, ref iscale, _)) in instances.borrow().instances().iter().enumerate() {
+                        if has_zero(iscale) {
+                            continue;
+                        }
+
+                        let (imins, imaxs) = transform_aabb(&mins, &maxs, itransform, iscale);
+                        let (wmins, wmaxs) = transform_aabb(&imins, &imaxs, transform, scale);
+
+                        if !ray_hits_aabb(orig, dir, &wmins, &wmaxs) {
+                            continue;
+                        }
+
+                        let (iorig, idir) = to_local(itransform, iscale, &lorig, &ldir);
+
+                        match cast_ray_on_triangles(coords, faces, &iorig, &idir) {
+                            Some((toi, n, i)) if best.as_ref().map(|b| toi < b.0).unwrap_or(true) =>
+                                best = Some((toi, normal_from_local(itransform, iscale, &n), i, Some(id))),
+                            _ => { }
+                        }
+                    }
+                })
+            }
+        }
+    });
+
+    best.map(|(toi, n, i, id)| {
+        let mut wn = na::normalize(&normal_from_local(transform, scale, &n));
+
+        if na::dot(&wn, dir) > 0.0 {
+            wn = -wn
+        }
+
+        (toi, wn, i, id)
+    })
+}
+
+fn has_zero(scale: &Vec3<f32>) -> bool {
+    scale.x == 0.0 || scale.y == 0.0 || scale.z == 0.0
+}
+
+fn local_aabb(coords: &[Pnt3<f32>]) -> Option<(Pnt3<f32>, Pnt3<f32>)> {
+    let mut res: Option<(Pnt3<f32>, Pnt3<f32>)> = None;
+
+    for p in coords.iter() {
+        res = match res {
+            Some((mins, maxs)) => Some((na::inf(&mins, p), na::sup(&maxs, p))),
+            None               => Some((*p, *p))
+        };
+    }
+
+    res
+}
+
+// The axis-aligned box containing the image of the box `[mins, maxs]` by `transform * scale`.
+fn transform_aabb(mins: &Pnt3<f32>, maxs: &Pnt3<f32>, transform: &Iso3<f32>, scale: &Vec3<f32>) -> (Pnt3<f32>, Pnt3<f32>) {
+    let mut res: Option<(Pnt3<f32>, Pnt3<f32>)> = None;
+
+    for i in range(0u, 8) {
+        let x = if i & 1 == 0 { mins.x } else { maxs.x };
+        let y = if i & 2 == 0 { mins.y } else { maxs.y };
+        let z = if i & 4 == 0 { mins.z } else { maxs.z };
+        let p = *transform * Pnt3::new(x * scale.x, y * scale.y, z * scale.z);
+
+        res = match res {
+            Some((rmins, rmaxs)) => Some((na::inf(&rmins, &p), na::sup(&rmaxs, &p))),
+            None                 => Some((p, p))
+        };
+    }
+
+    res.unwrap()
+}
+
+fn ray_hits_aabb(orig: &Pnt3<f32>, dir: &Vec3<f32>, mins: &Pnt3<f32>, maxs: &Pnt3<f32>) -> bool {
+    AABB::new(*mins, *maxs).toi_with_ray(&Ray::new(*orig, *dir), true).is_some()
+}
+
+// Transforms a ray by the inverse of `transform * scale`.
+//
+// The time of impact along the ray is preserved since the transformation is affine.
+fn to_local(transform: &Iso3<f32>, scale: &Vec3<f32>, orig: &Pnt3<f32>, dir: &Vec3<f32>) -> (Pnt3<f32>, Vec3<f32>) {
+    let lorig: Pnt3<f32> = transform.inv_transform(orig);
+    let ldir:  Vec3<f32> = transform.inv_rotate(dir);
+
+    (Pnt3::new(lorig.x / scale.x, lorig.y / scale.y, lorig.z / scale.z),
+     Vec3::new(ldir.x / scale.x, ldir.y / scale.y, ldir.z / scale.z))
+}
+
+// Normals are transformed by the inverse transpose of the scaling.
+fn normal_from_local(transform: &Iso3<f32>, scale: &Vec3<f32>, n: &Vec3<f32>) -> Vec3<f32> {
+    transform.rotate(&Vec3::new(n.x / scale.x, n.y / scale.y, n.z / scale.z))
+}
+
+// The triangles are cast on one at a time: an ncollide `TriMesh` would need a copy of the mesh,
+// and picking must not copy every mesh of the scene.
+fn cast_ray_on_triangles(coords: &[Pnt3<f32>],
+                         faces:  &[Vec3<u32>],
+                         orig:   &Pnt3<f32>,
+                         dir:    &Vec3<f32>)
+                         -> Option<(f32, Vec3<f32>, uint)> {
+    let ray      = Ray::new(*orig, *dir);
+    let mut best: Option<(f32, Vec3<f32>, uint)> = None;
+
+    for (i, face) in faces.iter().enumerate() {
+        let triangle = Triangle::new(coords[face.x as uint], coords[face.y as uint], coords[face.z as uint]);
+
+        match triangle.toi_and_normal_with_ray(&ray, false) {
+            Some(hit) if best.as_ref().map(|b| hit.toi < b.0).unwrap_or(true) => best = Some((hit.toi, hit.normal, i)),
+            _ => { }
+        }
+    }
+
+    best
+}
+
+#[cfg(test)]
+mod test {
+    use std::num::Float;
+    use na::{Pnt3, Vec3, Iso3};
+    use na;
+    use super::{cast_ray_on_triangles, transform_aabb, to_local, ray_hits_aabb};
+
+    // Two unit right triangles facing `z`, at `z = 0` and `z = -1`.
+    fn triangles() -> (Vec<Pnt3<f32>>, Vec<Vec3<u32>>) {
+        let coords = vec!(Pnt3::new(0.0f32, 0.0, 0.0), Pnt3::new(1.0, 0.0, 0.0), Pnt3::new(0.0, 1.0, 0.0),
+                          Pnt3::new(0.0, 0.0, -1.0), Pnt3::new(1.0, 0.0, -1.0), Pnt3::new(0.0, 1.0, -1.0));
+        let faces  = vec!(Vec3::new(3u32, 4, 5), Vec3::new(0, 1, 2));
+
+        (coords, faces)
+    }
+
+    #[test]
+    fn closest_triangle_is_hit() {
+        let (coords, faces) = triangles();
+        let (toi, _, i)     = cast_ray_on_triangles(coords.as_slice(), faces.as_slice(),
+                                                    &Pnt3::new(0.25, 0.25, 2.0), &-Vec3::z()).unwrap();
+
+        assert!((toi - 2.0).abs() < 1.0e-5);
+        assert_eq!(i, 1);
+    }
+
+    #[test]
+    fn triangles_are_two_sided() {
+        let (coords, faces) = triangles();
+        let (toi, _, i)     = cast_ray_on_triangles(coords.as_slice(), faces.as_slice(),
+                                                    &Pnt3::new(0.25, 0.25, -3.0), &Vec3::z()).unwrap();
+
+        assert!((toi - 2.0).abs() < 1.0e-5);
+        assert_eq!(i, 0);
+    }
+
+    #[test]
+    fn ray_misses_outside_triangles() {
+        let (coords, faces) = triangles();
+
+        assert!(cast_ray_on_triangles(coords.as_slice(), faces.as_slice(), &Pnt3::new(0.75, 0.75, 2.0), &-Vec3::z()).is_none());
+        assert!(cast_ray_on_triangles(coords.as_slice(), faces.as_slice(), &Pnt3::new(0.25, 0.25, 2.0), &Vec3::z()).is_none());
+    }
+
+    #[test]
+    fn local_rays_keep_the_time_of_impact() {
+        let (coords, faces) = triangles();
+        let transform       = Iso3::new(Vec3::new(1.0f32, 2.0, 3.0), Vec3::new(0.0, 0.5, 0.0));
+        let scale           = Vec3::new(2.0f32, 3.0, 4.0);
+        let local_target    = Pnt3::new(0.25f32, 0.25, 0.0);
+        let target          = transform * Pnt3::new(0.5f32, 0.75, 0.0);
+        let orig            = target + Vec3::new(0.0, 0.0, 5.0);
+        let dir             = na::normalize(&(target - orig));
+
+        let (lorig, ldir) = to_local(&transform, &scale, &orig, &dir);
+        let (toi, _, _)   = cast_ray_on_triangles(coords.as_slice(), faces.as_slice(), &lorig, &ldir).unwrap();
+
+        assert!(na::approx_eq_eps(&(lorig + ldir * toi), &local_target, &1.0e-4));
+        assert!((toi - 5.0).abs() < 1.0e-4);
+    }
+
+    #[test]
+    fn transformed_aabb_contains_the_transformed_box() {
+        let transform    = Iso3::new(Vec3::new(1.0f32, 0.0, 0.0), Vec3::new(0.0, 0.0, 0.7));
+        let scale        = Vec3::new(2.0f32, 1.0, 1.0);
+        let (mins, maxs) = transform_aabb(&Pnt3::new(-1.0, -1.0, -1.0), &Pnt3::new(1.0, 1.0, 1.0), &transform, &scale);
+
+        for i in range(0u, 8) {
+            let x = if i & 1 == 0 { -2.0f32 } else { 2.0 };
+            let y = if i & 2 == 0 { -1.0f32 } else { 1.0 };
+            let z = if i & 4 == 0 { -1.0f32 } else { 1.0 };
+            let p = transform * Pnt3::new(x, y, z);
+
+            assert!(p.x >= mins.x - 1.0e-5 && p.y >= mins.y - 1.0e-5 && p.z >= mins.z - 1.0e-5);
+            assert!(p.x <= maxs.x + 1.0e-5 && p.y <= maxs.y + 1.0e-5 && p.z <= maxs.z + 1.0e-5);
+        }
+
+        assert!(ray_hits_aabb(&Pnt3::new(1.0, 0.0, 5.0), &-Vec3::z(), &mins, &maxs));
+        assert!(!ray_hits_aabb(&Pnt3::new(1.0, 0.0, 5.0), &Vec3::z(), &mins, &maxs));
+    }
+}
@@ -0,0 +1,136 @@
+//! Points kept on the GPU between frames.
+
+use std::cmp;
+use gl;
+use gl::types::*;
+use na::{Pnt3, Vec3, Iso3};
+use resource::{GPUVector, BufferType, AllocationType};
+use builtin::VertexColorsShader;
+use camera::Camera;
+use point_renderer::{Sprites, PointSprite, PointShape, PointSize};
+
+/// A set of points with one color per vertex.
+///
+/// Unlike the points drawn with `Window::draw_point`, its vertices are uploaded to the GPU once and
+/// kept there until they are modified.
+///
+/// Points larger than one pixel are drawn as square sprites, since `glPointSize` is not supported
+/// by every driver. Setting a sprite style gives control over their shape and texture. One pixel
+/// points without a sprite style are drawn with `GL_POINTS`.
+pub struct PointCloud {
+    coords:  GPUVector<Pnt3<GLfloat>>,
+    colors:  GPUVector<Pnt3<GLfloat>>,
+    size:    f32,
+    sprite:  Option<PointSprite>,
+    sprites: Option<Sprites> // Built from `coords` and `colors` when drawn as sprites.
+}
+
+impl PointCloud {
+    /// Creates a new point cloud.
+    ///
+    /// # Failures:
+    /// Fails if `coords` and `colors` do not have the same length.
+    pub fn new(coords: Vec<Pnt3<GLfloat>>, colors: Vec<Pnt3<GLfloat>>, size: f32) -> PointCloud {
+        assert!(coords.len() == colors.len(), "There must be exactly one color per vertex.");
+
+        PointCloud {
+            coords:  GPUVector::new(coords, BufferType::Array, AllocationType::StaticDraw),
+            colors:  GPUVector::new(colors, BufferType::Array, AllocationType::StaticDraw),
+            size:    size,
+            sprite:  None,
+            sprites: None
+        }
+    }
+
+    /// The size of the points, in pixels.
+    #[inline]
+    pub fn size(&self) -> f32 {
+        self.size
+    }
+
+    /// Sets the size of the points, in pixels.
+    #[inline]
+    pub fn set_size(&mut self, size: f32) {
+        self.size = size;
+        self.sprites = None;
+    }
+
+    /// The style of the sprites used to draw these points, if any.
+    #[inline]
+    pub fn sprite<'a>(&'a self) -> Option<&'a PointSprite> {
+        self.sprite.as_ref()
+    }
+
+    /// Sets the style of the sprites used to draw these points.
+    ///
+    /// If `None`, points larger than one pixel are drawn as squares.
+    #[inline]
+    pub fn set_sprite(&mut self, sprite: Option<PointSprite>) {
+        self.sprite = sprite
+    }
+
+    /// Mutably accesses the vertices of this point cloud.
+    ///
+    /// They are uploaded again to the GPU before the next rendering.
+    #[inline]
+    pub fn modify_vertices(&mut self, f: &mut |&mut Vec<Pnt3<GLfloat>>| -> ()) {
+        let _ = self.coords.data_mut().as_mut().map(|coords| (*f)(coords));
+        self.sprites = None;
+    }
+
+    /// Accesses the vertices of this point cloud.
+    #[inline]
+    pub fn read_vertices(&self, f: &mut |&[Pnt3<GLfloat>]| -> ()) {
+        let _ = self.coords.data().as_ref().map(|coords| (*f)(coords.as_slice()));
+    }
+
+    /// Mutably accesses the vertex colors of this point cloud.
+    ///
+    /// They are uploaded again to the GPU before the next rendering.
+    #[inline]
+    pub fn modify_colors(&mut self, f: &mut |&mut Vec<Pnt3<GLfloat>>| -> ()) {
+        let _ = self.colors.data_mut().as_mut().map(|colors| (*f)(colors));
+        self.sprites = None;
+    }
+
+    /// Accesses the vertex colors of this point cloud.
+    #[inline]
+    pub fn read_colors(&self, f: &mut |&[Pnt3<GLfloat>]| -> ()) {
+        let _ = self.colors.data().as_ref().map(|colors| (*f)(colors.as_slice()));
+    }
+
+    /// Renders this point cloud.
+    pub fn render(&mut self, transform: &Iso3<f32>, scale: &Vec3<f32>, pass: uint, camera: &mut Camera) {
+        let sprite = match self.sprite {
+            Some(ref sprite)        => Some(sprite.clone()),
+            None if self.size > 1.0 => Some(PointSprite::new(PointShape::Square)),
+            None                    => None
+        };
+
+        match sprite {
+            Some(ref sprite) => {
+                if self.sprites.is_none() {
+                    let mut sprites = Sprites::new();
+
+                    match (self.coords.data(), self.colors.data()) {
+                        (&Some(ref coords), &Some(ref colors)) => {
+                            for i in range(0u, cmp::min(coords.len(), colors.len())) {
+                                sprites.push(coords[i], colors[i], PointSize::Pixels(self.size))
+                            }
+                        },
+                        _ => { }
+                    }
+
+                    self.sprites = Some(sprites);
+                }
+
+                self.sprites.as_mut().unwrap().render(sprite, transform, scale, pass, camera);
+            },
+            None => {
+                VertexColorsShader::get_global_shader(|s| {
+                    s.render(gl::POINTS, pass, transform, scale, camera, &mut self.coords, &mut self.colors)
+                });
+            }
+        }
+    }
+}
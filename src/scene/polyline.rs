@@ -0,0 +1,141 @@
+//! Lines kept on the GPU between frames.
+
+use std::cmp;
+use gl;
+use gl::types::*;
+use na::{Pnt3, Vec3, Iso3};
+use resource::{GPUVector, BufferType, AllocationType};
+use builtin::VertexColorsShader;
+use camera::Camera;
+use thick_line_renderer::{ThickLines, LineStyle};
+
+/// A line strip with one color per vertex.
+///
+/// Unlike the lines drawn with `Window::draw_line`, its vertices are uploaded to the GPU once and
+/// kept there until they are modified.
+///
+/// Lines wider than one pixel are drawn as thick, anti-aliased lines with round caps and joins,
+/// since `glLineWidth` only supports a width of 1 on core profiles. Setting a line style gives
+/// control over their caps, joins and dashes. One pixel wide lines without a style are drawn
+/// with `GL_LINE_STRIP`.
+pub struct Polyline {
+    coords: GPUVector<Pnt3<GLfloat>>,
+    colors: GPUVector<Pnt3<GLfloat>>,
+    width:  f32,
+    style:  Option<LineStyle>,
+    thick:  Option<ThickLines> // Built from `coords` and `colors` when `style` is set.
+}
+
+impl Polyline {
+    /// Creates a new line strip joining consecutive vertices.
+    ///
+    /// # Failures:
+    /// Fails if `coords` and `colors` do not have the same length.
+    pub fn new(coords: Vec<Pnt3<GLfloat>>, colors: Vec<Pnt3<GLfloat>>, width: f32) -> Polyline {
+        assert!(coords.len() == colors.len(), "There must be exactly one color per vertex.");
+
+        Polyline {
+            coords: GPUVector::new(coords, BufferType::Array, AllocationType::StaticDraw),
+            colors: GPUVector::new(colors, BufferType::Array, AllocationType::StaticDraw),
+            width:  width,
+            style:  None,
+            thick:  None
+        }
+    }
+
+    /// The width of the lines, in pixels.
+    #[inline]
+    pub fn width(&self) -> f32 {
+        self.width
+    }
+
+    /// Sets the width of the lines, in pixels.
+    ///
+    /// This is ignored if a line style is set: its own width is used instead.
+    #[inline]
+    pub fn set_width(&mut self, width: f32) {
+        self.width = width
+    }
+
+    /// The style of the thick lines used to draw this polyline, if any.
+    #[inline]
+    pub fn style(&self) -> Option<LineStyle> {
+        self.style
+    }
+
+    /// Sets the style of the thick lines used to draw this polyline.
+    ///
+    /// If `None`, the polyline is drawn with round caps and joins, and its `width`.
+    #[inline]
+    pub fn set_style(&mut self, style: Option<LineStyle>) {
+        self.style = style
+    }
+
+    /// Mutably accesses the vertices of this polyline.
+    ///
+    /// They are uploaded again to the GPU before the next rendering.
+    #[inline]
+    pub fn modify_vertices(&mut self, f: &mut |&mut Vec<Pnt3<GLfloat>>| -> ()) {
+        let _ = self.coords.data_mut().as_mut().map(|coords| (*f)(coords));
+        self.thick = None;
+    }
+
+    /// Accesses the vertices of this polyline.
+    #[inline]
+    pub fn read_vertices(&self, f: &mut |&[Pnt3<GLfloat>]| -> ()) {
+        let _ = self.coords.data().as_ref().map(|coords| (*f)(coords.as_slice()));
+    }
+
+    /// Mutably accesses the vertex colors of this polyline.
+    ///
+    /// They are uploaded again to the GPU before the next rendering.
+    #[inline]
+    pub fn modify_colors(&mut self, f: &mut |&mut Vec<Pnt3<GLfloat>>| -> ()) {
+        let _ = self.colors.data_mut().as_mut().map(|colors| (*f)(colors));
+        self.thick = None;
+    }
+
+    /// Accesses the vertex colors of this polyline.
+    #[inline]
+    pub fn read_colors(&self, f: &mut |&[Pnt3<GLfloat>]| -> ()) {
+        let _ = self.colors.data().as_ref().map(|colors| (*f)(colors.as_slice()));
+    }
+
+    /// Renders this polyline.
+    pub fn render(&mut self, transform: &Iso3<f32>, scale: &Vec3<f32>, pass: uint, camera: &mut Camera) {
+        let style = match self.style {
+            Some(style)              => Some(style),
+            None if self.width > 1.0 => Some(LineStyle::new(self.width)),
+            None                     => None
+        };
+
+        match style {
+            Some(ref style) => {
+                if self.thick.is_none() {
+                    let mut thick = ThickLines::new();
+
+                    match (self.coords.data(), self.colors.data()) {
+                        (&Some(ref coords), &Some(ref colors)) => {
+                            let len = cmp::min(coords.len(), colors.len());
+                            thick.push_polyline(coords.slice_to(len), colors.slice_to(len))
+                        },
+                        _ => { }
+                    }
+
+                    // The segments are rebuilt whenever the polyline changes: keep them on the GPU only.
+                    thick.unload_from_ram();
+                    self.thick = Some(thick);
+                }
+
+                self.thick.as_mut().unwrap().render(style, transform, scale, pass, camera);
+
+                return
+            },
+            None => { }
+        }
+
+        VertexColorsShader::get_global_shader(|s| {
+            s.render(gl::LINE_STRIP, pass, transform, scale, camera, &mut self.coords, &mut self.colors)
+        });
+    }
+}
@@ -1,14 +1,27 @@
 use std::rc::{Rc, Weak};
 use std::cell::{Ref, RefMut, RefCell};
 use std::mem;
+use std::io::{IoResult, IoError, InvalidInput};
+use std::cmp::Ordering;
+use std::f32;
+use std::num::Float;
+use gl;
 use na;
-use na::{Iso3, Pnt2, Vec3, Pnt3, Transformation, Rotation, Translation, RotationWithTranslation};
+use na::{Iso3, Pnt2, Vec2, Vec3, Pnt3, Transformation, Rotation, Translation, RotationWithTranslation};
 use resource::{Mesh, MeshManager, Texture, TextureManager, Material, MaterialManager};
-use ncollide::procedural::TriMesh3;
+use ncollide::procedural::{TriMesh, TriMesh3, IndexBuffer, Polyline2};
 use ncollide::procedural;
-use scene::Object;
+use scene::{Object, PickResult, Polyline, PointCloud};
+use scene::pick;
 use camera::Camera;
-use light::Light;
+use light::LightSource;
+use text::Label;
+use loader::{LoadError, ParseError};
+use loader::{gltf, stl, ply};
+use loader::gltf::{GltfDocument, GltfImage};
+
+#[path = "../error.rs"]
+mod error;
 
 // XXX: once something like `fn foo(self: Rc<RefCell<SceneNode>>)` is allowed, this extra struct
 // will not be needed any more.
@@ -22,12 +35,16 @@ pub struct SceneNodeData {
     up_to_date:      bool,
     children:        Vec<SceneNode>,
     object:          Option<Object>,
+    polyline:        Option<Polyline>,
+    point_cloud:     Option<PointCloud>,
+    labels:          Vec<(Pnt3<f32>, Label)>,
     parent:          Option<Weak<RefCell<SceneNodeData>>>
 }
 
 /// A node of the scene graph.
 ///
-/// This may represent a group of other nodes, and/or contain an object that can be rendered.
+/// This may represent a group of other nodes, and/or contain an object, a polyline or a point
+/// cloud that can be rendered.
 #[deriving(Clone)]
 pub struct SceneNode {
     data:   Rc<RefCell<SceneNodeData>>,
@@ -69,6 +86,12 @@ impl SceneNodeData {
         self.object.is_some()
     }
 
+    /// Whether this node has at least one child.
+    #[inline]
+    pub fn has_children(&self) -> bool {
+        !self.children.is_empty()
+    }
+
     /// Whether this node has no parent.
     #[inline]
     pub fn is_root(&self) -> bool {
@@ -79,9 +102,40 @@ impl SceneNodeData {
     }
 
     /// Render the scene graph rooted by this node.
-    pub fn render(&mut self, pass: uint, camera: &mut Camera, light: &Light) {
+    ///
+    /// Opaque objects are rendered first. Then, transparent objects are rendered with alpha
+    /// blending enabled, sorted from the farthest to the closest to the camera.
+    pub fn render(&mut self, pass: uint, camera: &mut Camera, lights: &[LightSource]) {
         if self.visible {
-            self.do_render(&na::one(), &na::one(), pass, camera, light)
+            // `None` stands for the object of `self`.
+            let mut transparents: Vec<(f32, Option<SceneNode>)> = Vec::new();
+
+            self.do_render(&na::one(), &na::one(), pass, camera, lights, &mut transparents);
+
+            if self.object.as_ref().map(|o| o.data().is_transparent()).unwrap_or(false) {
+                let dist = self.sqdist_to_camera(&*camera);
+                transparents.push((dist, None));
+            }
+
+            if transparents.is_empty() {
+                return;
+            }
+
+            transparents.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(Ordering::Equal));
+
+            verify!(gl::Enable(gl::BLEND));
+            verify!(gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA));
+            verify!(gl::DepthMask(gl::FALSE));
+
+            for &(_, ref node) in transparents.iter() {
+                match *node {
+                    Some(ref node) => node.data().render_object(pass, camera, lights),
+                    None           => self.render_object(pass, camera, lights)
+                }
+            }
+
+            verify!(gl::DepthMask(gl::TRUE));
+            verify!(gl::Disable(gl::BLEND));
         }
     }
 
@@ -90,7 +144,8 @@ impl SceneNodeData {
                  scale:        &Vec3<f32>,
                  pass:         uint,
                  camera:       &mut Camera,
-                 light:        &Light) {
+                 lights:       &[LightSource],
+                 transparents: &mut Vec<(f32, Option<SceneNode>)>) {
         if !self.up_to_date {
             self.up_to_date      = true;
             self.world_transform = *transform * self.local_transform;
@@ -98,18 +153,91 @@ impl SceneNodeData {
         }
 
         match self.object {
-            Some(ref o) => o.render(&self.world_transform, &self.world_scale, pass, camera, light),
-            None        => { }
+            Some(ref o) if !o.data().is_transparent() =>
+                o.render(&self.world_transform, &self.world_scale, pass, camera, lights),
+            _ => { }
+        }
+
+        for p in self.polyline.iter_mut() {
+            p.render(&self.world_transform, &self.world_scale, pass, camera)
+        }
+
+        for p in self.point_cloud.iter_mut() {
+            p.render(&self.world_transform, &self.world_scale, pass, camera)
         }
 
         for c in self.children.iter_mut() {
+            let node   = c.clone();
             let mut bc = c.data_mut();
             if bc.visible {
-                bc.do_render(&self.world_transform, &self.world_scale, pass, camera, light)
+                bc.do_render(&self.world_transform, &self.world_scale, pass, camera, lights, transparents);
+
+                if bc.object.as_ref().map(|o| o.data().is_transparent()).unwrap_or(false) {
+                    let dist = bc.sqdist_to_camera(&*camera);
+                    transparents.push((dist, Some(node)));
+                }
             }
         }
     }
 
+    /// Renders the objects casting shadows of the scene graph rooted by this node.
+    ///
+    /// Every object is rendered with `material` instead of its own material.
+    pub fn render_shadow_casters(&mut self, pass: uint, camera: &mut Camera, material: &mut Material) {
+        if self.visible {
+            self.do_render_shadow_casters(&na::one(), &na::one(), pass, camera, material)
+        }
+    }
+
+    fn do_render_shadow_casters(&mut self,
+                                transform: &Iso3<f32>,
+                                scale:     &Vec3<f32>,
+                                pass:      uint,
+                                camera:    &mut Camera,
+                                material:  &mut Material) {
+        if !self.up_to_date {
+            self.up_to_date      = true;
+            self.world_transform = *transform * self.local_transform;
+            self.world_scale     = *scale * self.local_scale;
+        }
+
+        match self.object {
+            Some(ref o) if o.data().casts_shadows() =>
+                o.render_with_material(material, &self.world_transform, &self.world_scale, pass, camera, &[]),
+            _ => { }
+        }
+
+        for c in self.children.iter_mut() {
+            let mut bc = c.data_mut();
+            if bc.visible {
+                bc.do_render_shadow_casters(&self.world_transform, &self.world_scale, pass, camera, material);
+            }
+        }
+    }
+
+    fn render_object(&self, pass: uint, camera: &mut Camera, lights: &[LightSource]) {
+        match self.object {
+            Some(ref o) => o.render(&self.world_transform, &self.world_scale, pass, camera, lights),
+            None        => { }
+        }
+    }
+
+    // The world transformation and scale used to render this node, computed from its ancestors.
+    fn compute_world_transformation_and_scale(&self) -> (Iso3<f32>, Vec3<f32>) {
+        let (transform, scale) = match self.parent.as_ref().and_then(|p| p.upgrade()) {
+            Some(p) => p.borrow().compute_world_transformation_and_scale(),
+            None    => (na::one(), na::one())
+        };
+
+        (transform * self.local_transform, scale * self.local_scale)
+    }
+
+    fn sqdist_to_camera(&self, camera: &Camera) -> f32 {
+        let center = na::orig::<Pnt3<f32>>() + self.world_transform.translation;
+
+        na::sqdist(&center, &camera.eye())
+    }
+
     /// A reference to the object possibly contained by this node.
     #[inline]
     pub fn object<'a>(&'a self) -> Option<&'a Object> {
@@ -140,6 +268,64 @@ impl SceneNodeData {
         self.object_mut().expect("This scene node does not contain an Object.")
     }
 
+    /// A reference to the polyline possibly contained by this node.
+    #[inline]
+    pub fn polyline<'a>(&'a self) -> Option<&'a Polyline> {
+        self.polyline.as_ref()
+    }
+
+    /// A mutable reference to the polyline possibly contained by this node.
+    #[inline]
+    pub fn polyline_mut<'a>(&'a mut self) -> Option<&'a mut Polyline> {
+        self.polyline.as_mut()
+    }
+
+    /// A reference to the point cloud possibly contained by this node.
+    #[inline]
+    pub fn point_cloud<'a>(&'a self) -> Option<&'a PointCloud> {
+        self.point_cloud.as_ref()
+    }
+
+    /// A mutable reference to the point cloud possibly contained by this node.
+    #[inline]
+    pub fn point_cloud_mut<'a>(&'a mut self) -> Option<&'a mut PointCloud> {
+        self.point_cloud.as_mut()
+    }
+
+    /// The labels attached to this node, with their anchors in the local frame of this node.
+    #[inline]
+    pub fn labels<'a>(&'a self) -> &'a [(Pnt3<f32>, Label)] {
+        self.labels.as_slice()
+    }
+
+    /// A mutable reference to the labels attached to this node, with their anchors in the local
+    /// frame of this node.
+    #[inline]
+    pub fn labels_mut<'a>(&'a mut self) -> &'a mut Vec<(Pnt3<f32>, Label)> {
+        &mut self.labels
+    }
+
+    /// Applies a closure to the labels of the visible nodes of the scene graph rooted by this
+    /// node, with their anchors in world coordinates.
+    ///
+    /// This uses the world transformations computed during the last rendering.
+    pub fn apply_to_visible_labels(&self, f: &mut |&Pnt3<f32>, &Label| -> ()) {
+        if !self.visible {
+            return
+        }
+
+        for &(ref anchor, ref label) in self.labels.iter() {
+            let s = &self.world_scale;
+            let a = self.world_transform * Pnt3::new(anchor.x * s.x, anchor.y * s.y, anchor.z * s.z);
+
+            (*f)(&a, label)
+        }
+
+        for c in self.children.iter() {
+            c.data().apply_to_visible_labels(f)
+        }
+    }
+
     ///////////////////~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ HERE
     /* FIXME: the ~Any is kind of problematic here…
     /// Attaches user-defined data to the objects contained by this node and its children.
@@ -194,6 +380,26 @@ impl SceneNodeData {
         self.apply_to_objects_mut(&mut |o| o.enable_backface_culling(active))
     }
 
+    /// Sets whether the objects contained by this node and its children cast shadows.
+    #[inline]
+    pub fn set_cast_shadows(&mut self, active: bool) {
+        self.apply_to_objects_mut(&mut |o| o.set_cast_shadows(active))
+    }
+
+    /// Sets whether shadows are drawn on the objects contained by this node and its children.
+    #[inline]
+    pub fn set_receive_shadows(&mut self, active: bool) {
+        self.apply_to_objects_mut(&mut |o| o.set_receive_shadows(active))
+    }
+
+    /// Draws the objects contained by this node and its children once per instance.
+    ///
+    /// Each instance is given by its transformation, scale and color. See `Object::set_instances`.
+    #[inline]
+    pub fn set_instances(&mut self, instances: Vec<(Iso3<f32>, Vec3<f32>, Pnt3<f32>)>) {
+        self.apply_to_objects_mut(&mut |o| o.set_instances(instances.clone()))
+    }
+
     /// Mutably accesses the vertices of the objects contained by this node and its children.
     ///
     /// The provided closure is called once per object.
@@ -274,17 +480,42 @@ impl SceneNodeData {
         self.apply_to_objects_mut(&mut |o| o.set_color(r, g, b))
     }
 
+    /// Sets the opacity of the objects contained by this node and its children.
+    ///
+    /// The opacity must be on the range `[0.0, 1.0]`.
+    #[inline]
+    pub fn set_alpha(&mut self, alpha: f32) {
+        self.apply_to_objects_mut(&mut |o| o.set_alpha(alpha))
+    }
+
+    /// Sets the color and the opacity of the objects contained by this node and its children.
+    ///
+    /// Colors components must be on the range `[0.0, 1.0]`.
+    #[inline]
+    pub fn set_color_with_alpha(&mut self, r: f32, g: f32, b: f32, a: f32) {
+        self.apply_to_objects_mut(&mut |o| o.set_color_with_alpha(r, g, b, a))
+    }
+
+    /// Sets the opacity map of the objects contained by this node and its children.
+    pub fn set_opacity_texture(&mut self, texture: Option<Rc<Texture>>) {
+        self.apply_to_objects_mut(&mut |o| o.set_opacity_texture(texture.clone()))
+    }
+
     /// Sets the texture of the objects contained by this node and its children.
     ///
     /// The texture is loaded from a file and registered by the global `TextureManager`.
     ///
     /// # Arguments
     ///   * `path` - relative path of the texture on the disk
+    ///
+    /// Returns an error if the texture cannot be loaded. In that case, the texture is unchanged.
     #[inline]
-    pub fn set_texture_from_file(&mut self, path: &Path, name: &str) {
-        let texture = TextureManager::get_global_manager(|tm| tm.add(path, name));
+    pub fn set_texture_from_file(&mut self, path: &Path, name: &str) -> IoResult<()> {
+        let texture = try!(TextureManager::get_global_manager(|tm| tm.add(path, name)));
 
-        self.set_texture(texture)
+        self.set_texture(texture);
+
+        Ok(())
     }
 
     /// Sets the texture of the objects contained by this node and its children.
@@ -554,6 +785,9 @@ impl SceneNode {
             up_to_date:      false,
             children:        Vec::new(),
             object:          object,
+            polyline:        None,
+            point_cloud:     None,
+            labels:          Vec::new(),
             parent:          None
         };
 
@@ -704,6 +938,75 @@ impl SceneNode {
         node
     }
 
+    /// Adds a double-sided rectangle lying on the `xy` plane, drawn without lighting.
+    ///
+    /// # Arguments
+    /// * `w` - the rectangle width.
+    /// * `h` - the rectangle height.
+    pub fn add_rectangle(&mut self, w: f32, h: f32) -> SceneNode {
+        self.add_convex_polygon(procedural::rectangle(&Vec2::new(w, h)).coords, na::one())
+    }
+
+    /// Adds a double-sided disk lying on the `xy` plane, drawn without lighting.
+    ///
+    /// # Arguments
+    /// * `r` - the disk radius.
+    pub fn add_circle(&mut self, r: f32) -> SceneNode {
+        self.add_convex_polygon(procedural::circle(&(r * 2.0), 64).coords, na::one())
+    }
+
+    /// Adds a double-sided convex polygon lying on the `xy` plane, drawn without lighting.
+    ///
+    /// The polygon is triangulated as a fan from its first vertex. The texture coordinates are
+    /// given by the polygon bounding rectangle.
+    ///
+    /// # Arguments
+    /// * `polygon` - the polygon vertices, in clockwise or counterclockwise order.
+    /// * `scale` - the scaling factors applied to the polygon vertices.
+    pub fn add_convex_polygon(&mut self, polygon: Vec<Pnt2<f32>>, scale: Vec2<f32>) -> SceneNode {
+        let coords: Vec<Pnt3<f32>> = polygon.iter().map(|p| Pnt3::new(p.x * scale.x, p.y * scale.y, 0.0)).collect();
+
+        let mut mins = Pnt2::new(f32::MAX, f32::MAX);
+        let mut maxs = Pnt2::new(-f32::MAX, -f32::MAX);
+
+        for p in coords.iter() {
+            mins = Pnt2::new(mins.x.min(p.x), mins.y.min(p.y));
+            maxs = Pnt2::new(maxs.x.max(p.x), maxs.y.max(p.y));
+        }
+
+        let extents = maxs - mins;
+        let uvs     = coords.iter().map(|p| {
+            Pnt2::new(if extents.x > 0.0 { (p.x - mins.x) / extents.x } else { 0.0 },
+                      if extents.y > 0.0 { (maxs.y - p.y) / extents.y } else { 0.0 })
+        }).collect();
+        let normals = Vec::from_elem(coords.len(), Vec3::z());
+        let faces   = range(2u, coords.len()).map(|i| Vec3::new(0, (i - 1) as u32, i as u32)).collect();
+
+        let mesh     = TriMesh::new(coords, Some(normals), Some(uvs), Some(IndexBuffer::Unified(faces)));
+        let mut node = self.add_trimesh(mesh, na::one());
+
+        node.enable_backface_culling(false);
+        node.set_material_with_name("flat");
+
+        node
+    }
+
+    /// Adds a closed polyline lying on the `xy` plane, e.g. the outline of a 2D shape.
+    ///
+    /// The last vertex of `polyline` is joined to the first one.
+    pub fn add_planar_polyline(&mut self, polyline: &Polyline2<f32>, color: Pnt3<f32>, width: f32) -> SceneNode {
+        let mut coords: Vec<Pnt3<f32>> = polyline.coords.iter().map(|p| Pnt3::new(p.x, p.y, 0.0)).collect();
+
+        if !coords.is_empty() {
+            let first = coords[0];
+            coords.push(first);
+        }
+
+        let colors = Vec::from_elem(coords.len(), color);
+
+        self.add_polyline(coords, colors, width)
+    }
+
     /// Creates and adds a new object using the geometry registered as `geometry_name`.
     pub fn add_geom_with_name(&mut self, geometry_name: &str, scale: Vec3<f32>) -> Option<SceneNode> {
         MeshManager::get_global_manager(|mm| mm.get(geometry_name)).map(|g| self.add_mesh(g, scale))
@@ -723,70 +1026,282 @@ impl SceneNode {
         self.add_mesh(Rc::new(RefCell::new(Mesh::from_trimesh(descr, false))), scale)
     }
 
+    /// Creates and adds a new object drawing a mesh once per instance, with a single draw call
+    /// if the OpenGL context supports it (see `Instances::is_supported`).
+    ///
+    /// Each instance is given by its transformation, scale and color, relative to the new node.
+    /// Use `set_instances` to update them.
+    pub fn add_instanced(&mut self, mesh: Rc<RefCell<Mesh>>, instances: Vec<(Iso3<f32>, Vec3<f32>, Pnt3<f32>)>) -> SceneNode {
+        let mut node = self.add_mesh(mesh, na::one());
+
+        node.set_instances(instances);
+
+        node
+    }
+
+    /// Creates and adds a new node drawing a line strip through `coords`.
+    ///
+    /// The vertices are uploaded to the GPU once and kept there until they are modified with
+    /// `data_mut().polyline_mut()`. There must be exactly one color per vertex.
+    pub fn add_polyline(&mut self, coords: Vec<Pnt3<f32>>, colors: Vec<Pnt3<f32>>, width: f32) -> SceneNode {
+        let node = SceneNode::new_empty();
+
+        node.data.borrow_mut().polyline = Some(Polyline::new(coords, colors, width));
+        self.add_child(node.clone());
+
+        node
+    }
+
+    /// Creates and adds a new node drawing a point at each element of `coords`.
+    ///
+    /// The points are uploaded to the GPU once and kept there until they are modified with
+    /// `data_mut().point_cloud_mut()`. There must be exactly one color per point.
+    pub fn add_point_cloud(&mut self, coords: Vec<Pnt3<f32>>, colors: Vec<Pnt3<f32>>, size: f32) -> SceneNode {
+        let node = SceneNode::new_empty();
+
+        node.data.borrow_mut().point_cloud = Some(PointCloud::new(coords, colors, size));
+        self.add_child(node.clone());
+
+        node
+    }
+
+    /// Attaches a label to this node.
+    ///
+    /// The label is displayed next to `anchor`, given in the local frame of this node, as long
+    /// as this node is visible.
+    pub fn add_label(&mut self, anchor: Pnt3<f32>, label: Label) {
+        self.data_mut().labels.push((anchor, label))
+    }
+
+    /// Removes every label attached to this node.
+    pub fn clear_labels(&mut self) {
+        self.data_mut().labels.clear()
+    }
+
     /// Creates and adds multiple nodes created from an obj file.
     ///
     /// This will create a new node serving as a root of the scene described by the obj file. This
     /// newly created node is added to this node's children.
-    pub fn add_obj(&mut self, path: &Path, mtl_dir: &Path, scale: Vec3<f32>) -> SceneNode {
+    ///
+    /// Returns the new node and the list of warnings about ignored data, including textures that
+    /// failed to load. Nothing is added if the file cannot be loaded.
+    pub fn add_obj(&mut self, path: &Path, mtl_dir: &Path, scale: Vec3<f32>)
+                   -> Result<(SceneNode, Vec<ParseError>), LoadError> {
         let tex = TextureManager::get_global_manager(|tm| tm.get_default());
         let mat = MaterialManager::get_global_manager(|mm| mm.get_default());
 
-        // FIXME: is there some error-handling stuff to do here instead of the `let _`.
-        let result = MeshManager::load_obj(path, mtl_dir, path.as_str().unwrap()).map(|objs| {
-            let mut root;
+        let (objs, mut warnings) = try!(MeshManager::load_obj(path, mtl_dir, path.as_str().unwrap()));
 
-            let self_root = objs.len() == 1;
-            let child_scale;
+        if objs.is_empty() {
+            return Err(LoadError::Parse(ParseError::new(0, "", "the file does not contain any face.".to_string())));
+        }
 
-            if self_root {
-                root = self.clone();
-                child_scale = scale;
-            }
-            else {
-                root = SceneNode::new(scale, na::one(), None);
-                self.add_child(root.clone());
-                child_scale = na::one();
-            }
+        let mut root;
 
-            for (_, mesh, mtl) in objs.into_iter() {
-                let mut object = Object::new(
-                    mesh,
-                    1.0, 1.0, 1.0,
-                    tex.clone(),
-                    mat.clone()
-                    );
+        let self_root = objs.len() == 1;
+        let child_scale;
 
-                match mtl {
-                    None      => { },
-                    Some(mtl) => {
-                        object.set_color(mtl.diffuse.x, mtl.diffuse.y, mtl.diffuse.z);
+        if self_root {
+            root = self.clone();
+            child_scale = scale;
+        }
+        else {
+            root = SceneNode::new(scale, na::one(), None);
+            self.add_child(root.clone());
+            child_scale = na::one();
+        }
 
-                        for t in mtl.diffuse_texture.iter() {
+        for (_, mesh, mtl) in objs.into_iter() {
+            let mut object = Object::new(
+                mesh,
+                1.0, 1.0, 1.0,
+                tex.clone(),
+                mat.clone()
+                );
+
+            match mtl {
+                None      => { },
+                Some(mtl) => {
+                    object.set_color_with_alpha(mtl.diffuse.x, mtl.diffuse.y, mtl.diffuse.z, mtl.alpha);
+
+                    let maps = [ ("map_Kd", &mtl.diffuse_texture),
+                                 ("map_Ka", &mtl.ambiant_texture),
+                                 ("map_d",  &mtl.opacity_map) ];
+
+                    for &(tag, t) in maps.iter() {
+                        for t in t.iter() {
                             let mut tpath = mtl_dir.clone();
                             tpath.push(t.as_slice());
-                            object.set_texture_from_file(&tpath, tpath.as_str().unwrap())
-                        }
 
-                        for t in mtl.ambiant_texture.iter() {
-                            let mut tpath = mtl_dir.clone();
-                            tpath.push(t.as_slice());
-                            object.set_texture_from_file(&tpath, tpath.as_str().unwrap())
+                            match TextureManager::get_global_manager(|tm| tm.add(&tpath, tpath.as_str().unwrap())) {
+                                Ok(texture) => {
+                                    if tag == "map_d" {
+                                        object.set_opacity_texture(Some(texture))
+                                    }
+                                    else {
+                                        object.set_texture(texture)
+                                    }
+                                },
+                                Err(e) => warnings.push(ParseError::new(0, tag, format!("{}", e)))
+                            }
                         }
                     }
                 }
+            }
+
+            let _ = root.add_object(child_scale, na::one(), object);
+        }
+
+        if self_root {
+            let node = root.data().children.last().unwrap().clone();
+
+            Ok((node, warnings))
+        }
+        else {
+            Ok((root, warnings))
+        }
+    }
+
+    /// Creates and adds multiple nodes created from a glTF 2.0 file (`.gltf` or `.glb`).
+    ///
+    /// This will create a new node serving as a root of the scene described by the file. This
+    /// newly created node is added to this node's children. The glTF node hierarchy is preserved
+    /// and meshes used by several nodes are shared.
+    ///
+    /// Returns the new node and the list of warnings about ignored data, including textures that
+    /// failed to load, and rotations under non-uniformly scaled parents, whose shear cannot be
+    /// represented. Nothing is added if the file cannot be loaded.
+    pub fn add_gltf(&mut self, path: &Path) -> Result<(SceneNode, Vec<ParseError>), LoadError> {
+        let (doc, mut warnings) = try!(gltf::parse_file(path));
+
+        // Textures are keyed by their file path, so an image file shared with other scenes keeps
+        // the sampling options it was first loaded with.
+        let textures: Vec<Option<Rc<Texture>>> = doc.textures.iter().enumerate().map(|(i, texture)| {
+            let options = &texture.options;
+            let texture = match doc.images[texture.image] {
+                GltfImage::File(ref ipath) => match ipath.as_str() {
+                    Some(name) => TextureManager::get_global_manager(|tm| tm.add_with_options(ipath, name, options)),
+                    None       => Err(IoError {
+                        kind:   InvalidInput,
+                        desc:   "the texture path is not valid UTF-8",
+                        detail: Some(format!("{}", ipath.display()))
+                    })
+                },
+                GltfImage::Memory(ref data) => {
+                    let name = format!("{}#texture{}", path.display(), i);
+                    TextureManager::get_global_manager(|tm| tm.add_from_memory_with_options(data.as_slice(), name.as_slice(), options))
+                }
+            };
 
-                let _ = root.add_object(child_scale, na::one(), object);
+            match texture {
+                Ok(texture) => Some(texture),
+                Err(e)      => {
+                    warnings.push(ParseError::new(0, "images", format!("{}", e)));
+                    None
+                }
             }
+        }).collect();
+
+        let mut root    = SceneNode::new(na::one(), na::one(), None);
+        let mut visited = Vec::from_elem(doc.nodes.len(), false);
+
+        for id in doc.roots.iter() {
+            root.add_gltf_node(&doc, textures.as_slice(), *id, na::one(), visited.as_mut_slice(), &mut warnings);
+        }
+
+        self.add_child(root.clone());
+
+        Ok((root, warnings))
+    }
+
+    fn add_gltf_node(&mut self,
+                     doc:          &GltfDocument,
+                     textures:     &[Option<Rc<Texture>>],
+                     id:           uint,
+                     parent_scale: Vec3<f32>,
+                     visited:      &mut [bool],
+                     warnings:     &mut Vec<ParseError>) {
+        if visited[id] {
+            return;
+        }
+
+        visited[id] = true;
+
+        let gnode = &doc.nodes[id];
 
-            if self_root {
-                root.data().children.last().expect("There was nothing on this obj file.").clone()
+        // Our nodes cannot represent the shear of a rotation under a non-uniform scale.
+        if (parent_scale.x != parent_scale.y || parent_scale.x != parent_scale.z) &&
+           na::sqnorm(&na::rotation(&gnode.transform)) > 1.0e-12 {
+            warnings.push(ParseError::new(0, "nodes",
+                format!("node `{}` is rotated under a non-uniformly scaled parent: its shear is ignored", gnode.name)));
+        }
+
+        // Our nodes do not scale their children translations, so do it here.
+        let mut transform = gnode.transform;
+        transform.translation = transform.translation * parent_scale;
+
+        let mut node = SceneNode::new(gnode.scale, transform, None);
+
+        for primitives in gnode.mesh.iter().map(|m| &doc.meshes[*m]) {
+            for primitive in primitives.iter() {
+                let material = primitive.material.map(|m| &doc.materials[m]);
+                let color    = material.map(|m| m.base_color).unwrap_or(Pnt3::new(1.0, 1.0, 1.0));
+                let texture  = material.and_then(|m| m.base_color_texture).and_then(|t| textures[t].clone());
+                let texture  = texture.unwrap_or_else(|| TextureManager::get_global_manager(|tm| tm.get_default()));
+                let mat      = MaterialManager::get_global_manager(|mm| mm.get_default());
+
+                let mut object = Object::new(primitive.mesh.clone(), color.x, color.y, color.z, texture, mat);
+                object.set_alpha(material.map(|m| m.alpha).unwrap_or(1.0));
+                object.set_alpha_cutoff(material.and_then(|m| m.alpha_cutoff));
+
+                let _ = node.add_object(na::one(), na::one(), object);
             }
-            else {
-                root
+        }
+
+        for child in gnode.children.iter() {
+            node.add_gltf_node(doc, textures, *child, parent_scale * gnode.scale, visited, warnings);
+        }
+
+        self.add_child(node);
+    }
+
+    /// Creates and adds a new object from an ASCII or binary STL file.
+    ///
+    /// Returns the new node and the list of warnings about ignored lines. Nothing is added if the
+    /// file cannot be loaded.
+    pub fn add_stl(&mut self, path: &Path, scale: Vec3<f32>) -> Result<(SceneNode, Vec<ParseError>), LoadError> {
+        let (mesh, warnings) = try!(stl::parse_file(path));
+
+        Ok((self.add_trimesh(mesh, scale), warnings))
+    }
+
+    /// Creates and adds a new object or point cloud from a PLY file.
+    ///
+    /// Files without faces are added as point clouds, with their vertex colors if any, and white
+    /// points otherwise. Vertex colors of meshes are ignored.
+    ///
+    /// Returns the new node and the list of warnings about ignored data. Nothing is added if the
+    /// file cannot be loaded.
+    pub fn add_ply(&mut self, path: &Path, scale: Vec3<f32>) -> Result<(SceneNode, Vec<ParseError>), LoadError> {
+        let (mesh, mut warnings) = try!(ply::parse_file(path));
+
+        if mesh.is_point_cloud() {
+            let ply::PlyMesh { coords, colors, .. } = mesh;
+
+            let colors   = colors.unwrap_or_else(|| Vec::from_elem(coords.len(), Pnt3::new(1.0, 1.0, 1.0)));
+            let mut node = self.add_point_cloud(coords, colors, 1.0);
+
+            node.set_local_scale(scale.x, scale.y, scale.z);
+
+            Ok((node, warnings))
+        }
+        else {
+            if mesh.colors.is_some() {
+                warnings.push(ParseError::new(0, "vertex", "vertex colors of meshes are ignored.".to_string()));
             }
-        });
 
-        result.unwrap()
+            Ok((self.add_trimesh(mesh.to_trimesh().unwrap(), scale), warnings))
+        }
     }
 
     /// Applies a closure to each object contained by this node and its children.
@@ -809,6 +1324,232 @@ impl SceneNode {
         }
     }
 
+    /// Applies a closure to each object contained by this node and its children, together with
+    /// the world transformation and scale it is rendered with.
+    pub fn apply_to_objects_with_world_transformation(&self, f: &mut |&Object, &Iso3<f32>, &Vec3<f32>| -> ()) {
+        let (transform, scale) = match self.data().parent.as_ref().and_then(|p| p.upgrade()) {
+            Some(p) => p.borrow().compute_world_transformation_and_scale(),
+            None    => (na::one(), na::one())
+        };
+
+        self.do_apply_to_objects_with_world_transformation(&transform, &scale, f)
+    }
+
+    fn do_apply_to_objects_with_world_transformation(&self,
+                                                     transform: &Iso3<f32>,
+                                                     scale:     &Vec3<f32>,
+                                                     f:         &mut |&Object, &Iso3<f32>, &Vec3<f32>| -> ()) {
+        let data            = self.data();
+        let world_transform = *transform * data.local_transform;
+        let world_scale     = *scale * data.local_scale;
+
+        match data.object {
+            Some(ref o) => (*f)(o, &world_transform, &world_scale),
+            None        => { }
+        }
+
+        for c in data.children.iter() {
+            c.do_apply_to_objects_with_world_transformation(&world_transform, &world_scale, f)
+        }
+    }
+
+    /// The axis-aligned bounding box of the visible objects contained by this node and its
+    /// children, in world coordinates.
+    ///
+    /// Returns the minimum and maximum corners of the box, or `None` if there is no vertex. Every
+    /// instance of instanced objects is included. Hidden nodes and their children, polylines and
+    /// point clouds are not taken into account.
+    pub fn world_bounding_box(&self) -> Option<(Pnt3<f32>, Pnt3<f32>)> {
+        let (transform, scale) = match self.data().parent.as_ref().and_then(|p| p.upgrade()) {
+            Some(p) => p.borrow().compute_world_transformation_and_scale(),
+            None    => (na::one(), na::one())
+        };
+        let mut res = None;
+
+        self.do_world_bounding_box(&transform, &scale, &mut res);
+
+        res
+    }
+
+    fn do_world_bounding_box(&self,
+                             transform: &Iso3<f32>,
+                             scale:     &Vec3<f32>,
+                             res:       &mut Option<(Pnt3<f32>, Pnt3<f32>)>) {
+        let data = self.data();
+
+        if !data.visible {
+            return;
+        }
+
+        let world_transform = *transform * data.local_transform;
+        let world_scale     = *scale * data.local_scale;
+
+        match data.object {
+            Some(ref o) => {
+                let instances = o.data().instances().map(|i| i.borrow());
+
+                o.read_vertices(&mut |vs| {
+                    let mut add = |v: Pnt3<f32>| {
+                        let p = world_transform * Pnt3::new(v.x * world_scale.x, v.y * world_scale.y, v.z * world_scale.z);
+
+                        *res = match *res {
+                            Some((mins, maxs)) => Some((na::inf(&mins, &p), na::sup(&maxs, &p))),
+                            None               => Some((p, p))
+                        };
+                    };
+
+                    match instances {
+                        Some(ref instances) => {
+                            for &(ref itransform, ref iscale, _) in instances.instances().iter() {
+                                for v in vs.iter() {
+                                    add(*itransform * Pnt3::new(v.x * iscale.x, v.y * iscale.y, v.z * iscale.z))
+                                }
+                            }
+                        },
+                        None => {
+                            for v in vs.iter() {
+                                add(*v)
+                            }
+                        }
+                    }
+                })
+            },
+            None => { }
+        }
+
+        for c in data.children.iter() {
+            c.do_world_bounding_box(&world_transform, &world_scale, res)
+        }
+    }
+
+    /// Merges the meshes of every object contained by this node and its children into a single
+    /// triangle mesh, with their world transformations and scales baked in.
+    ///
+    /// Normals and texture coordinates are kept only if every mesh has them. Returns `None` if
+    /// there is no object.
+    pub fn to_world_trimesh(&self) -> Option<TriMesh3<f32>> {
+        let mut coords  = Vec::new();
+        let mut faces   = Vec::new();
+        let mut normals = Some(Vec::new());
+        let mut uvs     = Some(Vec::new());
+        let mut empty   = true;
+
+        self.apply_to_objects_with_world_transformation(&mut |o, transform, scale| {
+            let base = coords.len() as u32;
+            empty    = false;
+
+            o.read_vertices(&mut |vs| {
+                for v in vs.iter() {
+                    coords.push(*transform * Pnt3::new(v.x * scale.x, v.y * scale.y, v.z * scale.z))
+                }
+            });
+
+            let nverts = coords.len() - base as uint;
+
+            o.read_faces(&mut |fs| {
+                for f in fs.iter() {
+                    faces.push(*f + Vec3::new(base, base, base))
+                }
+            });
+
+            let mut has_normals = false;
+
+            o.read_normals(&mut |ns| {
+                if ns.len() == nverts {
+                    has_normals = true;
+
+                    for n in normals.iter_mut() {
+                        // Normals are transformed by the inverse transpose of the scaling.
+                        n.extend(ns.iter().map(|n| {
+                            let n = Vec3::new(n.x / scale.x, n.y / scale.y, n.z / scale.z);
+                            na::normalize(&na::rotate(transform, &n))
+                        }))
+                    }
+                }
+            });
+
+            if !has_normals {
+                normals = None
+            }
+
+            let mut has_uvs = false;
+
+            o.read_uvs(&mut |ts| {
+                if ts.len() == nverts {
+                    has_uvs = true;
+
+                    for t in uvs.iter_mut() {
+                        t.push_all(ts)
+                    }
+                }
+            });
+
+            if !has_uvs {
+                uvs = None
+            }
+        });
+
+        if empty {
+            None
+        }
+        else {
+            Some(TriMesh::new(coords, normals, uvs, Some(IndexBuffer::Unified(faces))))
+        }
+    }
+
+    /// Casts a ray on the visible objects contained by this node and its children.
+    ///
+    /// Returns the closest hit, if any.
+    ///
+    /// # Arguments
+    /// * `orig` - the world-space origin of the ray.
+    /// * `dir` - the world-space direction of the ray.
+    pub fn cast_ray(&self, orig: &Pnt3<f32>, dir: &Vec3<f32>) -> Option<PickResult> {
+        self.do_cast_ray(&na::one(), &na::one(), orig, dir)
+    }
+
+    fn do_cast_ray(&self,
+                   transform: &Iso3<f32>,
+                   scale:     &Vec3<f32>,
+                   orig:      &Pnt3<f32>,
+                   dir:       &Vec3<f32>)
+                   -> Option<PickResult> {
+        let data = self.data();
+
+        if !data.visible {
+            return None;
+        }
+
+        let world_transform = *transform * data.local_transform;
+        let world_scale     = *scale * data.local_scale;
+
+        let mut best = data.object.as_ref().and_then(|o| {
+            pick::cast_ray_on_object(o, &world_transform, &world_scale, orig, dir).map(|(toi, normal, triangle, instance)| {
+                PickResult {
+                    node:     self.clone(),
+                    point:    *orig + *dir * toi,
+                    normal:   normal,
+                    triangle: triangle,
+                    instance: instance,
+                    toi:      toi
+                }
+            })
+        });
+
+        for c in data.children.iter() {
+            match c.do_cast_ray(&world_transform, &world_scale, orig, dir) {
+                Some(res) => {
+                    if best.as_ref().map(|b| res.toi < b.toi).unwrap_or(true) {
+                        best = Some(res)
+                    }
+                },
+                None => { }
+            }
+        }
+
+        best
+    }
+
     //
     //
     // fwd
@@ -816,8 +1557,8 @@ impl SceneNode {
     //
 
     /// Render the scene graph rooted by this node.
-    pub fn render(&mut self, pass: uint, camera: &mut Camera, light: &Light) {
-        self.data_mut().render(pass, camera, light)
+    pub fn render(&mut self, pass: uint, camera: &mut Camera, lights: &[LightSource]) {
+        self.data_mut().render(pass, camera, lights)
     }
 
     /// Sets the material of the objects contained by this node and its children.
@@ -858,6 +1599,26 @@ impl SceneNode {
         self.data_mut().enable_backface_culling(active)
     }
 
+    /// Sets whether the objects contained by this node and its children cast shadows.
+    #[inline]
+    pub fn set_cast_shadows(&mut self, active: bool) {
+        self.data_mut().set_cast_shadows(active)
+    }
+
+    /// Sets whether shadows are drawn on the objects contained by this node and its children.
+    #[inline]
+    pub fn set_receive_shadows(&mut self, active: bool) {
+        self.data_mut().set_receive_shadows(active)
+    }
+
+    /// Draws the objects contained by this node and its children once per instance.
+    ///
+    /// Each instance is given by its transformation, scale and color. See `Object::set_instances`.
+    #[inline]
+    pub fn set_instances(&mut self, instances: Vec<(Iso3<f32>, Vec3<f32>, Pnt3<f32>)>) {
+        self.data_mut().set_instances(instances)
+    }
+
     /// Mutably accesses the vertices of the objects contained by this node and its children.
     ///
     /// The provided closure is called once per object.
@@ -938,14 +1699,37 @@ impl SceneNode {
         self.data_mut().set_color(r, g, b)
     }
 
+    /// Sets the opacity of the objects contained by this node and its children.
+    ///
+    /// The opacity must be on the range `[0.0, 1.0]`.
+    #[inline]
+    pub fn set_alpha(&mut self, alpha: f32) {
+        self.data_mut().set_alpha(alpha)
+    }
+
+    /// Sets the color and the opacity of the objects contained by this node and its children.
+    ///
+    /// Colors components must be on the range `[0.0, 1.0]`.
+    #[inline]
+    pub fn set_color_with_alpha(&mut self, r: f32, g: f32, b: f32, a: f32) {
+        self.data_mut().set_color_with_alpha(r, g, b, a)
+    }
+
+    /// Sets the opacity map of the objects contained by this node and its children.
+    pub fn set_opacity_texture(&mut self, texture: Option<Rc<Texture>>) {
+        self.data_mut().set_opacity_texture(texture)
+    }
+
     /// Sets the texture of the objects contained by this node and its children.
     ///
     /// The texture is loaded from a file and registered by the global `TextureManager`.
     ///
     /// # Arguments
     ///   * `path` - relative path of the texture on the disk
+    ///
+    /// Returns an error if the texture cannot be loaded. In that case, the texture is unchanged.
     #[inline]
-    pub fn set_texture_from_file(&mut self, path: &Path, name: &str) {
+    pub fn set_texture_from_file(&mut self, path: &Path, name: &str) -> IoResult<()> {
         self.data_mut().set_texture_from_file(path, name)
     }
 
@@ -0,0 +1,217 @@
+//! Shadow map rendering.
+
+use std::f32;
+use std::num::FloatMath;
+use gl;
+use gl::types::*;
+use na::{Pnt3, Vec3, Mat4, Iso3, PerspMat3, OrthoMat3};
+use na;
+use glfw;
+use resource::{FramebufferManager, RenderTarget};
+use builtin::DepthMaterial;
+use light::{LightKind, LightSource};
+use camera::Camera;
+use scene::SceneNode;
+
+#[path = "error.rs"]
+mod error;
+
+/// The widest frustum rendered for the shadows of a point light, in radians.
+static MAX_POINT_FOV: f32 = 2.0 * f32::consts::FRAC_PI_3;
+
+/// A camera looking at the scene from the point of view of a light.
+struct LightCamera {
+    eye:           Pnt3<f32>,
+    view:          Iso3<f32>,
+    znear:         f32,
+    zfar:          f32,
+    proj_view:     Mat4<f32>,
+    inv_proj_view: Mat4<f32>
+}
+
+impl LightCamera {
+    fn new() -> LightCamera {
+        LightCamera {
+            eye:           na::orig(),
+            view:          na::one(),
+            znear:         0.1,
+            zfar:          1024.0,
+            proj_view:     na::one(),
+            inv_proj_view: na::one()
+        }
+    }
+
+    fn look_at_z(&mut self, eye: Pnt3<f32>, at: Pnt3<f32>, projection: Mat4<f32>, znear: f32, zfar: f32) {
+        let dir = na::normalize(&(at - eye));
+        let up  = if dir.y.abs() > 0.99 { Vec3::x() } else { Vec3::y() };
+
+        self.eye   = eye;
+        self.znear = znear;
+        self.zfar  = zfar;
+        self.view  = na::one();
+        self.view.look_at_z(&eye, &at, &up);
+
+        self.proj_view     = projection * na::to_homogeneous(&na::inv(&self.view).unwrap());
+        self.inv_proj_view = na::inv(&self.proj_view).unwrap();
+    }
+}
+
+impl Camera for LightCamera {
+    fn handle_event(&mut self, _: &glfw::Window, _: &glfw::WindowEvent) { }
+
+    fn eye(&self) -> Pnt3<f32> {
+        self.eye
+    }
+
+    fn view_transform(&self) -> Iso3<f32> {
+        self.view
+    }
+
+    fn transformation(&self) -> Mat4<f32> {
+        self.proj_view
+    }
+
+    fn inv_transformation(&self) -> Mat4<f32> {
+        self.inv_proj_view
+    }
+
+    fn clip_planes(&self) -> (f32, f32) {
+        (self.znear, self.zfar)
+    }
+
+    fn update(&mut self, _: &glfw::Window) { }
+}
+
+/// Structure which renders the depth of the scene as seen from a light.
+///
+/// The region of the scene covered by the shadow map is a sphere. Objects outside of this region
+/// neither cast nor receive shadows.
+///
+/// Point lights are handled like spot lights aimed at the center of the region: a single
+/// frustum, at most 120 degrees wide, is rendered instead of a cube map. Only the part of the
+/// region in front of the light is shadowed, and everything outside of this frustum is lit. Place
+/// point lights outside of the region so that it fits in the frustum.
+pub struct ShadowMap {
+    size:     uint,
+    center:   Pnt3<f32>,
+    radius:   f32,
+    target:   RenderTarget,
+    material: DepthMaterial,
+    camera:   LightCamera
+}
+
+impl ShadowMap {
+    /// Creates a new square shadow map with `size` texels on each side.
+    ///
+    /// It initially covers a sphere of radius 10 centered at the origin.
+    pub fn new(size: uint) -> ShadowMap {
+        ShadowMap {
+            size:     size,
+            center:   na::orig(),
+            radius:   10.0,
+            target:   FramebufferManager::new_render_target(size, size),
+            material: DepthMaterial::new(),
+            camera:   LightCamera::new()
+        }
+    }
+
+    /// The number of texels on each side of this shadow map.
+    #[inline]
+    pub fn size(&self) -> uint {
+        self.size
+    }
+
+    /// The center of the region covered by this shadow map.
+    #[inline]
+    pub fn center(&self) -> Pnt3<f32> {
+        self.center
+    }
+
+    /// The radius of the region covered by this shadow map.
+    #[inline]
+    pub fn radius(&self) -> f32 {
+        self.radius
+    }
+
+    /// Sets the region of the scene covered by this shadow map.
+    ///
+    /// A smaller region gives sharper shadows.
+    #[inline]
+    pub fn set_region(&mut self, center: Pnt3<f32>, radius: f32) {
+        assert!(radius > 0.0, "The shadow map radius must be strictly positive.");
+        self.center = center;
+        self.radius = radius;
+    }
+
+    /// The transformation from world coordinates to the light device coordinates.
+    #[inline]
+    pub fn transformation(&self) -> Mat4<f32> {
+        self.camera.transformation()
+    }
+
+    /// The opengl handle to the depth texture of this shadow map.
+    #[inline]
+    pub fn depth_texture_id(&self) -> GLuint {
+        self.target.depth_id()
+    }
+
+    /// Renders the objects of `scene` casting shadows, as seen from `light`.
+    ///
+    /// The camera is needed to locate lights sticking to it. This leaves the shadow map render
+    /// target selected.
+    pub fn render(&mut self,
+                  scene:               &mut SceneNode,
+                  light:               &LightSource,
+                  camera:              &Camera,
+                  framebuffer_manager: &mut FramebufferManager) {
+        let (pos, dir) = light.position_and_direction(camera);
+        let radius     = self.radius;
+        let center     = self.center;
+
+        match light.kind {
+            LightKind::Directional => {
+                let eye   = center + dir * (-2.0 * radius);
+                let proj  = OrthoMat3::new(2.0 * radius, 2.0 * radius, 0.01 * radius, 4.0 * radius);
+
+                self.camera.look_at_z(eye, center, *proj.as_mat(), 0.01 * radius, 4.0 * radius);
+            },
+            LightKind::Spot => {
+                let fov   = (2.0 * light.cutoff).min(0.9 * f32::consts::PI);
+                let zfar  = na::dist(&pos, &center) + radius;
+                let proj  = PerspMat3::new(1.0, fov, 0.1, zfar);
+
+                self.camera.look_at_z(pos, pos + dir, *proj.as_mat(), 0.1, zfar);
+            },
+            LightKind::Point => {
+                let dist  = na::dist(&pos, &center);
+                let at    = if dist < 0.0001 { pos - Vec3::y() } else { center };
+                let zfar  = dist + radius;
+                // Fit the region if the light is outside of it.
+                let fov   = if dist > radius { 2.0 * (radius / dist).asin() } else { MAX_POINT_FOV };
+                let proj  = PerspMat3::new(1.0, fov.min(MAX_POINT_FOV), 0.1, zfar);
+
+                self.camera.look_at_z(pos, at, *proj.as_mat(), 0.1, zfar);
+            }
+        }
+
+        framebuffer_manager.select(&self.target);
+
+        let mut viewport: [GLint, ..4] = [0, 0, 0, 0];
+        unsafe { verify!(gl::GetIntegerv(gl::VIEWPORT, &mut viewport[0])); }
+
+        verify!(gl::Viewport(0, 0, self.size as GLint, self.size as GLint));
+        verify!(gl::Scissor(0, 0, self.size as GLint, self.size as GLint));
+        verify!(gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT));
+
+        // Push the depths a bit away to avoid self-shadowing artifacts.
+        verify!(gl::Enable(gl::POLYGON_OFFSET_FILL));
+        verify!(gl::PolygonOffset(2.0, 4.0));
+
+        scene.data_mut().render_shadow_casters(0, &mut self.camera, &mut self.material);
+
+        verify!(gl::Disable(gl::POLYGON_OFFSET_FILL));
+
+        verify!(gl::Viewport(viewport[0], viewport[1], viewport[2], viewport[3]));
+        verify!(gl::Scissor(viewport[0], viewport[1], viewport[2], viewport[3]));
+    }
+}
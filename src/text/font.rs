@@ -3,9 +3,12 @@
 // It has been modified to work with gl-rs, nalgebra, and rust-freetype
 
 use std::rc::Rc;
-use std::num::UnsignedInt;
+use std::cell::RefCell;
+use std::collections::HashMap;
 use std::cmp;
+use std::num::Float;
 use std::ptr;
+use std::io::{IoResult, IoError, InvalidInput, OtherIoError};
 use std::kinds::marker::NoCopy;
 use libc::{c_uint, c_void};
 use gl;
@@ -14,19 +17,106 @@ use freetype::ffi;
 use na::Vec2;
 use na;
 use text::Glyph;
+use text::sdf;
 
 #[path = "../error.rs"]
 mod error;
 
+/// Width of the texture atlases, in pixels.
+static ATLAS_WIDTH:          uint   = 1024;
+/// Initial height of the texture atlases, in pixels. It is doubled each time the atlas is full.
+static ATLAS_INITIAL_HEIGHT: uint   = 64;
+/// The `FT_KERNING_DEFAULT` kerning mode: grid-fitted distances in 26.6 pixels.
+static FT_KERNING_DEFAULT:   c_uint = 0;
+/// Extent of the distance fields around the glyphs, relative to the font size.
+static SDF_SPREAD_RATIO:     f32    = 0.125;
+
+// A glyph copied to the atlas, with what is needed to compute kerning.
+struct CachedGlyph {
+    glyph: Glyph,
+    face:  uint,
+    index: c_uint
+}
+
+// The glyphs rasterized so far, and the pixels of the atlas they are packed into, row by row.
+struct GlyphAtlas {
+    glyphs:     HashMap<char, Option<CachedGlyph>>,
+    pixels:     Vec<u8>,
+    dimensions: Vec2<uint>,
+    cursor:     Vec2<uint>,
+    row_height: uint,
+    dirty:      bool
+}
+
+impl GlyphAtlas {
+    fn new() -> GlyphAtlas {
+        GlyphAtlas {
+            glyphs:     HashMap::new(),
+            pixels:     Vec::from_elem(ATLAS_WIDTH * ATLAS_INITIAL_HEIGHT, 0u8),
+            dimensions: Vec2::new(ATLAS_WIDTH, ATLAS_INITIAL_HEIGHT),
+            cursor:     na::zero(),
+            row_height: 0,
+            dirty:      true
+        }
+    }
+
+    // Copies a bitmap with contiguous rows to the atlas and returns its position, in pixels.
+    fn insert(&mut self, pixels: &[u8], stride: uint, rows: uint) -> Vec2<f32> {
+        let width = cmp::min(stride, self.dimensions.x - 1);
+
+        /* If we've exhausted the width for this row, add another. */
+        if self.cursor.x + width + 1 > self.dimensions.x {
+            self.cursor.x   = 0;
+            self.cursor.y   = self.cursor.y + self.row_height + 1;
+            self.row_height = 0;
+        }
+
+        /* Rows are contiguous so growing the atlas only requires new rows at its end. */
+        while self.cursor.y + rows > self.dimensions.y {
+            self.dimensions.y = self.dimensions.y * 2;
+
+            let len = self.dimensions.x * self.dimensions.y - self.pixels.len();
+            self.pixels.grow(len, 0u8);
+        }
+
+        for r in range(0u, rows) {
+            let src = r * stride;
+            let dst = (self.cursor.y + r) * self.dimensions.x + self.cursor.x;
+
+            for c in range(0u, width) {
+                self.pixels[dst + c] = pixels[src + c];
+            }
+        }
+
+        let res = Vec2::new(self.cursor.x as f32, self.cursor.y as f32);
+
+        self.cursor.x   = self.cursor.x + width + 1;
+        self.row_height = cmp::max(self.row_height, rows);
+        self.dirty      = true;
+
+        res
+    }
+}
+
 /// A ttf font.
+///
+/// Glyphs are rasterized the first time they are needed, into a texture atlas that grows on
+/// demand. Characters missing from the font are looked up in its fallback fonts, in the order
+/// they were added.
+///
+/// Fonts created with `new_sdf` or `from_memory_sdf` store signed distance fields instead of
+/// coverage bitmaps. They stay sharp when drawn larger or smaller than the size they were loaded
+/// with, and support outlines.
 pub struct Font {
-    library:          ffi::FT_Library,
-    face:             ffi::FT_Face,
-    texture_atlas:    GLuint,
-    atlas_dimensions: Vec2<uint>,
-    glyphs:           Vec<Option<Glyph>>,
-    height:           i32,
-    nocpy:            NoCopy
+    library:       ffi::FT_Library,
+    faces:         RefCell<Vec<ffi::FT_Face>>,
+    memory:        RefCell<Vec<Vec<u8>>>, // Fonts loaded from memory must outlive their face.
+    size:          i32,
+    height:        i32,
+    spread:        uint,
+    texture_atlas: GLuint,
+    atlas:         RefCell<GlyphAtlas>,
+    nocpy:         NoCopy
 }
 
 impl Font {
@@ -40,150 +130,400 @@ impl Font {
         Font::do_new(Some(path), &[], size)
     }
 
+    /// Loads a new ttf font from the memory, rendered with signed distance fields.
+    ///
+    /// `size` is the size the distance fields are computed at. 32 to 64 pixels give a good
+    /// quality at every size.
+    pub fn from_memory_sdf(font: &[u8], size: i32) -> Rc<Font> {
+        Font::load(None, font, size, true)
+    }
+
+    /// Loads a new ttf font from a file, rendered with signed distance fields.
+    ///
+    /// `size` is the size the distance fields are computed at. 32 to 64 pixels give a good
+    /// quality at every size.
+    pub fn new_sdf(path: &Path, size: i32) -> Rc<Font> {
+        Font::load(Some(path), &[], size, true)
+    }
+
     /// Loads a new ttf font from a file.
     pub fn do_new(path: Option<&Path>, memory: &[u8], size: i32) -> Rc<Font> {
+        Font::load(path, memory, size, false)
+    }
+
+    fn load(path: Option<&Path>, memory: &[u8], size: i32, sdf: bool) -> Rc<Font> {
+        let spread = if sdf { cmp::max((size as f32 * SDF_SPREAD_RATIO) as uint, 2) } else { 0 };
+
         let mut font = Font {
-            library:          ptr::null_mut(),
-            face:             ptr::null_mut(),
-            texture_atlas:    0,
-            atlas_dimensions: na::zero(),
-            glyphs:           range(0, 128).map(|_:int| None).collect(),
-            height:           0,
-            nocpy:            NoCopy
+            library:       ptr::null_mut(),
+            faces:         RefCell::new(Vec::new()),
+            memory:        RefCell::new(Vec::new()),
+            size:          size,
+            height:        0,
+            spread:        spread,
+            texture_atlas: 0,
+            atlas:         RefCell::new(GlyphAtlas::new()),
+            nocpy:         NoCopy
         };
 
         unsafe {
             let _ = ffi::FT_Init_FreeType(&mut font.library);
+        }
+
+        let face = match font.load_face(path, memory) {
+            Ok(face) => face,
+            Err(e)   => panic!("Failed to create TTF face: {}", e)
+        };
+
+        unsafe {
+            font.height = ((*(*face).size).metrics.height >> 6) as i32;
+        }
+
+        font.faces.borrow_mut().push(face);
+
+        unsafe { verify!(gl::GenTextures(1, &mut font.texture_atlas)); }
+        verify!(gl::BindTexture(gl::TEXTURE_2D, font.texture_atlas));
+
+        /* Clamp to the edge to avoid artifacts when scaling. */
+        verify!(gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as i32));
+        verify!(gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as i32));
+
+        /* Linear filtering usually looks best for text. */
+        verify!(gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32));
+        verify!(gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32));
 
+        assert!(font.height > 0);
+
+        Rc::new(font)
+    }
+
+    /// Adds a font file to look for the characters missing from this font.
+    ///
+    /// Fails, leaving this font unchanged, if the file cannot be loaded.
+    pub fn add_fallback(&self, path: &Path) -> IoResult<()> {
+        let face = try!(self.load_face(Some(path), &[]));
+        self.faces.borrow_mut().push(face);
+
+        Ok(())
+    }
+
+    /// Adds a font loaded from the memory to look for the characters missing from this font.
+    ///
+    /// Fails, leaving this font unchanged, if `font` is not a valid font.
+    pub fn add_fallback_from_memory(&self, font: &[u8]) -> IoResult<()> {
+        let face = try!(self.load_face(None, font));
+        self.faces.borrow_mut().push(face);
+
+        Ok(())
+    }
+
+    fn load_face(&self, path: Option<&Path>, memory: &[u8]) -> IoResult<ffi::FT_Face> {
+        let mut face = ptr::null_mut();
+
+        unsafe {
             match path {
                 Some(path) => {
-                    let c_str = path.as_str().expect("Invalid path.").to_c_str();
-                    if ffi::FT_New_Face(font.library, c_str.as_ptr(), 0, &mut font.face) != 0 {
-                        panic!("Failed to create TTF face.");
+                    let c_str = match path.as_str() {
+                        Some(s) => s.to_c_str(),
+                        None    => return Err(IoError {
+                            kind:   InvalidInput,
+                            desc:   "the font path is not valid UTF-8",
+                            detail: Some(format!("{}", path.display()))
+                        })
+                    };
+
+                    let err = ffi::FT_New_Face(self.library, c_str.as_ptr(), 0, &mut face);
+
+                    if err != 0 {
+                        return Err(face_error(format!("{}: FreeType error {}", path.display(), err)))
                     }
                 },
                 None => {
-                    if ffi::FT_New_Memory_Face(font.library, &memory[0], memory.len() as i64, 0, &mut font.face) != 0 {
-                        panic!("Failed to create TTF face.");
+                    if memory.is_empty() {
+                        return Err(face_error("the font data is empty".to_string()))
                     }
-                }
-            }
 
-            let _ = ffi::FT_Set_Pixel_Sizes(font.face, 0, size as c_uint);
-            verify!(gl::ActiveTexture(gl::TEXTURE0));
+                    let memory = memory.to_vec();
+                    let err    = ffi::FT_New_Memory_Face(self.library, &memory[0], memory.len() as i64, 0, &mut face);
 
-            let     ft_glyph   = (*font.face).glyph;
-            let     max_width  = 1024;
-            let mut row_width  = 0;
-            let mut row_height = 0;
-
-            for curr in range(0u, 128) {
-                if ffi::FT_Load_Char(font.face, curr as u64, ffi::FT_LOAD_RENDER) != 0 {
-                    continue;
-                }
+                    if err != 0 {
+                        return Err(face_error(format!("FreeType error {}", err)))
+                    }
 
-                /* If we've exhausted the width for this row, add another. */
-                if row_width + (*ft_glyph).bitmap.width + 1 >= max_width {
-                    font.atlas_dimensions.x = cmp::max(font.atlas_dimensions.x, row_width as uint);
-                    font.atlas_dimensions.y = font.atlas_dimensions.y + row_height;
-                    row_width = 0; row_height = 0;
+                    self.memory.borrow_mut().push(memory);
                 }
+            }
 
-                let advance    = Vec2::new(((*ft_glyph).advance.x >> 6) as f32, ((*ft_glyph).advance.y >> 6) as f32);
-                let dimensions = Vec2::new((*ft_glyph).bitmap.width as f32, (*ft_glyph).bitmap.rows as f32);
-                let offset     = Vec2::new((*ft_glyph).bitmap_left as f32, (*ft_glyph).bitmap_top as f32);
-                let buffer     = Vec::from_raw_buf(&*(*ft_glyph).bitmap.buffer, (dimensions.x * dimensions.y) as uint);
-                let glyph      = Glyph::new(na::zero(), advance, dimensions, offset, buffer);
-                    
+            let _ = ffi::FT_Set_Pixel_Sizes(face, 0, self.size as c_uint);
+        }
 
-                row_width   = row_width + (dimensions.x + 1.0) as i32;
-                row_height  = cmp::max(row_height, (*ft_glyph).bitmap.rows as uint);
-                font.height = cmp::max(font.height, row_height as i32);
+        Ok(face)
+    }
 
-                font.glyphs[curr] = Some(glyph);
-            }
+    /// The opengl id to the texture atlas of this font.
+    ///
+    /// The glyphs rasterized since the last call are uploaded to the texture first.
+    pub fn texture_atlas(&self) -> GLuint {
+        let mut atlas = self.atlas.borrow_mut();
 
-            font.atlas_dimensions.x = UnsignedInt::next_power_of_two(cmp::max(font.atlas_dimensions.x, row_width as uint));
-            font.atlas_dimensions.y = UnsignedInt::next_power_of_two(font.atlas_dimensions.y + row_height);
+        if atlas.dirty {
+            verify!(gl::BindTexture(gl::TEXTURE_2D, self.texture_atlas));
 
             /* We're using 1 byte alignment buffering. */
             verify!(gl::PixelStorei(gl::UNPACK_ALIGNMENT, 1));
 
-            verify!(gl::GenTextures(1, &mut font.texture_atlas));
-            verify!(gl::BindTexture(gl::TEXTURE_2D, font.texture_atlas));
-            verify!(gl::TexImage2D(gl::TEXTURE_2D, 0, gl::RGB as GLint,
-                                   font.atlas_dimensions.x as i32, font.atlas_dimensions.y as i32,
-                                   0, gl::RED, gl::UNSIGNED_BYTE, ptr::null()));
-
-            /* Clamp to the edge to avoid artifacts when scaling. */
-            verify!(gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as i32));
-            verify!(gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as i32));
-
-            /* Linear filtering usually looks best for text. */
-            verify!(gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32));
-            verify!(gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32));
-
-            /* Copy all glyphs into the texture atlas. */
-            let mut offset: Vec2<i32> = na::zero();
-            row_height = 0;
-            for curr in range(0u, 128) {
-                let glyph = match *&mut font.glyphs[curr] {
-                    Some(ref mut g) => g,
-                    None            => continue
-                };
-
-                if offset.x + (glyph.dimensions.x as i32) + 1 >= max_width {
-                    offset.y   = offset.y + row_height as i32;
-                    row_height = 0;
-                    offset.x   = 0;
+            unsafe {
+                verify!(gl::TexImage2D(gl::TEXTURE_2D, 0, gl::RGB as GLint,
+                                       atlas.dimensions.x as i32, atlas.dimensions.y as i32,
+                                       0, gl::RED, gl::UNSIGNED_BYTE,
+                                       &atlas.pixels[0] as *const u8 as *const c_void));
+            }
+
+            /* Reset the state. */
+            verify!(gl::PixelStorei(gl::UNPACK_ALIGNMENT, 4));
+
+            atlas.dirty = false;
+        }
+
+        self.texture_atlas
+    }
+
+    /// The dimensions of the texture atlas of this font.
+    ///
+    /// They change when the atlas grows to make room for new glyphs.
+    #[inline]
+    pub fn atlas_dimensions(&self) -> Vec2<uint> {
+        self.atlas.borrow().dimensions
+    }
+
+    /// The glyph of a character, rasterized on demand.
+    ///
+    /// The position of the glyph in the texture atlas is given in pixels. Returns `None` if
+    /// neither this font nor its fallbacks have a glyph for this character.
+    pub fn glyph(&self, c: char) -> Option<Glyph> {
+        let mut atlas = self.atlas.borrow_mut();
+
+        if !atlas.glyphs.contains_key(&c) {
+            let glyph = self.rasterize(c, &mut *atlas);
+            let _     = atlas.glyphs.insert(c, glyph);
+        }
+
+        match atlas.glyphs.get(&c) {
+            Some(&Some(ref g)) => Some(g.glyph.clone()),
+            _                  => None
+        }
+    }
+
+    /// The horizontal adjustment of the space between two consecutive characters, in pixels.
+    ///
+    /// This is zero if the font has no kerning information, or if the characters come from
+    /// different fallback fonts.
+    pub fn kerning(&self, left: char, right: char) -> f32 {
+        let _     = self.glyph(left);
+        let _     = self.glyph(right);
+        let atlas = self.atlas.borrow();
+
+        match (atlas.glyphs.get(&left), atlas.glyphs.get(&right)) {
+            (Some(&Some(ref l)), Some(&Some(ref r))) if l.face == r.face => {
+                let face        = self.faces.borrow()[l.face];
+                let mut kerning = ffi::FT_Vector { x: 0, y: 0 };
+
+                unsafe {
+                    if ffi::FT_Get_Kerning(face, l.index, r.index, FT_KERNING_DEFAULT, &mut kerning) != 0 {
+                        return 0.0
+                    }
+                }
+
+                kerning.x as f32 / 64.0
+            },
+            _ => 0.0
+        }
+    }
+
+    fn rasterize(&self, c: char, atlas: &mut GlyphAtlas) -> Option<CachedGlyph> {
+        for (i, face) in self.faces.borrow().iter().enumerate() {
+            unsafe {
+                let index = ffi::FT_Get_Char_Index(*face, c as u64);
+
+                if index == 0 || ffi::FT_Load_Glyph(*face, index, ffi::FT_LOAD_RENDER) != 0 {
+                    continue;
                 }
 
-                if !glyph.buffer.is_empty() {
-                    verify!(gl::TexSubImage2D(
-                                gl::TEXTURE_2D, 0, offset.x, offset.y,
-                                glyph.dimensions.x as i32, glyph.dimensions.y as i32,
-                                gl::RED, gl::UNSIGNED_BYTE, &glyph.buffer[0] as *const u8 as *const c_void));
+                let ft_glyph   = (**face).glyph;
+                let advance    = Vec2::new(((*ft_glyph).advance.x >> 6) as f32, ((*ft_glyph).advance.y >> 6) as f32);
+                let mut width  = (*ft_glyph).bitmap.width as uint;
+                let mut rows   = (*ft_glyph).bitmap.rows as uint;
+                let mut offset = Vec2::new((*ft_glyph).bitmap_left as f32, (*ft_glyph).bitmap_top as f32);
+                let mut pixels = bitmap_pixels(&(*ft_glyph).bitmap);
+
+                /* The distance field extends around the glyph. */
+                if self.spread != 0 && width != 0 && rows != 0 {
+                    pixels   = sdf::distance_field(pixels.as_slice(), width, rows, self.spread);
+                    width    = width + 2 * self.spread;
+                    rows     = rows + 2 * self.spread;
+                    offset.x = offset.x - self.spread as f32;
+                    offset.y = offset.y + self.spread as f32;
                 }
 
-                /* Calculate the position in the texture. */
-                glyph.tex.x = offset.x as f32 / (font.atlas_dimensions.x as f32);
-                glyph.tex.y = offset.y as f32 / (font.atlas_dimensions.y as f32);
+                let dimensions = Vec2::new(width as f32, rows as f32);
+                let tex        = atlas.insert(pixels.as_slice(), width, rows);
 
-                offset.x   = offset.x + glyph.dimensions.x as i32;
-                row_height = cmp::max(row_height, glyph.dimensions.y as uint);
+                return Some(CachedGlyph {
+                    glyph: Glyph::new(tex, advance, dimensions, offset, Vec::new()),
+                    face:  i,
+                    index: index
+                })
             }
         }
 
-        /* Reset the state. */
-        verify!(gl::PixelStorei(gl::UNPACK_ALIGNMENT, 4));
+        None
+    }
 
-        assert!(font.height > 0);
+    /// The width of a single line of text, in pixels, kerning included.
+    pub fn line_width(&self, line: &str) -> f32 {
+        let mut width = 0.0;
+        let mut prev  = None;
 
-        Rc::new(font)
+        for curr in line.chars() {
+            let glyph = match self.glyph(curr) {
+                Some(g) => g,
+                None    => continue
+            };
+
+            match prev {
+                Some(prev) => width = width + self.kerning(prev, curr),
+                None       => { }
+            }
+
+            prev  = Some(curr);
+            width = width + glyph.advance.x;
+        }
+
+        width
     }
 
-    /// The opengl id to the texture atlas of this font.
+    /// The width and the height, in pixels, of a text drawn with this font.
+    ///
+    /// The text is split into lines on line breaks only, and measured at the size the font was
+    /// loaded with. Use `TextLayout::measure` to take wrapping, line spacing and scaling into
+    /// account.
+    pub fn measure(&self, text: &str) -> Vec2<f32> {
+        let mut width  = 0.0f32;
+        let mut nlines = 0u;
+
+        for line in text.lines_any() {
+            width  = width.max(self.line_width(line));
+            nlines = nlines + 1;
+        }
+
+        Vec2::new(width, (self.height as uint * nlines) as f32)
+    }
+
+    /// Splits a text into lines, with their widths in pixels.
+    ///
+    /// Lines are split on line breaks, and at spaces to keep them narrower than `max_width`.
+    pub fn wrap<'a>(&self, text: &'a str, max_width: Option<f32>) -> Vec<(&'a str, f32)> {
+        wrap_lines(text, max_width, &mut |line| self.line_width(line))
+    }
+
+    /// The height of this font.
     #[inline]
-    pub fn texture_atlas(&self) -> GLuint {
-        self.texture_atlas
+    pub fn height(&self) -> i32 {
+        self.height
     }
 
-    /// The dimensions of the texture atlas of this font.
+    /// The size, in pixels, this font was loaded with.
     #[inline]
-    pub fn atlas_dimensions(&self) -> Vec2<uint> {
-        self.atlas_dimensions
+    pub fn size(&self) -> i32 {
+        self.size
     }
 
-    /// The glyphs of the this font.
+    /// Whether the texture atlas of this font contains signed distance fields.
     #[inline]
-    pub fn glyphs<'a>(&'a self) -> &'a [Option<Glyph>] {
-        self.glyphs.as_slice()
+    pub fn is_sdf(&self) -> bool {
+        self.spread != 0
     }
 
-    /// The height of this font.
+    /// The extent of the distance fields around the glyphs, in pixels of the texture atlas.
+    ///
+    /// This is zero if the font is not rendered with signed distance fields.
     #[inline]
-    pub fn height(&self) -> i32 {
-        self.height
+    pub fn spread(&self) -> uint {
+        self.spread
+    }
+}
+
+// Splits a text into lines on line breaks, and at spaces to keep them narrower than
+// `max_width`. A word wider than `max_width` is left alone on its own line.
+fn wrap_lines<'a>(text:       &'a str,
+                  max_width:  Option<f32>,
+                  line_width: &mut |&str| -> f32)
+                  -> Vec<(&'a str, f32)> {
+    let mut res = Vec::new();
+
+    for line in text.lines_any() {
+        let max_width = match max_width {
+            Some(w) => w,
+            None    => {
+                res.push((line, (*line_width)(line)));
+                continue
+            }
+        };
+
+        // Greedily add words to the current line.
+        let mut begin  = 0u;
+        let mut end    = 0u;
+        let mut width  = 0.0;
+        let mut offset = 0u;
+
+        for word in line.split(' ') {
+            let word_end  = offset + word.len();
+            let candidate = (*line_width)(line.slice(begin, word_end));
+
+            if candidate <= max_width || offset == begin {
+                end   = word_end;
+                width = candidate;
+            }
+            else {
+                res.push((line.slice(begin, end), width));
+
+                begin = offset;
+                end   = word_end;
+                width = (*line_width)(word);
+            }
+
+            offset = word_end + 1;
+        }
+
+        res.push((line.slice(begin, end), width));
+    }
+
+    res
+}
+
+// Copies the rows of a FreeType bitmap contiguously.
+unsafe fn bitmap_pixels(bitmap: &ffi::FT_Bitmap) -> Vec<u8> {
+    let width   = bitmap.width as uint;
+    let rows    = bitmap.rows as uint;
+    let mut res = Vec::with_capacity(width * rows);
+
+    for r in range(0u, rows) {
+        let src = bitmap.buffer.offset(r as int * bitmap.pitch as int);
+
+        for c in range(0u, width) {
+            res.push(*src.offset(c as int));
+        }
+    }
+
+    res
+}
+
+// The error returned when FreeType cannot create a face.
+fn face_error(reason: String) -> IoError {
+    IoError {
+        kind:   OtherIoError,
+        desc:   "failed to create the TTF face",
+        detail: Some(reason)
     }
 }
 
@@ -195,3 +535,38 @@ impl Drop for Font {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::wrap_lines;
+
+    // Every character is 10 pixels wide.
+    fn wrap<'a>(text: &'a str, max_width: Option<f32>) -> Vec<(&'a str, f32)> {
+        wrap_lines(text, max_width, &mut |line| (line.char_len() * 10) as f32)
+    }
+
+    #[test]
+    fn no_max_width() {
+        assert!(wrap("hello world", None) == vec!(("hello world", 110.0)));
+    }
+
+    #[test]
+    fn breaks_at_word_boundaries() {
+        assert!(wrap("aaa bbb ccc", Some(75.0)) == vec!(("aaa bbb", 70.0), ("ccc", 30.0)));
+        assert!(wrap("aaa bbb ccc", Some(65.0)) == vec!(("aaa", 30.0), ("bbb", 30.0), ("ccc", 30.0)));
+        assert!(wrap("aaa bbb ccc", Some(110.0)) == vec!(("aaa bbb ccc", 110.0)));
+    }
+
+    #[test]
+    fn long_words_stay_on_their_own_line() {
+        assert!(wrap("a abcdefgh b", Some(35.0)) == vec!(("a", 10.0), ("abcdefgh", 80.0), ("b", 10.0)));
+        assert!(wrap("abcdefgh", Some(35.0)) == vec!(("abcdefgh", 80.0)));
+    }
+
+    #[test]
+    fn explicit_line_breaks() {
+        assert!(wrap("aaa\nbbb ccc", None) == vec!(("aaa", 30.0), ("bbb ccc", 70.0)));
+        assert!(wrap("aaa bbb\r\nccc", Some(35.0)) == vec!(("aaa", 30.0), ("bbb", 30.0), ("ccc", 30.0)));
+        assert!(wrap("aaa\n\nbbb", Some(35.0)) == vec!(("aaa", 30.0), ("", 0.0), ("bbb", 30.0)));
+    }
+}
@@ -4,8 +4,8 @@
 
 use na::Vec2;
 
-#[packed]
 /// A ttf glyph.
+#[deriving(Clone)]
 pub struct Glyph {
     #[doc(hidden)]
     pub tex:        Vec2<f32>,
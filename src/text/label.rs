@@ -0,0 +1,42 @@
+use std::rc::Rc;
+use na::{Pnt3, Vec2};
+use text::{Font, TextLayout};
+
+/// A piece of text displayed next to a point of the scene.
+///
+/// The label is drawn in screen space at the projection of its anchor so it always faces the
+/// camera and keeps the same size. It is hidden when its anchor is behind the camera.
+#[deriving(Clone)]
+pub struct Label {
+    /// The text of the label. It may contain several lines.
+    pub text:       String,
+    /// The font of the text.
+    pub font:       Rc<Font>,
+    /// The color of the text.
+    pub color:      Pnt3<f32>,
+    /// The position of the text relative to the projected anchor, in pixels. The `y` axis points
+    /// down.
+    pub offset:     Vec2<f32>,
+    /// The alignment, wrapping and background of the text. The alignment is relative to the
+    /// projected anchor.
+    pub layout:     TextLayout,
+    /// Whether the label is hidden by the objects in front of its anchor.
+    ///
+    /// Such labels are drawn together with the 3D scene: the 2D scene is drawn over them and
+    /// post-processing effects apply to them. Other labels are drawn last, on top of everything.
+    pub depth_test: bool
+}
+
+impl Label {
+    /// Creates a left-aligned label with no offset and no depth test.
+    pub fn new(text: &str, font: &Rc<Font>, color: &Pnt3<f32>) -> Label {
+        Label {
+            text:       text.to_string(),
+            font:       font.clone(),
+            color:      *color,
+            offset:     Vec2::new(0.0, 0.0),
+            layout:     TextLayout::new(),
+            depth_test: false
+        }
+    }
+}
@@ -0,0 +1,92 @@
+use std::num::Float;
+use na::{Pnt3, Vec2};
+use text::Font;
+
+/// The horizontal alignment of the lines of a text.
+#[deriving(Clone, Copy, PartialEq, Show)]
+pub enum TextAlign {
+    /// The lines start at the text position.
+    Left,
+    /// The lines are centered on the text position.
+    Center,
+    /// The lines end at the text position.
+    Right
+}
+
+/// Options controlling how a text is laid out on the screen.
+#[deriving(Clone, Copy, PartialEq, Show)]
+pub struct TextLayout {
+    /// The alignment of each line relative to the text position.
+    pub align:            TextAlign,
+    /// The maximum width of the lines, in pixels. Longer lines are wrapped at spaces. A single
+    /// word wider than this is not broken.
+    pub max_width:        Option<f32>,
+    /// The distance between the baselines of consecutive lines, relative to the font height.
+    pub line_spacing:     f32,
+    /// The color of a rectangle filled behind the text, if any.
+    pub background:       Option<Pnt3<f32>>,
+    /// The opacity of the background rectangle.
+    pub background_alpha: f32,
+    /// The margin between the text and the borders of the background rectangle, in pixels.
+    pub padding:          f32,
+    /// The height of the text, in pixels. If `None`, the text is drawn at the size its font was
+    /// loaded with. Only fonts rendered with signed distance fields stay sharp when scaled.
+    pub size:             Option<f32>,
+    /// The color of an outline drawn around the glyphs, if any. Only fonts rendered with signed
+    /// distance fields support outlines.
+    pub outline:          Option<Pnt3<f32>>,
+    /// The width of the outline, in pixels. It is limited by the spread of the distance fields.
+    pub outline_width:    f32,
+    /// The color of a shadow drawn behind the text, if any.
+    pub shadow:           Option<Pnt3<f32>>,
+    /// The opacity of the shadow.
+    pub shadow_alpha:     f32,
+    /// The position of the shadow relative to the text, in pixels. The `y` axis points down.
+    pub shadow_offset:    Vec2<f32>
+}
+
+impl TextLayout {
+    /// The default layout: left-aligned lines separated by the font height, drawn at the font
+    /// size, without wrapping, background nor effects.
+    pub fn new() -> TextLayout {
+        TextLayout {
+            align:            TextAlign::Left,
+            max_width:        None,
+            line_spacing:     1.0,
+            background:       None,
+            background_alpha: 0.5,
+            padding:          4.0,
+            size:             None,
+            outline:          None,
+            outline_width:    1.0,
+            shadow:           None,
+            shadow_alpha:     0.5,
+            shadow_offset:    Vec2::new(2.0, 2.0)
+        }
+    }
+
+    /// The width and the height, in pixels, of a text laid out with these options.
+    ///
+    /// The background padding is not included.
+    pub fn measure(&self, text: &str, font: &Font) -> Vec2<f32> {
+        let scale = self.scale(font);
+        let lines = font.wrap(text, self.max_width.map(|w| w / scale));
+
+        if lines.is_empty() {
+            return Vec2::new(0.0, 0.0)
+        }
+
+        let width  = lines.iter().fold(0.0f32, |w, &(_, lw)| w.max(lw)) * scale;
+        let height = font.height() as f32 * scale;
+
+        Vec2::new(width, height + height * self.line_spacing * (lines.len() - 1) as f32)
+    }
+
+    /// The factor the glyphs of `font` are scaled by to be drawn at the requested size.
+    pub fn scale(&self, font: &Font) -> f32 {
+        match self.size {
+            Some(size) => size / font.size() as f32,
+            None       => 1.0
+        }
+    }
+}
@@ -2,8 +2,13 @@
 
 pub use text::glyph::Glyph;
 pub use text::font::Font;
+pub use text::label::Label;
+pub use text::layout::{TextAlign, TextLayout};
 pub use text::renderer::TextRenderer;
 
 mod glyph;
 mod font;
+mod label;
+mod layout;
 mod renderer;
+mod sdf;
@@ -3,10 +3,13 @@
 // It has been modified to work with gl-rs, nalgebra, and rust-freetype
 
 use std::rc::Rc;
+use std::mem;
+use std::num::Float;
 use gl;
 use gl::types::*;
 use na::{Vec2, Pnt2, Pnt3};
-use text::Font;
+use na;
+use text::{Font, Label, TextAlign, TextLayout};
 use camera::Camera;
 use resource::{BufferType, AllocationType, Shader, ShaderUniform, ShaderAttribute, GPUVector};
 
@@ -14,19 +17,33 @@ use resource::{BufferType, AllocationType, Shader, ShaderUniform, ShaderAttribut
 mod error;
 
 struct TextRenderContext {
-    color: Pnt3<f32>,
-    font:  Rc<Font>,
-    begin: uint,
-    size:  uint
+    color:         Pnt3<f32>,
+    alpha:         f32,
+    solid:         bool,
+    outline:       Pnt3<f32>,
+    outline_width: f32,
+    scale:         f32,
+    font:          Rc<Font>,
+    begin:         uint,
+    size:          uint,
+    depth:         f32,
+    depth_test:    bool
 }
 
 impl TextRenderContext {
-    pub fn new(color: Pnt3<f32>, font: Rc<Font>, begin: uint, size: uint) -> TextRenderContext {
+    pub fn new(color: Pnt3<f32>, font: Rc<Font>, begin: uint, size: uint, depth: f32, depth_test: bool) -> TextRenderContext {
         TextRenderContext {
-            color:   color,
-            font:    font,
-            begin:   begin,
-            size:    size
+            color:         color,
+            alpha:         1.0,
+            solid:         false,
+            outline:       color,
+            outline_width: 0.0,
+            scale:         1.0,
+            font:          font,
+            begin:         begin,
+            size:          size,
+            depth:         depth,
+            depth_test:    depth_test
         }
     }
 }
@@ -35,12 +52,20 @@ impl TextRenderContext {
 pub struct TextRenderer {
     shader:   Shader,
     invsz:    ShaderUniform<Vec2<f32>>,
+    invatlas: ShaderUniform<Vec2<f32>>,
     tex:      ShaderUniform<GLint>,
     color:    ShaderUniform<Pnt3<f32>>,
+    alpha:    ShaderUniform<GLfloat>,
+    solid:    ShaderUniform<GLint>,
+    sdf:      ShaderUniform<GLfloat>,
+    outline:  ShaderUniform<Pnt3<f32>>,
+    owidth:   ShaderUniform<GLfloat>,
+    depth:    ShaderUniform<GLfloat>,
     pos:      ShaderAttribute<Pnt2<f32>>,
     uvs:      ShaderAttribute<Pnt2<f32>>,
     contexts: Vec<TextRenderContext>,
     coords:   GPUVector<Pnt2<f32>>,
+    labels:   Vec<(Pnt3<f32>, Label)>
 }
 
 impl TextRenderer {
@@ -52,13 +77,21 @@ impl TextRenderer {
 
         TextRenderer {
             invsz:    shader.get_uniform("invsz").expect("Could not find invsz"),
+            invatlas: shader.get_uniform("invatlas").expect("Could not find invatlas"),
             tex:      shader.get_uniform("tex0").expect("Could not find tex0"),
             color:    shader.get_uniform("color").expect("Could not find color"),
+            alpha:    shader.get_uniform("alpha").expect("Could not find alpha"),
+            solid:    shader.get_uniform("solid").expect("Could not find solid"),
+            sdf:      shader.get_uniform("sdf").expect("Could not find sdf"),
+            outline:  shader.get_uniform("outline").expect("Could not find outline"),
+            owidth:   shader.get_uniform("outline_width").expect("Could not find outline_width"),
+            depth:    shader.get_uniform("depth").expect("Could not find depth"),
             pos:      shader.get_attrib("pos").expect("Could not find pos"),
             uvs:      shader.get_attrib("uvs").expect("Could not find uvs"),
             shader:   shader,
             contexts: Vec::new(),
-            coords:   GPUVector::new(Vec::new(), BufferType::Array, AllocationType::StreamDraw)
+            coords:   GPUVector::new(Vec::new(), BufferType::Array, AllocationType::StreamDraw),
+            labels:   Vec::new()
         }
     }
 
@@ -66,62 +99,245 @@ impl TextRenderer {
     /// frames. This method must be called for each text to draw, and at each update loop
     /// iteration.
     pub fn draw_text(&mut self, text: &str, pos: &Pnt2<f32>, font: &Rc<Font>, color: &Pnt3<f32>) {
+        self.push_text(text, pos, font, color, &TextLayout::new(), -1.0, false)
+    }
+
+    /// Adds a piece of text to be drawn during the next frame, with the alignment, wrapping and
+    /// background given by `layout`. The text is not persistent between frames.
+    pub fn draw_text_with_layout(&mut self,
+                                 text:   &str,
+                                 pos:    &Pnt2<f32>,
+                                 font:   &Rc<Font>,
+                                 color:  &Pnt3<f32>,
+                                 layout: &TextLayout) {
+        self.push_text(text, pos, font, color, layout, -1.0, false)
+    }
+
+    /// Adds a label anchored to a point of the scene, to be drawn during the next frame. The
+    /// label is not persistent between frames.
+    ///
+    /// The label is actually positioned by `project_labels`.
+    pub fn draw_label(&mut self, anchor: &Pnt3<f32>, label: &Label) {
+        self.labels.push((*anchor, label.clone()))
+    }
+
+    /// Projects the labels added since the last frame on the screen with `camera`.
+    ///
+    /// Labels with an anchor behind the camera or outside of its clipping planes are dropped.
+    /// This is called by the window with the camera used to render the scene.
+    pub fn project_labels(&mut self, camera: &Camera, width: f32, height: f32) {
+        let mut labels = mem::replace(&mut self.labels, Vec::new());
+        let transform  = camera.transformation();
+
+        for &(ref anchor, ref label) in labels.iter() {
+            let h = transform * na::to_homogeneous(anchor);
+
+            if h.w <= 0.0 {
+                continue
+            }
+
+            let depth = h.z / h.w;
+
+            if depth < -1.0 || depth > 1.0 {
+                continue
+            }
+
+            let pos = Pnt2::new((1.0 + h.x / h.w) * width  / 2.0 + label.offset.x,
+                                (1.0 - h.y / h.w) * height / 2.0 + label.offset.y);
+
+            self.push_text(label.text.as_slice(), &pos, &label.font, &label.color, &label.layout, depth, label.depth_test)
+        }
+
+        // Keep the allocation for the next frame.
+        labels.clear();
+        self.labels = labels;
+    }
+
+    fn push_text(&mut self,
+                 text:       &str,
+                 pos:        &Pnt2<f32>,
+                 font:       &Rc<Font>,
+                 color:      &Pnt3<f32>,
+                 layout:     &TextLayout,
+                 depth:      f32,
+                 depth_test: bool) {
+        let scale = layout.scale(&**font);
+        let lines = font.wrap(text, layout.max_width.map(|w| w / scale));
+
+        if lines.is_empty() {
+            return
+        }
+
+        match layout.background {
+            Some(ref bg) => {
+                let size  = layout.measure(text, &**font);
+                let left  = aligned_x(layout.align, pos.x, size.x) - layout.padding;
+                let top   = pos.y - layout.padding;
+                let right = left + size.x + 2.0 * layout.padding;
+                let bot   = pos.y + size.y + layout.padding;
+
+                self.push_rectangle(left, top, right, bot, bg, layout.background_alpha, font, depth, depth_test)
+            },
+            None => { }
+        }
+
+        let (outline, outline_width) = match layout.outline {
+            Some(outline) if font.is_sdf() => (outline, layout.outline_width),
+            _                              => (*color, 0.0)
+        };
+
+        match layout.shadow {
+            Some(shadow) => {
+                let shadow_pos = *pos + layout.shadow_offset;
+
+                match self.push_glyphs(lines.as_slice(), &shadow_pos, &**font, layout, scale) {
+                    Some((begin, size)) => {
+                        let mut ctxt = TextRenderContext::new(shadow, font.clone(), begin, size, depth, depth_test);
+
+                        // The shadow has the shape of the outlined text.
+                        ctxt.alpha         = layout.shadow_alpha;
+                        ctxt.outline       = shadow;
+                        ctxt.outline_width = outline_width;
+                        ctxt.scale         = scale;
+
+                        self.contexts.push(ctxt)
+                    },
+                    None => { }
+                }
+            },
+            None => { }
+        }
+
+        match self.push_glyphs(lines.as_slice(), pos, &**font, layout, scale) {
+            Some((begin, size)) => {
+                let mut ctxt = TextRenderContext::new(*color, font.clone(), begin, size, depth, depth_test);
+
+                ctxt.outline       = outline;
+                ctxt.outline_width = outline_width;
+                ctxt.scale         = scale;
+
+                self.contexts.push(ctxt)
+            },
+            None => { }
+        }
+    }
+
+    // Adds the quads of the glyphs of `lines`, and returns the range of the added coordinates.
+    fn push_glyphs(&mut self,
+                   lines:  &[(&str, f32)],
+                   pos:    &Pnt2<f32>,
+                   font:   &Font,
+                   layout: &TextLayout,
+                   scale:  f32)
+                   -> Option<(uint, uint)> {
+        let height  = font.height() as f32 * scale;
+        let spacing = height * layout.line_spacing;
+        let mut res = None;
+
         for coords in self.coords.data_mut().iter_mut() {
             let begin = coords.len();
 
-            for (line_count, line) in text.lines_any().enumerate() {
-                let mut temp_pos = pos.clone();
-                temp_pos.y       = temp_pos.y + (font.height() as uint * (line_count + 1)) as f32;
+            for (line_count, &(line, width)) in lines.iter().enumerate() {
+                let mut temp_pos = Pnt2::new(aligned_x(layout.align, pos.x, width * scale), pos.y);
+                let mut prev     = None;
+                temp_pos.y       = temp_pos.y + height + spacing * line_count as f32;
 
                 for curr in line.chars() {
-                    // XXX: do _not_ use a hashmap!
-                    let glyph = match font.glyphs()[curr as uint] {
-                        Some(ref g) => g,
-                        None        => continue,
+                    let glyph = match font.glyph(curr) {
+                        Some(g) => g,
+                        None    => continue,
                     };
 
-                    let end_x = temp_pos.x + glyph.offset.x;
-                    let end_y = -temp_pos.y - (glyph.dimensions.y - glyph.offset.y);
-                    let end_w = glyph.dimensions.x;
-                    let end_h = glyph.dimensions.y;
+                    match prev {
+                        Some(prev) => temp_pos.x = temp_pos.x + font.kerning(prev, curr) * scale,
+                        None       => { }
+                    }
+
+                    prev = Some(curr);
 
-                    temp_pos.x = temp_pos.x + glyph.advance.x; 
-                    temp_pos.y = temp_pos.y + glyph.advance.y; 
+                    let end_x = temp_pos.x + glyph.offset.x * scale;
+                    let end_y = -temp_pos.y - (glyph.dimensions.y - glyph.offset.y) * scale;
+                    let end_w = glyph.dimensions.x * scale;
+                    let end_h = glyph.dimensions.y * scale;
+                    let tex_w = glyph.dimensions.x;
+                    let tex_h = glyph.dimensions.y;
+
+                    temp_pos.x = temp_pos.x + glyph.advance.x * scale;
+                    temp_pos.y = temp_pos.y + glyph.advance.y * scale;
 
                     // Skip empty glyphs.
-                    if end_w <= 0.1 || end_h <= 0.1 {
+                    if tex_w <= 0.1 || tex_h <= 0.1 {
                         continue;
                     }
 
-                    let adimx = font.atlas_dimensions().x as f32;
-                    let adimy = font.atlas_dimensions().y as f32;
-
+                    // Texture coordinates are in pixels since the atlas may still grow.
                     coords.push(Pnt2::new(end_x, -end_y - end_h));
                     coords.push(Pnt2::new(glyph.tex.x, glyph.tex.y));
 
                     coords.push(Pnt2::new(end_x, -end_y));
-                    coords.push(Pnt2::new(glyph.tex.x, glyph.tex.y + (end_h / adimy)));
+                    coords.push(Pnt2::new(glyph.tex.x, glyph.tex.y + tex_h));
 
                     coords.push(Pnt2::new(end_x + end_w, -end_y));
-                    coords.push(Pnt2::new(glyph.tex.x + (end_w / adimx), glyph.tex.y + (end_h / adimy)));
+                    coords.push(Pnt2::new(glyph.tex.x + tex_w, glyph.tex.y + tex_h));
 
                     coords.push(Pnt2::new(end_x, -end_y - end_h));
                     coords.push(Pnt2::new(glyph.tex.x, glyph.tex.y));
 
                     coords.push(Pnt2::new(end_x + end_w, -end_y));
-                    coords.push(Pnt2::new(glyph.tex.x + (end_w / adimx), glyph.tex.y + (end_h / adimy)));
+                    coords.push(Pnt2::new(glyph.tex.x + tex_w, glyph.tex.y + tex_h));
 
                     coords.push(Pnt2::new(end_x + end_w, -end_y - end_h));
-                    coords.push(Pnt2::new(glyph.tex.x + (end_w / adimx), glyph.tex.y));
+                    coords.push(Pnt2::new(glyph.tex.x + tex_w, glyph.tex.y));
                 }
             }
 
             let size = coords.len() - begin;
 
             if size > 0 {
-                self.contexts.push(TextRenderContext::new(color.clone(), font.clone(), begin, size));
+                res = Some((begin, size));
             }
         }
+
+        res
+    }
+
+    // Adds a filled rectangle given by its corners, in pixels.
+    fn push_rectangle(&mut self,
+                      left:       f32,
+                      top:        f32,
+                      right:      f32,
+                      bottom:     f32,
+                      color:      &Pnt3<f32>,
+                      alpha:      f32,
+                      font:       &Rc<Font>,
+                      depth:      f32,
+                      depth_test: bool) {
+        for coords in self.coords.data_mut().iter_mut() {
+            let begin = coords.len();
+            let uv    = Pnt2::new(0.0, 0.0);
+
+            coords.push(Pnt2::new(left, top));
+            coords.push(uv);
+            coords.push(Pnt2::new(left, bottom));
+            coords.push(uv);
+            coords.push(Pnt2::new(right, bottom));
+            coords.push(uv);
+
+            coords.push(Pnt2::new(left, top));
+            coords.push(uv);
+            coords.push(Pnt2::new(right, bottom));
+            coords.push(uv);
+            coords.push(Pnt2::new(right, top));
+            coords.push(uv);
+
+            // The font is not sampled, but the context needs one.
+            let mut ctxt = TextRenderContext::new(*color, font.clone(), begin, 12, depth, depth_test);
+
+            ctxt.alpha = alpha;
+            ctxt.solid = true;
+
+            self.contexts.push(ctxt)
+        }
     }
 
     /// Actually draws the text.
@@ -133,7 +349,7 @@ impl TextRenderer {
         verify!(gl::PolygonMode(gl::FRONT_AND_BACK, gl::FILL));
         verify!(gl::Enable(gl::BLEND));
         verify!(gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA));
-        verify!(gl::Disable(gl::DEPTH_TEST));
+        verify!(gl::DepthMask(gl::FALSE));
 
         self.pos.enable();
         self.uvs.enable();
@@ -141,13 +357,30 @@ impl TextRenderer {
         self.invsz.upload(&Vec2::new(1.0 / width, -1.0 / height));
 
         for ctxt in self.contexts.iter() {
-            verify!(gl::BindTexture(gl::TEXTURE_2D, ctxt.font.texture_atlas()));
+            let atlas = ctxt.font.texture_atlas();
+            let adims = ctxt.font.atlas_dimensions();
+
+            verify!(gl::BindTexture(gl::TEXTURE_2D, atlas));
             verify!(gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as i32));
             verify!(gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as i32));
 
             self.pos.bind_sub_buffer(&mut self.coords, 1, ctxt.begin + 0);
             self.uvs.bind_sub_buffer(&mut self.coords, 1, ctxt.begin + 1);
             self.color.upload(&ctxt.color);
+            self.alpha.upload(&ctxt.alpha);
+            self.solid.upload(&(ctxt.solid as GLint));
+            self.sdf.upload(&(2.0 * ctxt.font.spread() as f32 * ctxt.scale));
+            self.outline.upload(&ctxt.outline);
+            self.owidth.upload(&ctxt.outline_width);
+            self.depth.upload(&ctxt.depth);
+
+            if ctxt.depth_test {
+                verify!(gl::Enable(gl::DEPTH_TEST));
+            }
+            else {
+                verify!(gl::Disable(gl::DEPTH_TEST));
+            }
+            self.invatlas.upload(&Vec2::new(1.0 / adims.x as f32, 1.0 / adims.y as f32));
 
             verify!(gl::DrawArrays(gl::TRIANGLES, 0, (ctxt.size / 2) as i32));
         }
@@ -155,6 +388,7 @@ impl TextRenderer {
         self.pos.disable();
         self.uvs.enable();
 
+        verify!(gl::DepthMask(gl::TRUE));
         verify!(gl::Enable(gl::DEPTH_TEST));
         verify!(gl::Disable(gl::BLEND));
 
@@ -166,6 +400,15 @@ impl TextRenderer {
     }
 }
 
+// The abscissa where a line of width `width` starts to be aligned with `x`.
+fn aligned_x(align: TextAlign, x: f32, width: f32) -> f32 {
+    match align {
+        TextAlign::Left   => x,
+        TextAlign::Center => x - width / 2.0,
+        TextAlign::Right  => x - width
+    }
+}
+
 /// Vertex shader used by the material to display line.
 pub static TEXT_VERTEX_SRC:   &'static str = A_VERY_LONG_STRING;
 /// Fragment shader used by the material to display line.
@@ -175,8 +418,10 @@ const A_VERY_LONG_STRING: &'static str =
 "
 #version 120
 
-uniform vec2 invsz;
-uniform vec3 color;
+uniform vec2  invsz;
+uniform vec2  invatlas;
+uniform vec3  color;
+uniform float depth;
 
 attribute vec2 pos; 
 attribute vec2 uvs; 
@@ -185,8 +430,8 @@ varying vec2 tex;
 varying vec3 Color; 
 
 void main() {
-    gl_Position = vec4(pos.x * invsz.x - 1.0, pos.y * invsz.y + 1.0, -1.0, 1.0);
-    tex         = uvs;
+    gl_Position = vec4(pos.x * invsz.x - 1.0, pos.y * invsz.y + 1.0, depth, 1.0);
+    tex         = uvs * invatlas;
     Color       = color;
 }
 ";
@@ -196,11 +441,31 @@ const ANOTHER_VERY_LONG_STRING: &'static str =
 #version 120
 
 uniform sampler2D tex0;
+uniform float     alpha;
+uniform int       solid;
+uniform float     sdf; // Screen pixels per unit of the distance field, zero for bitmap fonts.
+uniform vec3      outline;
+uniform float     outline_width;
 
 varying vec2 tex;
 varying vec3 Color; 
 
 void main() {
-    gl_FragColor = vec4(Color, texture2D(tex0, tex).r); 
+    float value = texture2D(tex0, tex).r;
+
+    if (solid != 0) {
+        gl_FragColor = vec4(Color, alpha);
+    }
+    else if (sdf > 0.0) {
+        // Signed distance to the outline of the glyph, in screen pixels.
+        float dist   = (value - 0.5) * sdf;
+        float fill   = clamp(dist + 0.5, 0.0, 1.0);
+        float border = clamp(dist + outline_width + 0.5, 0.0, 1.0);
+
+        gl_FragColor = vec4(mix(outline, Color, fill), border * alpha);
+    }
+    else {
+        gl_FragColor = vec4(Color, value * alpha);
+    }
 }
 ";
@@ -0,0 +1,170 @@
+// Signed distance fields of glyphs, computed with the exact Euclidean distance transform of
+// Felzenszwalb and Huttenlocher.
+
+use std::num::Float;
+use std::cmp;
+
+static INF: f32 = 1.0e20;
+
+/// Computes the signed distance field of a glyph bitmap.
+///
+/// The result is `2 * spread` pixels wider and taller than the bitmap so that the field extends
+/// around the glyph. Distances from `-spread` to `spread` pixels are mapped to `[0, 255]`, with
+/// 128 on the outline of the glyph and larger values inside.
+pub fn distance_field(coverage: &[u8], width: uint, rows: uint, spread: uint) -> Vec<u8> {
+    let w = width + 2 * spread;
+    let h = rows + 2 * spread;
+
+    // Squared distances to the nearest pixel inside, and to the nearest pixel outside.
+    let mut to_inside  = Vec::from_elem(w * h, INF);
+    let mut to_outside = Vec::from_elem(w * h, 0.0f32);
+
+    for r in range(0u, rows) {
+        for c in range(0u, width) {
+            if coverage[r * width + c] > 127 {
+                let i = (r + spread) * w + c + spread;
+
+                to_inside[i]  = 0.0;
+                to_outside[i] = INF;
+            }
+        }
+    }
+
+    transform(to_inside.as_mut_slice(), w, h);
+    transform(to_outside.as_mut_slice(), w, h);
+
+    let mut res = Vec::with_capacity(w * h);
+
+    for i in range(0u, w * h) {
+        // The outline lies half-way between the centers of an inside and an outside pixel.
+        let dist = if to_inside[i] == 0.0 {
+            to_outside[i].sqrt() - 0.5
+        }
+        else {
+            0.5 - to_inside[i].sqrt()
+        };
+
+        let value = 0.5 + dist / (2.0 * spread as f32);
+
+        res.push((value.max(0.0).min(1.0) * 255.0) as u8);
+    }
+
+    res
+}
+
+// Replaces each value of the grid by its squared distance to the nearest zero, column by column,
+// then row by row.
+fn transform(grid: &mut [f32], width: uint, height: uint) {
+    let n     = cmp::max(width, height);
+    let mut f = Vec::from_elem(n, 0.0f32);
+    let mut d = Vec::from_elem(n, 0.0f32);
+    let mut v = Vec::from_elem(n, 0u);
+    let mut z = Vec::from_elem(n + 1, 0.0f32);
+
+    for x in range(0u, width) {
+        for y in range(0u, height) {
+            f[y] = grid[y * width + x];
+        }
+
+        transform_1d(f.slice_to(height), d.slice_to_mut(height), v.as_mut_slice(), z.as_mut_slice());
+
+        for y in range(0u, height) {
+            grid[y * width + x] = d[y];
+        }
+    }
+
+    for y in range(0u, height) {
+        for x in range(0u, width) {
+            f[x] = grid[y * width + x];
+        }
+
+        transform_1d(f.slice_to(width), d.slice_to_mut(width), v.as_mut_slice(), z.as_mut_slice());
+
+        for x in range(0u, width) {
+            grid[y * width + x] = d[x];
+        }
+    }
+}
+
+// One-dimensional distance transform: computes the lower envelope of the parabolas rooted at
+// each sample. `v` and `z` are work buffers for the parabolas of the envelope and their bounds.
+fn transform_1d(f: &[f32], d: &mut [f32], v: &mut [uint], z: &mut [f32]) {
+    let n     = f.len();
+    let mut k = 0u;
+
+    v[0] = 0;
+    z[0] = -INF;
+    z[1] = INF;
+
+    for q in range(1u, n) {
+        let qf = q as f32;
+
+        loop {
+            let p = v[k] as f32;
+            let s = ((f[q] + qf * qf) - (f[v[k]] + p * p)) / (2.0 * qf - 2.0 * p);
+
+            if s <= z[k] {
+                // z[0] is -INF so this never goes below zero.
+                k = k - 1;
+            }
+            else {
+                k        = k + 1;
+                v[k]     = q;
+                z[k]     = s;
+                z[k + 1] = INF;
+                break;
+            }
+        }
+    }
+
+    k = 0;
+
+    for q in range(0u, n) {
+        let qf = q as f32;
+
+        while z[k + 1] < qf {
+            k = k + 1;
+        }
+
+        let p = v[k] as f32;
+
+        d[q] = (qf - p) * (qf - p) + f[v[k]];
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::distance_field;
+
+    #[test]
+    fn filled_square() {
+        // A 4x4 square in the middle of a 6x6 bitmap.
+        let mut coverage = Vec::from_elem(6 * 6, 0u8);
+
+        for r in range(1u, 5) {
+            for c in range(1u, 5) {
+                coverage[r * 6 + c] = 255;
+            }
+        }
+
+        let field = distance_field(coverage.as_slice(), 6, 6, 4);
+        let at    = |r: uint, c: uint| field[r * 14 + c];
+
+        assert!(field.len() == 14 * 14);
+
+        // The square covers the rows and columns 5 to 8 of the field.
+        assert!(at(6, 6) == 175); // 2 pixels from the outside.
+        assert!(at(5, 6) == 143); // On the inner edge.
+        assert!(at(4, 6) == 111); // On the outer edge.
+        assert!(at(2, 6) == 47);  // 3 pixels from the inside.
+        assert!(at(0, 0) == 0);   // Beyond the spread.
+
+        // The field is symmetric like the square.
+        for r in range(0u, 14) {
+            for c in range(0u, 14) {
+                assert!(at(r, c) == at(c, r));
+                assert!(at(r, c) == at(13 - r, c));
+            }
+        }
+    }
+}
@@ -0,0 +1,231 @@
+//! A renderer for thick, anti-aliased lines.
+
+use gl::types::*;
+use na::{Pnt3, Vec3, Iso3};
+use na;
+use resource::{BufferType, AllocationType, GPUVector};
+use builtin::ThickLinesShader;
+use camera::Camera;
+
+/// The shape of the free ends of lines.
+#[deriving(Clone, Copy, PartialEq, Show)]
+pub enum LineCap {
+    /// The line stops exactly at its end points.
+    Butt,
+    /// The line is extended by half its width with a square end.
+    Square,
+    /// The line is extended by half its width with a round end.
+    Round
+}
+
+/// The shape of the junctions between consecutive segments of a polyline.
+#[deriving(Clone, Copy, PartialEq, Show)]
+pub enum LineJoin {
+    /// The outer edges of both segments are extended until they meet.
+    Miter,
+    /// The junction is rounded.
+    Round
+}
+
+/// The appearance of thick lines.
+#[deriving(Clone, Copy, PartialEq, Show)]
+pub struct LineStyle {
+    /// The width of the lines, in pixels.
+    pub width: f32,
+    /// The shape of the free ends of the lines.
+    pub cap:   LineCap,
+    /// The shape of the junctions between consecutive segments.
+    pub join:  LineJoin,
+    /// The lengths of the dashes and of the gaps between them, measured along the lines in
+    /// model units. The lines are solid if this is `None`.
+    pub dash:  Option<(f32, f32)>
+}
+
+impl LineStyle {
+    /// Creates a solid line style with round caps and joins.
+    pub fn new(width: f32) -> LineStyle {
+        LineStyle {
+            width: width,
+            cap:   LineCap::Round,
+            join:  LineJoin::Round,
+            dash:  None
+        }
+    }
+}
+
+/// A set of thick line segments kept on the GPU.
+///
+/// Segments are expanded into camera-facing quads by the vertex shader, so their width does not
+/// depend on `glLineWidth`.
+pub struct ThickLines {
+    // Four vertices per segment, each made of six points: the point before the segment, its
+    // extremities, the point after it, the color, and the segment corner with its arc length.
+    vertices: GPUVector<Pnt3<GLfloat>>,
+    // Two triangles per segment, indexing its four vertices.
+    faces:    GPUVector<Vec3<GLuint>>
+}
+
+impl ThickLines {
+    /// Creates an empty set of lines.
+    pub fn new() -> ThickLines {
+        ThickLines {
+            vertices: GPUVector::new(Vec::new(), BufferType::Array, AllocationType::DynamicDraw),
+            faces:    GPUVector::new(Vec::new(), BufferType::ElementArray, AllocationType::DynamicDraw)
+        }
+    }
+
+    /// The number of segments.
+    #[inline]
+    pub fn len(&self) -> uint {
+        self.faces.len() / 2
+    }
+
+    /// Removes every segment.
+    pub fn clear(&mut self) {
+        for vertices in self.vertices.data_mut().iter_mut() {
+            vertices.clear()
+        }
+
+        for faces in self.faces.data_mut().iter_mut() {
+            faces.clear()
+        }
+    }
+
+    /// Uploads the segments to the GPU and frees their copy on RAM.
+    ///
+    /// Segments cannot be added or removed afterward. This saves memory for lines that do not
+    /// change.
+    pub fn unload_from_ram(&mut self) {
+        self.vertices.load_to_gpu();
+        self.vertices.unload_from_ram();
+        self.faces.load_to_gpu();
+        self.faces.unload_from_ram();
+    }
+
+    /// Adds a segment with caps at both ends.
+    pub fn push_segment(&mut self, a: Pnt3<GLfloat>, b: Pnt3<GLfloat>, color_a: Pnt3<GLfloat>, color_b: Pnt3<GLfloat>) {
+        self.push(a, a, b, b, color_a, color_b, 0.0)
+    }
+
+    /// Adds a polyline joining consecutive points, with caps at its extremities.
+    ///
+    /// # Failures:
+    /// Fails if `points` and `colors` do not have the same length.
+    pub fn push_polyline(&mut self, points: &[Pnt3<GLfloat>], colors: &[Pnt3<GLfloat>]) {
+        assert!(points.len() == colors.len(), "There must be exactly one color per point.");
+
+        let mut arc = 0.0;
+
+        for i in range(1u, points.len()) {
+            let prev = points[if i > 1 { i - 2 } else { i - 1 }];
+            let next = points[if i + 1 < points.len() { i + 1 } else { i }];
+
+            self.push(prev, points[i - 1], points[i], next, colors[i - 1], colors[i], arc);
+
+            arc = arc + na::dist(&points[i - 1], &points[i]);
+        }
+    }
+
+    fn push(&mut self,
+            prev:    Pnt3<GLfloat>,
+            a:       Pnt3<GLfloat>,
+            b:       Pnt3<GLfloat>,
+            next:    Pnt3<GLfloat>,
+            color_a: Pnt3<GLfloat>,
+            color_b: Pnt3<GLfloat>,
+            arc:     GLfloat) {
+        let corners = [ (0.0, -1.0), (1.0, -1.0), (1.0, 1.0), (0.0, 1.0) ];
+
+        for vertices in self.vertices.data_mut().iter_mut() {
+            let first = (vertices.len() / 6) as GLuint;
+
+            for faces in self.faces.data_mut().iter_mut() {
+                faces.push(Vec3::new(first, first + 1, first + 2));
+                faces.push(Vec3::new(first, first + 2, first + 3));
+            }
+
+            for &(end, side) in corners.iter() {
+                vertices.push(prev);
+                vertices.push(a);
+                vertices.push(b);
+                vertices.push(next);
+                vertices.push(if end == 0.0 { color_a } else { color_b });
+                vertices.push(Pnt3::new(end, side, arc));
+            }
+        }
+    }
+
+    /// Renders the segments with the given style.
+    pub fn render(&mut self,
+                  style:     &LineStyle,
+                  transform: &Iso3<f32>,
+                  scale:     &Vec3<f32>,
+                  pass:      uint,
+                  camera:    &mut Camera) {
+        if self.len() == 0 {
+            return
+        }
+
+        ThickLinesShader::get_global_shader(|s| s.render(style, pass, transform, scale, camera, &mut self.vertices, &mut self.faces))
+    }
+}
+
+/// Structure which manages the display of short-living thick lines.
+pub struct ThickLineRenderer {
+    batches: Vec<(LineStyle, ThickLines)>,
+    used:    uint
+}
+
+impl ThickLineRenderer {
+    /// Creates a new thick lines manager.
+    pub fn new() -> ThickLineRenderer {
+        ThickLineRenderer {
+            batches: Vec::new(),
+            used:    0
+        }
+    }
+
+    /// Indicates whether some lines have to be drawn.
+    pub fn needs_rendering(&self) -> bool {
+        self.used != 0
+    }
+
+    /// Adds a line to be drawn during the next frame. Lines are not persistent between frames.
+    pub fn draw_line(&mut self, a: Pnt3<GLfloat>, b: Pnt3<GLfloat>, color: Pnt3<GLfloat>, style: &LineStyle) {
+        self.batch(style).push_segment(a, b, color, color)
+    }
+
+    /// Adds a polyline to be drawn during the next frame. Lines are not persistent between frames.
+    pub fn draw_polyline(&mut self, points: &[Pnt3<GLfloat>], color: Pnt3<GLfloat>, style: &LineStyle) {
+        let colors = Vec::from_elem(points.len(), color);
+
+        self.batch(style).push_polyline(points, colors.as_slice())
+    }
+
+    // The lines of consecutive calls with the same style are drawn together. GPU buffers are
+    // reused from one frame to the next.
+    fn batch<'a>(&'a mut self, style: &LineStyle) -> &'a mut ThickLines {
+        if self.used == 0 || self.batches[self.used - 1].0 != *style {
+            if self.used == self.batches.len() {
+                self.batches.push((*style, ThickLines::new()));
+            }
+            else {
+                self.batches[self.used].0 = *style;
+            }
+
+            self.used = self.used + 1;
+        }
+
+        &mut self.batches[self.used - 1].1
+    }
+
+    /// Actually draws the lines.
+    pub fn render(&mut self, pass: uint, camera: &mut Camera) {
+        for &mut (ref style, ref mut lines) in self.batches.slice_to_mut(self.used).iter_mut() {
+            lines.render(style, &na::one(), &na::one(), pass, camera);
+            lines.clear();
+        }
+
+        self.used = 0;
+    }
+}
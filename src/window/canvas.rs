@@ -0,0 +1,89 @@
+//! The surface a window renders to.
+
+use glfw;
+use glfw::Context;
+use gl;
+#[cfg(feature = "headless")]
+use window::headless_context::HeadlessContext;
+
+#[path = "../error.rs"]
+mod error;
+
+/// Placeholder for the OSMesa context when the `headless` feature is disabled.
+#[cfg(not(feature = "headless"))]
+pub struct HeadlessContext;
+
+/// The surface a window renders to.
+pub enum Canvas {
+    /// A window of the display server.
+    Screen(glfw::Glfw, glfw::Window),
+    /// An image of fixed size rendered without any display server.
+    Offscreen(OffscreenCanvas)
+}
+
+/// An image of fixed size rendered without any display server.
+pub struct OffscreenCanvas {
+    /// The context must live as long as the canvas.
+    pub context:      HeadlessContext,
+    /// The width of the image.
+    pub width:        u32,
+    /// The height of the image.
+    pub height:       u32,
+    /// Whether the window has been closed.
+    pub should_close: bool
+}
+
+impl Canvas {
+    /// The glfw window, if this canvas is not off-screen.
+    pub fn glfw_window<'a>(&'a self) -> Option<&'a glfw::Window> {
+        match *self {
+            Canvas::Screen(_, ref window) => Some(window),
+            Canvas::Offscreen(_)          => None
+        }
+    }
+
+    /// The size of the canvas, in pixels.
+    pub fn size(&self) -> (u32, u32) {
+        match *self {
+            Canvas::Screen(_, ref window) => {
+                let (w, h) = window.get_size();
+
+                (w as u32, h as u32)
+            },
+            Canvas::Offscreen(ref o) => (o.width, o.height)
+        }
+    }
+
+    /// Indicates whether the window should be closed.
+    pub fn should_close(&self) -> bool {
+        match *self {
+            Canvas::Screen(_, ref window) => window.should_close(),
+            Canvas::Offscreen(ref o)      => o.should_close
+        }
+    }
+
+    /// Sets whether the window should be closed.
+    pub fn set_should_close(&mut self, close: bool) {
+        match *self {
+            Canvas::Screen(_, ref mut window) => window.set_should_close(close),
+            Canvas::Offscreen(ref mut o)      => o.should_close = close
+        }
+    }
+
+    /// Polls the events of the display server.
+    pub fn poll_events(&mut self) {
+        match *self {
+            Canvas::Screen(ref mut glfw, _) => glfw.poll_events(),
+            Canvas::Offscreen(_)            => { }
+        }
+    }
+
+    /// Makes the rendered image available, either by displaying it, or by waiting for the
+    /// rendering to complete.
+    pub fn swap_buffers(&mut self) {
+        match *self {
+            Canvas::Screen(_, ref mut window) => window.swap_buffers(),
+            Canvas::Offscreen(_)              => verify!(gl::Finish())
+        }
+    }
+}
@@ -0,0 +1,58 @@
+//! An OpenGL context without any window nor display server.
+
+use std::ptr;
+use libc;
+use gl;
+use window::osmesa;
+
+/// An OpenGL context created by the OSMesa software rasterizer.
+///
+/// This works without any display server nor GPU.
+pub struct HeadlessContext {
+    context: osmesa::OSMesaContext,
+    buffer:  Vec<u8> // NOTE: unused but must live as long as the context.
+}
+
+impl HeadlessContext {
+    /// Creates a context rendering to a color buffer of the given size, makes it current and
+    /// loads the OpenGL functions.
+    ///
+    /// Fails if the OSMesa context cannot be created.
+    pub fn new(width: uint, height: uint) -> HeadlessContext {
+        let mut buffer = Vec::from_elem(width * height * 4, 0u8);
+
+        let context = unsafe {
+            osmesa::OSMesaCreateContextExt(osmesa::OSMESA_RGBA, 24, 0, 0, ptr::null_mut())
+        };
+
+        if context.is_null() {
+            panic!("Unable to create an OSMesa context.")
+        }
+
+        let current = unsafe {
+            osmesa::OSMesaMakeCurrent(context,
+                                      buffer.as_mut_ptr() as *mut libc::c_void,
+                                      gl::UNSIGNED_BYTE,
+                                      width as libc::c_int,
+                                      height as libc::c_int)
+        };
+
+        if current == 0 {
+            unsafe { osmesa::OSMesaDestroyContext(context) };
+            panic!("Unable to make the OSMesa context current.")
+        }
+
+        gl::load_with(|name| name.with_c_str(|cname| unsafe { osmesa::OSMesaGetProcAddress(cname) }));
+
+        HeadlessContext {
+            context: context,
+            buffer:  buffer
+        }
+    }
+}
+
+impl Drop for HeadlessContext {
+    fn drop(&mut self) {
+        unsafe { osmesa::OSMesaDestroyContext(self.context) }
+    }
+}
@@ -2,8 +2,15 @@
 
 pub use window::window::Window;
 pub use window::event::{Event, Events, EventManager};
+pub use window::snapshot::Snapshot;
 // pub use window::render_frame::{RenderFrames, RenderFrame};
 
 mod window;
 mod event;
+mod snapshot;
+mod canvas;
+#[cfg(feature = "headless")]
+mod headless_context;
+#[cfg(feature = "headless")]
+mod osmesa;
 // mod render_frame;
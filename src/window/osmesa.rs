@@ -0,0 +1,29 @@
+//! Minimal bindings to the OSMesa off-screen rendering library.
+
+#![allow(non_snake_case)]
+
+use libc::{c_void, c_char, c_int, c_uint, c_uchar};
+
+/// An opaque OSMesa rendering context.
+pub type OSMesaContext = *mut c_void;
+
+/// Pixel format of the OSMesa color buffer (same value as `GL_RGBA`).
+pub const OSMESA_RGBA: c_uint = 0x1908;
+
+#[link(name = "OSMesa")]
+extern "C" {
+    pub fn OSMesaCreateContextExt(format:       c_uint,
+                                  depth_bits:   c_int,
+                                  stencil_bits: c_int,
+                                  accum_bits:   c_int,
+                                  sharelist:    OSMesaContext)
+                                  -> OSMesaContext;
+    pub fn OSMesaDestroyContext(ctx: OSMesaContext);
+    pub fn OSMesaMakeCurrent(ctx:    OSMesaContext,
+                             buffer: *mut c_void,
+                             kind:   c_uint,
+                             width:  c_int,
+                             height: c_int)
+                             -> c_uchar;
+    pub fn OSMesaGetProcAddress(name: *const c_char) -> *const c_void;
+}
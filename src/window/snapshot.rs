@@ -0,0 +1,178 @@
+//! Images read back from a framebuffer.
+
+use std::io::{File, IoResult, IoError, InvalidInput};
+use std::iter::repeat;
+use libc;
+use gl;
+use gl::types::*;
+#[cfg(feature = "screenshot")]
+use image;
+
+#[path = "../error.rs"]
+mod error;
+
+/// An RGB or RGBA image read back from a framebuffer.
+///
+/// Pixels are stored row by row, from the top-left corner to the bottom-right corner.
+pub struct Snapshot {
+    width:     u32,
+    height:    u32,
+    has_alpha: bool,
+    data:      Vec<u8>
+}
+
+impl Snapshot {
+    /// Reads a rectangle of RGB pixels from the framebuffer currently bound for reading.
+    ///
+    /// `x` and `y` are OpenGL coordinates of the bottom-left corner of the rectangle.
+    #[doc(hidden)]
+    pub fn read_from_framebuffer(x: i32, y: i32, width: u32, height: u32) -> Snapshot {
+        Snapshot::read(x, y, width, height, false)
+    }
+
+    /// Reads a rectangle of RGBA pixels from the framebuffer currently bound for reading.
+    ///
+    /// `x` and `y` are OpenGL coordinates of the bottom-left corner of the rectangle.
+    #[doc(hidden)]
+    pub fn read_rgba_from_framebuffer(x: i32, y: i32, width: u32, height: u32) -> Snapshot {
+        Snapshot::read(x, y, width, height, true)
+    }
+
+    fn read(x: i32, y: i32, width: u32, height: u32, has_alpha: bool) -> Snapshot {
+        let (format, ncomps) = if has_alpha { (gl::RGBA, 4) } else { (gl::RGB, 3) };
+        let row    = (width * ncomps) as uint;
+        let mut px: Vec<u8> = repeat(0).take(row * height as uint).collect();
+
+        if !px.is_empty() {
+            unsafe {
+                verify!(gl::PixelStorei(gl::PACK_ALIGNMENT, 1));
+                verify!(gl::ReadPixels(x, y,
+                                       width as GLint, height as GLint,
+                                       format,
+                                       gl::UNSIGNED_BYTE,
+                                       px.as_mut_ptr() as *mut libc::c_void));
+            }
+        }
+
+        // OpenGL rows go from bottom to top.
+        let mut data = Vec::with_capacity(px.len());
+
+        for r in range(0u, height as uint).rev() {
+            data.push_all(px.slice(r * row, (r + 1) * row));
+        }
+
+        Snapshot {
+            width:     width,
+            height:    height,
+            has_alpha: has_alpha,
+            data:      data
+        }
+    }
+
+    /// The width of this image.
+    #[inline]
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// The height of this image.
+    #[inline]
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Whether the pixels of this image have an alpha component.
+    #[inline]
+    pub fn has_alpha(&self) -> bool {
+        self.has_alpha
+    }
+
+    /// The RGB, or RGBA if `has_alpha` is `true`, components of the pixels of this image, with
+    /// rows ordered from top to bottom.
+    #[inline]
+    pub fn data<'a>(&'a self) -> &'a [u8] {
+        self.data.as_slice()
+    }
+
+    /// Moves the components of the pixels out of this image.
+    #[inline]
+    pub fn unwrap(self) -> Vec<u8> {
+        self.data
+    }
+
+    /// The RGB components of the pixel at the column `x` and the row `y` (from the top).
+    #[inline]
+    pub fn pixel(&self, x: u32, y: u32) -> (u8, u8, u8) {
+        let i = self.pixel_index(x, y);
+
+        (self.data[i], self.data[i + 1], self.data[i + 2])
+    }
+
+    /// The opacity of the pixel at the column `x` and the row `y` (from the top).
+    ///
+    /// This is 255 if this image has no alpha component.
+    #[inline]
+    pub fn alpha(&self, x: u32, y: u32) -> u8 {
+        let i = self.pixel_index(x, y);
+
+        if self.has_alpha { self.data[i + 3] } else { 255 }
+    }
+
+    fn pixel_index(&self, x: u32, y: u32) -> uint {
+        assert!(x < self.width && y < self.height, "Pixel coordinates out of bounds.");
+
+        let ncomps = if self.has_alpha { 4 } else { 3 };
+
+        ((y * self.width + x) * ncomps) as uint
+    }
+
+    /// Saves this image to a file.
+    ///
+    /// The file format is deduced from the path extension: either `png` or `ppm`. The alpha
+    /// component is not saved to `ppm` files. Saving `png` files requires the `screenshot`
+    /// feature.
+    pub fn save(&self, path: &Path) -> IoResult<()> {
+        match path.extension_str() {
+            Some("png") | Some("PNG") => self.save_png(path),
+            Some("ppm") | Some("PPM") => {
+                let mut file = try!(File::create(path));
+
+                try!(file.write_str(format!("P6\n{} {}\n255\n", self.width, self.height).as_slice()));
+
+                if self.has_alpha {
+                    let mut rgb = Vec::with_capacity((self.width * self.height * 3) as uint);
+
+                    for px in self.data.chunks(4) {
+                        rgb.push_all(px.slice_to(3));
+                    }
+
+                    file.write(rgb.as_slice())
+                }
+                else {
+                    file.write(self.data.as_slice())
+                }
+            },
+            _ => Err(IoError {
+                kind:   InvalidInput,
+                desc:   "unsupported image file extension",
+                detail: Some(format!("expected `png` or `ppm`, found: {}", path.display()))
+            })
+        }
+    }
+
+    #[cfg(feature = "screenshot")]
+    fn save_png(&self, path: &Path) -> IoResult<()> {
+        let color = if self.has_alpha { image::ColorType::RGBA(8) } else { image::ColorType::RGB(8) };
+
+        image::save_buffer(path, self.data.as_slice(), self.width, self.height, color)
+    }
+
+    #[cfg(not(feature = "screenshot"))]
+    fn save_png(&self, path: &Path) -> IoResult<()> {
+        Err(IoError {
+            kind:   InvalidInput,
+            desc:   "png support is disabled",
+            detail: Some(format!("enable the `screenshot` feature to save: {}", path.display()))
+        })
+    }
+}
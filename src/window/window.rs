@@ -11,22 +11,31 @@ use std::rc::Rc;
 use libc;
 use std::iter::repeat;
 use std::time::Duration;
+use std::io::IoResult;
 use time;
 use gl;
 use gl::types::*;
-use na::{Pnt2, Vec2, Vec3, Pnt3};
+use na::{Pnt2, Vec2, Vec3, Pnt3, Iso3};
 use na;
-use ncollide::procedural::TriMesh3;
+use ncollide::procedural::{TriMesh3, Polyline2};
 use camera::Camera;
-use scene::SceneNode;
+use scene::{SceneNode, PickResult};
 use line_renderer::LineRenderer;
-use point_renderer::PointRenderer;
+use point_renderer::{PointRenderer, PointSize, PointSprite};
+use thick_line_renderer::{ThickLineRenderer, LineStyle};
 use post_processing::PostProcessingEffect;
-use resource::{FramebufferManager, RenderTarget, Texture, TextureManager, Mesh, Material};
-use light::Light;
-use text::{TextRenderer, Font};
-use window::EventManager;
-use camera::ArcBall;
+use resource::{FramebufferManager, RenderTarget, Texture, TextureManager, MaterialManager, Mesh, Material};
+use light::{Light, LightSource};
+use loader::{LoadError, ParseError};
+use shadow_map::ShadowMap;
+use text::{TextRenderer, Font, Label, TextLayout};
+use window::{EventManager, Snapshot};
+use window::canvas::Canvas;
+#[cfg(feature = "headless")]
+use window::canvas::OffscreenCanvas;
+#[cfg(feature = "headless")]
+use window::headless_context::HeadlessContext;
+use camera::{ArcBall, Planar};
 
 
 static DEFAULT_WIDTH:  u32 = 800u32;
@@ -34,49 +43,72 @@ static DEFAULT_HEIGHT: u32 = 600u32;
 
 /// Structure representing a window and a 3D scene.
 ///
-/// This is the main interface with the 3d engine.
+/// This is the main interface with the 3d engine. A window either displays the scene on the
+/// screen, or renders it off-screen without any display server if it is created with
+/// `new_headless`.
 pub struct Window {
     events:                     Rc<Receiver<(f64, WindowEvent)>>,
     unhandled_events:           Rc<RefCell<Vec<WindowEvent>>>,
-    glfw:                       glfw::Glfw,
-    window:                     glfw::Window,
+    canvas:                     Canvas,
+    render_target:              RenderTarget,
     max_ms_per_frame:           Option<u64>,
     scene:                      SceneNode,
-    light_mode:                 Light, // FIXME: move that to the scene graph
+    scene_2d:                   SceneNode,
+    lights:                     Vec<LightSource>, // FIXME: move that to the scene graph
+    shadow_map:                 Option<ShadowMap>,
     background:                 Vec3<GLfloat>,
     line_renderer:              LineRenderer,
     point_renderer:             PointRenderer,
+    thick_line_renderer:        ThickLineRenderer,
     text_renderer:              TextRenderer,
+    label_renderer:             TextRenderer, // Depth-tested labels, drawn with the 3D scene.
     framebuffer_manager:        FramebufferManager,
     post_process_render_target: RenderTarget,
     timer:                      Timer,
     curr_time:                  u64,
-    camera:                     Rc<RefCell<ArcBall>>
+    camera:                     Rc<RefCell<ArcBall>>,
+    planar_camera:              Rc<RefCell<Planar>>,
+    input_2d:                   bool
 }
 
 impl Window {
     /// Indicates whether this window should be closed.
     #[inline]
     pub fn should_close(&self) -> bool {
-        self.window.should_close()
+        self.canvas.should_close()
+    }
+
+    /// Indicates whether this window renders off-screen, without any display server.
+    #[inline]
+    pub fn is_headless(&self) -> bool {
+        self.canvas.glfw_window().is_none()
     }
 
     /// Access the glfw context.
+    ///
+    /// # Failures:
+    /// Fails if the window is headless.
     #[inline]
     pub fn context<'r>(&'r self) -> &'r glfw::Glfw {
-        &self.glfw
+        match self.canvas {
+            Canvas::Screen(ref glfw, _) => glfw,
+            Canvas::Offscreen(_)        => panic!("A headless window has no glfw context.")
+        }
     }
 
     /// Access the glfw window.
+    ///
+    /// # Failures:
+    /// Fails if the window is headless.
     #[inline]
     pub fn glfw_window<'r>(&'r self) -> &'r glfw::Window {
-        &self.window
+        self.canvas.glfw_window().expect("A headless window has no glfw window.")
     }
 
     /// The window width.
     #[inline]
     pub fn width(&self) -> f32 {
-        let (w, _) = self.window.get_size();
+        let (w, _) = self.canvas.size();
 
         w as f32
     }
@@ -84,7 +116,7 @@ impl Window {
     /// The window height.
     #[inline]
     pub fn height(&self) -> f32 {
-        let (_, h) = self.window.get_size();
+        let (_, h) = self.canvas.size();
 
         h as f32
     }
@@ -92,7 +124,7 @@ impl Window {
     /// The size of the window.
     #[inline]
     pub fn size(&self) -> Vec2<f32> {
-        let (w, h) = self.window.get_size();
+        let (w, h) = self.canvas.size();
 
         Vec2::new(w as f32, h as f32)
     }
@@ -106,19 +138,25 @@ impl Window {
     /// Closes the window.
     #[inline]
     pub fn close(&mut self) {
-        self.window.set_should_close(true)
+        self.canvas.set_should_close(true)
     }
 
     /// Hides the window, without closing it. Use `show` to make it visible again.
     #[inline]
     pub fn hide(&mut self) {
-        self.window.hide()
+        match self.canvas.glfw_window() {
+            Some(window) => window.hide(),
+            None         => { }
+        }
     }
 
     /// Makes the window visible. Use `hide` to hide it.
     #[inline]
     pub fn show(&mut self) {
-        self.window.show()
+        match self.canvas.glfw_window() {
+            Some(window) => window.show(),
+            None         => { }
+        }
     }
 
     /// Sets the background color.
@@ -143,6 +181,28 @@ impl Window {
         self.point_renderer.draw_point(pt.clone(), color.clone());
     }
 
+    /// Adds a sprite to be drawn during the next frame.
+    ///
+    /// Consecutive sprites sharing the same `sprite` style are drawn with a single draw call.
+    #[inline]
+    pub fn draw_sprite(&mut self, pt: &Pnt3<f32>, color: &Pnt3<f32>, size: PointSize, sprite: &PointSprite) {
+        self.point_renderer.draw_sprite(pt.clone(), color.clone(), size, sprite);
+    }
+
+    /// Adds a thick, anti-aliased line to be drawn during the next frame.
+    #[inline]
+    pub fn draw_thick_line(&mut self, a: &Pnt3<f32>, b: &Pnt3<f32>, color: &Pnt3<f32>, style: &LineStyle) {
+        self.thick_line_renderer.draw_line(a.clone(), b.clone(), color.clone(), style);
+    }
+
+    /// Adds a thick, anti-aliased polyline to be drawn during the next frame.
+    ///
+    /// Consecutive segments are joined with the style `join`.
+    #[inline]
+    pub fn draw_thick_polyline(&mut self, points: &[Pnt3<f32>], color: &Pnt3<f32>, style: &LineStyle) {
+        self.thick_line_renderer.draw_polyline(points, color.clone(), style);
+    }
+
     // XXX: remove this (moved to the render_frame).
     /// Adds a string to be drawn during the next frame.
     #[inline]
@@ -150,6 +210,44 @@ impl Window {
         self.text_renderer.draw_text(text, pos, font, color);
     }
 
+    /// Adds a string to be drawn during the next frame, aligned, wrapped, and with a background
+    /// as given by `layout`.
+    ///
+    /// Use `TextLayout::measure` to know the size of the text before drawing it.
+    #[inline]
+    pub fn draw_text_with_layout(&mut self,
+                                 text:   &str,
+                                 pos:    &Pnt2<f32>,
+                                 font:   &Rc<Font>,
+                                 color:  &Pnt3<f32>,
+                                 layout: &TextLayout) {
+        self.text_renderer.draw_text_with_layout(text, pos, font, color, layout);
+    }
+
+    /// Adds a string to be drawn next to a point of the scene during the next frame.
+    ///
+    /// The text is positioned at the projection of `pos` by the camera used for rendering, and
+    /// is hidden if `pos` is behind it.
+    #[inline]
+    pub fn draw_text_3d(&mut self, text: &str, pos: &Pnt3<f32>, font: &Rc<Font>, color: &Pnt3<f32>) {
+        self.text_renderer.draw_label(pos, &Label::new(text, font, color));
+    }
+
+    /// Adds a label to be drawn next to a point of the scene during the next frame.
+    ///
+    /// Use this instead of `draw_text_3d` to offset the text or to hide it behind the scene
+    /// objects. Depth-tested labels are drawn with the 3D scene, so they are covered by the 2D
+    /// scene and altered by post-processing effects. Other labels are drawn on top of everything.
+    #[inline]
+    pub fn draw_label(&mut self, anchor: &Pnt3<f32>, label: &Label) {
+        if label.depth_test {
+            self.label_renderer.draw_label(anchor, label);
+        }
+        else {
+            self.text_renderer.draw_label(anchor, label);
+        }
+    }
+
     /// Removes an object from the scene.
     pub fn remove(&mut self, sn: &mut SceneNode) {
         sn.unlink()
@@ -167,15 +265,92 @@ impl Window {
     /// # Arguments
     /// * `path`  - relative path to the obj file.
     /// * `scale` - scale to apply to the model.
-    pub fn add_obj(&mut self, path: &Path, mtl_dir: &Path, scale: Vec3<f32>) -> SceneNode {
+    ///
+    /// Returns the new node and the list of warnings about ignored data.
+    pub fn add_obj(&mut self, path: &Path, mtl_dir: &Path, scale: Vec3<f32>)
+                   -> Result<(SceneNode, Vec<ParseError>), LoadError> {
         self.scene.add_obj(path, mtl_dir, scale)
     }
 
+    /// Adds glTF 2.0 models (`.gltf` or `.glb`) to the scene.
+    ///
+    /// Returns the root node of the loaded scene and the list of warnings about ignored data.
+    pub fn add_gltf(&mut self, path: &Path) -> Result<(SceneNode, Vec<ParseError>), LoadError> {
+        self.scene.add_gltf(path)
+    }
+
+    /// Adds an ASCII or binary STL model to the scene.
+    ///
+    /// Returns the new node and the list of warnings about ignored lines.
+    pub fn add_stl(&mut self, path: &Path, scale: Vec3<f32>) -> Result<(SceneNode, Vec<ParseError>), LoadError> {
+        self.scene.add_stl(path, scale)
+    }
+
+    /// Adds a PLY model to the scene. Files without faces are added as point clouds.
+    ///
+    /// Returns the new node and the list of warnings about ignored data.
+    pub fn add_ply(&mut self, path: &Path, scale: Vec3<f32>) -> Result<(SceneNode, Vec<ParseError>), LoadError> {
+        self.scene.add_ply(path, scale)
+    }
+
     /// Adds an unnamed mesh to the scene.
     pub fn add_mesh(&mut self, mesh: Rc<RefCell<Mesh>>, scale: Vec3<f32>) -> SceneNode {
         self.scene.add_mesh(mesh, scale)
     }
 
+    /// Adds a mesh drawn once per instance, with a single draw call if the OpenGL context
+    /// supports it.
+    ///
+    /// Each instance is given by its transformation, scale and color.
+    pub fn add_instanced(&mut self, mesh: Rc<RefCell<Mesh>>, instances: Vec<(Iso3<f32>, Vec3<f32>, Pnt3<f32>)>) -> SceneNode {
+        self.scene.add_instanced(mesh, instances)
+    }
+
+    /// Adds a line strip kept on the GPU between frames.
+    ///
+    /// There must be exactly one color per vertex.
+    pub fn add_polyline(&mut self, coords: Vec<Pnt3<f32>>, colors: Vec<Pnt3<f32>>, width: f32) -> SceneNode {
+        self.scene.add_polyline(coords, colors, width)
+    }
+
+    /// Adds a set of points kept on the GPU between frames.
+    ///
+    /// There must be exactly one color per point.
+    pub fn add_point_cloud(&mut self, coords: Vec<Pnt3<f32>>, colors: Vec<Pnt3<f32>>, size: f32) -> SceneNode {
+        self.scene.add_point_cloud(coords, colors, size)
+    }
+
+    /// Adds a rectangle to the 2D scene layer.
+    ///
+    /// # Arguments
+    /// * `w` - the rectangle width.
+    /// * `h` - the rectangle height.
+    pub fn add_rectangle(&mut self, w: f32, h: f32) -> SceneNode {
+        self.scene_2d.add_rectangle(w, h)
+    }
+
+    /// Adds a disk to the 2D scene layer.
+    ///
+    /// # Arguments
+    /// * `r` - the disk radius.
+    pub fn add_circle(&mut self, r: f32) -> SceneNode {
+        self.scene_2d.add_circle(r)
+    }
+
+    /// Adds a convex polygon to the 2D scene layer.
+    ///
+    /// # Arguments
+    /// * `polygon` - the polygon vertices, in clockwise or counterclockwise order.
+    /// * `scale` - the scaling factors applied to the polygon vertices.
+    pub fn add_convex_polygon(&mut self, polygon: Vec<Pnt2<f32>>, scale: Vec2<f32>) -> SceneNode {
+        self.scene_2d.add_convex_polygon(polygon, scale)
+    }
+
+    /// Adds a closed polyline to the 2D scene layer, e.g. the outline of a 2D shape.
+    pub fn add_planar_polyline(&mut self, polyline: &Polyline2<f32>, color: Pnt3<f32>, width: f32) -> SceneNode {
+        self.scene_2d.add_planar_polyline(polyline, color, width)
+    }
+
     /// Creates and adds a new object using the geometry generated by a given procedural generator.
     /// Creates and adds a new object using a mesh descriptor.
     pub fn add_trimesh(&mut self, descr: TriMesh3<f32>, scale: Vec3<f32>) -> SceneNode {
@@ -261,7 +436,7 @@ impl Window {
     }
 
     #[doc(hidden)]
-    pub fn add_texture(&mut self, path: &Path, name: &str) -> Rc<Texture> {
+    pub fn add_texture(&mut self, path: &Path, name: &str) -> IoResult<Rc<Texture>> {
         TextureManager::get_global_manager(|tm| tm.add(path, name))
     }
 
@@ -270,9 +445,62 @@ impl Window {
         false // FIXME
     }
 
-    /// Sets the light mode. Only one light is supported.
+    /// Replaces every light of the scene by a single white point light.
     pub fn set_light(&mut self, pos: Light) {
-        self.light_mode = pos;
+        self.lights.clear();
+        self.lights.push(LightSource::new_point(pos));
+    }
+
+    /// Adds a light to the scene.
+    ///
+    /// The default object material takes at most `builtin::MAX_LIGHTS` lights into account.
+    pub fn add_light(&mut self, light: LightSource) {
+        self.lights.push(light);
+    }
+
+    /// Removes every light from the scene.
+    pub fn clear_lights(&mut self) {
+        self.lights.clear();
+    }
+
+    /// The lights of the scene.
+    #[inline]
+    pub fn lights<'a>(&'a self) -> &'a [LightSource] {
+        self.lights.as_slice()
+    }
+
+    /// Mutable reference to the lights of the scene.
+    #[inline]
+    pub fn lights_mut<'a>(&'a mut self) -> &'a mut Vec<LightSource> {
+        &mut self.lights
+    }
+
+    /// Enables or disables shadows.
+    ///
+    /// Shadows are cast by the first light of the scene, `lights()[0]`, only. The other lights
+    /// never cast shadows. Shadows are drawn by the default object material and are disabled by
+    /// default.
+    ///
+    /// Point lights shadow only the part of the scene in front of them, see `ShadowMap`.
+    pub fn set_shadows(&mut self, enabled: bool) {
+        if !enabled {
+            if self.shadow_map.is_some() {
+                set_default_shadow_map(None);
+            }
+
+            self.shadow_map = None;
+        }
+        else if self.shadow_map.is_none() {
+            self.shadow_map = Some(ShadowMap::new(2048));
+        }
+    }
+
+    /// Mutable reference to the shadow map, if shadows are enabled.
+    ///
+    /// Use this to adjust the region of the scene covered by the shadows.
+    #[inline]
+    pub fn shadow_map_mut<'a>(&'a mut self) -> Option<&'a mut ShadowMap> {
+        self.shadow_map.as_mut()
     }
 
     /// Opens a window, hide it then calls a user-defined procedure.
@@ -301,6 +529,50 @@ impl Window {
         Window::do_new(title, false, width, height)
     }
 
+    /// Creates a window rendering off-screen, without any display server.
+    ///
+    /// The OpenGL context is created by the OSMesa software rasterizer, so this works without
+    /// any GPU either, e.g., to render regression images on a continuous integration server. The
+    /// scene is rendered exactly as by an on-screen window, and is read back with `snap_image` or
+    /// `save_screenshot`.
+    ///
+    /// Since there is no window, there are no events, and the `update`, `start_pass`,
+    /// `render_complete` and `handle_event` methods of the cameras are never called. The aspect
+    /// ratio of the default camera is set automatically, but user-provided cameras have to be
+    /// configured by the caller.
+    ///
+    /// This requires the `headless` feature.
+    ///
+    /// # Failures:
+    /// Fails if the OSMesa context cannot be created.
+    #[cfg(feature = "headless")]
+    pub fn new_headless(width: u32, height: u32) -> Window {
+        assert!(width != 0 && height != 0, "The headless window size must not be zero.");
+
+        let context = HeadlessContext::new(width as uint, height as uint);
+        let canvas  = Canvas::Offscreen(OffscreenCanvas {
+            context:      context,
+            width:        width,
+            height:       height,
+            should_close: false
+        });
+
+        // There is no event source.
+        let (_, events) = channel();
+
+        init_gl();
+        verify!(gl::Viewport(0, 0, width as GLint, height as GLint));
+        verify!(gl::Scissor(0, 0, width as GLint, height as GLint));
+
+        let target = FramebufferManager::new_render_target(width as uint, height as uint);
+        let res    = Window::new_with_canvas(canvas, events, target, width, height);
+
+        res.camera.borrow_mut().set_aspect(width as f32 / height as f32);
+        res.planar_camera.borrow_mut().set_size(width as f32, height as f32);
+
+        res
+    }
+
     // FIXME: make this pub?
     fn do_new(title: &str, hide: bool, width: u32, height: u32) -> Window {
         // FIXME: glfw::set_error_callback(~ErrorCallback);
@@ -314,41 +586,55 @@ impl Window {
         verify!(gl::load_with(|name| window.get_proc_address(name)));
         init_gl();
 
-        let mut usr_window = Window {
+        // setup callbacks
+        window.set_framebuffer_size_polling(true);
+        window.set_key_polling(true);
+        window.set_mouse_button_polling(true);
+        window.set_cursor_pos_polling(true);
+        window.set_scroll_polling(true);
+
+        if hide {
+            window.hide()
+        }
+
+        Window::new_with_canvas(Canvas::Screen(glfw, window), events, FramebufferManager::screen(), width, height)
+    }
+
+    fn new_with_canvas(canvas:        Canvas,
+                       events:        Receiver<(f64, WindowEvent)>,
+                       render_target: RenderTarget,
+                       width:         u32,
+                       height:        u32)
+                       -> Window {
+        let res = Window {
             max_ms_per_frame:      None,
-            glfw:                  glfw,
-            window:                window,
+            canvas:                canvas,
+            render_target:         render_target,
             events:                Rc::new(events),
             unhandled_events:      Rc::new(RefCell::new(Vec::new())),
             scene:                 SceneNode::new_empty(),
-            light_mode:            Light::Absolute(Pnt3::new(0.0, 10.0, 0.0)),
+            scene_2d:              SceneNode::new_empty(),
+            lights:                vec!(LightSource::new_point(Light::Absolute(Pnt3::new(0.0, 10.0, 0.0)))),
+            shadow_map:            None,
             background:            Vec3::new(0.0, 0.0, 0.0),
             line_renderer:         LineRenderer::new(),
             point_renderer:        PointRenderer::new(),
+            thick_line_renderer:   ThickLineRenderer::new(),
             text_renderer:         TextRenderer::new(),
+            label_renderer:        TextRenderer::new(),
             post_process_render_target: FramebufferManager::new_render_target(width as uint, height as uint),
             framebuffer_manager:   FramebufferManager::new(),
             timer:                 Timer::new().unwrap(),
             curr_time:             time::precise_time_ns(),
-            camera:                Rc::new(RefCell::new(ArcBall::new(Pnt3::new(0.0f32, 0.0, -1.0), na::orig())))
+            camera:                Rc::new(RefCell::new(ArcBall::new(Pnt3::new(0.0f32, 0.0, -1.0), na::orig()))),
+            planar_camera:         Rc::new(RefCell::new(Planar::new(na::orig(), 1.0))),
+            input_2d:              false
         };
 
-        // setup callbacks
-        usr_window.window.set_framebuffer_size_polling(true);
-        usr_window.window.set_key_polling(true);
-        usr_window.window.set_mouse_button_polling(true);
-        usr_window.window.set_cursor_pos_polling(true);
-        usr_window.window.set_scroll_polling(true);
-
-        if hide {
-            usr_window.window.hide()
-        }
+        // The default camera frames the whole scene instead of looking at the origin.
+        res.camera.borrow_mut().set_frame_on_reset(Some(res.scene.clone()));
 
-        // usr_window.framebuffer_size_callback(DEFAULT_WIDTH, DEFAULT_HEIGHT);
-        let light = usr_window.light_mode.clone();
-        usr_window.set_light(light);
-
-        usr_window
+        res
     }
 
     /// Reference to the scene associated with this window.
@@ -363,13 +649,57 @@ impl Window {
         &mut self.scene
     }
 
-    // FIXME: give more options for the snap size and offset.
+    /// Reference to the 2D scene layer associated with this window.
+    ///
+    /// The 2D scene is drawn on top of the 3D scene with the planar camera. Use `set_2d_input`
+    /// to let the planar camera handle the mouse and keyboard events.
+    #[inline]
+    pub fn scene_2d<'a>(&'a self) -> &'a SceneNode {
+        &self.scene_2d
+    }
+
+    /// Mutable reference to the 2D scene layer associated with this window.
+    #[inline]
+    pub fn scene_2d_mut<'a>(&'a mut self) -> &'a mut SceneNode {
+        &mut self.scene_2d
+    }
+
+    /// The default camera, used when no camera is given to the rendering methods.
+    #[inline]
+    pub fn camera<'a>(&'a self) -> &'a Rc<RefCell<ArcBall>> {
+        &self.camera
+    }
+
+    /// The camera used to draw the 2D scene layer.
+    #[inline]
+    pub fn planar_camera<'a>(&'a self) -> &'a Rc<RefCell<Planar>> {
+        &self.planar_camera
+    }
+
+    /// Indicates whether the mouse and keyboard events are sent to the planar camera instead of
+    /// the 3D camera.
+    #[inline]
+    pub fn is_2d_input(&self) -> bool {
+        self.input_2d
+    }
+
+    /// Sets whether the mouse and keyboard events are sent to the planar camera instead of the 3D
+    /// camera.
+    ///
+    /// This is `false` by default.
+    #[inline]
+    pub fn set_2d_input(&mut self, input_2d: bool) {
+        self.input_2d = input_2d
+    }
+
     /// Read the pixels currently displayed to the screen.
     ///
+    /// Rows are ordered from bottom to top. See `snap_image` for a more convenient alternative.
+    ///
     /// # Arguments:
     /// * `out` - the output buffer. It is automatically resized.
     pub fn snap(&self, out: &mut Vec<u8>) {
-        let (width, height) = self.window.get_size();
+        let (width, height) = self.canvas.size();
 
         let size = (width * height * 3) as uint;
 
@@ -385,13 +715,110 @@ impl Window {
         unsafe {
             gl::PixelStorei(gl::PACK_ALIGNMENT, 1);
             gl::ReadPixels(0, 0,
-                           width, height,
+                           width as i32, height as i32,
                            gl::RGB,
                            gl::UNSIGNED_BYTE,
                            (&mut out[0]) as *mut u8 as *mut libc::c_void);
         }
     }
 
+    /// Finds the closest visible object under a point of the window, using the default camera.
+    ///
+    /// # Arguments
+    /// * `screen_pos` - the window point, in pixels, from the window top-left corner. This is the
+    /// same convention as the cursor position.
+    pub fn pick(&self, screen_pos: &Pnt2<f32>) -> Option<PickResult> {
+        let camera = self.camera.borrow();
+
+        self.pick_with_camera(&*camera, screen_pos)
+    }
+
+    /// Finds the closest visible object under a point of the window, using the given camera.
+    pub fn pick_with_camera(&self, camera: &Camera, screen_pos: &Pnt2<f32>) -> Option<PickResult> {
+        let (orig, dir) = camera.unproject(screen_pos, &self.size());
+
+        self.scene.cast_ray(&orig, &dir)
+    }
+
+    /// Reads the image currently displayed by the window.
+    ///
+    /// This is the final image, including post-processing effects and text.
+    pub fn snap_image(&self) -> Snapshot {
+        let (width, height) = self.canvas.size();
+
+        self.snap_image_rect(0, 0, width, height)
+    }
+
+    /// Reads the image currently displayed by the window, with its alpha channel.
+    ///
+    /// The alpha channel is only meaningful for headless windows: on screen, it depends on the
+    /// pixel format chosen by the window system.
+    pub fn snap_image_rgba(&self) -> Snapshot {
+        let (width, height) = self.canvas.size();
+
+        self.read_target(0, 0, width, height, true)
+    }
+
+    /// Reads a rectangle of the image currently displayed by the window.
+    ///
+    /// # Arguments
+    /// * `x` - the column of the top-left corner of the rectangle, from the window left border.
+    /// * `y` - the row of the top-left corner of the rectangle, from the window top border.
+    /// * `width` - the rectangle width.
+    /// * `height` - the rectangle height.
+    pub fn snap_image_rect(&self, x: u32, y: u32, width: u32, height: u32) -> Snapshot {
+        self.read_target(x, y, width, height, false)
+    }
+
+    fn read_target(&self, x: u32, y: u32, width: u32, height: u32, alpha: bool) -> Snapshot {
+        let (win_w, win_h) = self.canvas.size();
+
+        assert!(x + width <= win_w && y + height <= win_h,
+                "The snapped rectangle must lie inside of the window.");
+
+        let bottom    = (win_h - (y + height)) as i32;
+        // A headless window reads its render target, which is still selected since the last
+        // frame. On screen, the last frame has already been swapped to the front buffer.
+        let on_screen = self.canvas.glfw_window().is_some();
+
+        if on_screen {
+            verify!(gl::ReadBuffer(gl::FRONT));
+        }
+
+        let res = if alpha {
+            Snapshot::read_rgba_from_framebuffer(x as i32, bottom, width, height)
+        }
+        else {
+            Snapshot::read_from_framebuffer(x as i32, bottom, width, height)
+        };
+
+        if on_screen {
+            verify!(gl::ReadBuffer(gl::BACK));
+        }
+
+        res
+    }
+
+    /// Reads the image of the scene before post-processing.
+    ///
+    /// This is only meaningful if the last frame was rendered with a post-processing effect.
+    pub fn snap_scene_image(&mut self) -> Snapshot {
+        let (width, height) = self.canvas.size();
+
+        self.framebuffer_manager.select(&self.post_process_render_target);
+        let res = Snapshot::read_from_framebuffer(0, 0, width, height);
+        self.framebuffer_manager.select(&self.render_target);
+
+        res
+    }
+
+    /// Saves the image currently displayed by the window to a file.
+    ///
+    /// The file format is deduced from the path extension: either `png` or `ppm`.
+    pub fn save_screenshot(&self, path: &Path) -> IoResult<()> {
+        self.snap_image().save(path)
+    }
+
     /// Gets the events manager that gives access to an event iterator.
     pub fn events(&self) -> EventManager {
         EventManager::new(self.events.clone(), self.unhandled_events.clone())
@@ -415,7 +842,7 @@ impl Window {
         }
 
         unhandled_events.borrow_mut().clear();
-        self.glfw.poll_events();
+        self.canvas.poll_events();
     }
 
     fn handle_event(&mut self, camera: &mut Option<&mut Camera>, event: &WindowEvent) {
@@ -429,9 +856,19 @@ impl Window {
             _ => { }
         }
 
-        match *camera {
-            Some(ref mut cam) => cam.handle_event(&self.window, event),
-            None => self.camera.borrow_mut().handle_event(&self.window, event)
+        let window = match self.canvas.glfw_window() {
+            Some(window) => window,
+            None         => return
+        };
+
+        if !self.input_2d {
+            match *camera {
+                Some(ref mut cam) => cam.handle_event(window, event),
+                None              => self.camera.borrow_mut().handle_event(window, event)
+            }
+        }
+        else {
+            self.planar_camera.borrow_mut().handle_event(window, event)
         }
     }
 
@@ -483,13 +920,55 @@ impl Window {
         // XXX: too bad we have to do this at each frame…
         let w = self.width();
         let h = self.height();
-        camera.handle_event(&self.window, &WindowEvent::FramebufferSize(w as i32, h as i32));
 
-        camera.update(&self.window);
+        match self.canvas.glfw_window() {
+            Some(window) => {
+                camera.handle_event(window, &WindowEvent::FramebufferSize(w as i32, h as i32));
+                self.planar_camera.borrow_mut().handle_event(window, &WindowEvent::FramebufferSize(w as i32, h as i32));
 
-        match self.light_mode {
-            Light::StickToCamera => self.set_light(Light::StickToCamera),
-            _ => { }
+                camera.update(window);
+            },
+            None => { }
+        }
+
+        self.draw_frame(camera, post_processing);
+
+        // We are done: swap buffers
+        self.canvas.swap_buffers();
+
+        // Limit the fps if needed.
+        match self.max_ms_per_frame {
+            None     => { },
+            Some(ms) => {
+                let elapsed = (time::precise_time_ns() - self.curr_time) / 1000000;
+                if elapsed < ms {
+                    self.timer.sleep(Duration::milliseconds((ms - elapsed) as i64));
+                }
+            }
+        }
+
+        self.curr_time = time::precise_time_ns();
+
+        !self.should_close()
+    }
+
+    // Draws a whole frame, including post-processing and text, to the render target of this
+    // window: the screen, or the off-screen framebuffer of a headless window.
+    fn draw_frame(&mut self, camera: &mut Camera, post_processing: Option<&mut PostProcessingEffect>) {
+        let w = self.width();
+        let h = self.height();
+
+        match self.shadow_map {
+            Some(ref mut shadow_map) => {
+                if self.lights.is_empty() {
+                    set_default_shadow_map(None)
+                }
+                else {
+                    shadow_map.render(&mut self.scene, &self.lights[0], &*camera, &mut self.framebuffer_manager);
+                    set_default_shadow_map(Some(&*shadow_map))
+                }
+            },
+            None => { }
         }
 
         let mut post_processing = post_processing;
@@ -498,14 +977,21 @@ impl Window {
             self.framebuffer_manager.select(&self.post_process_render_target);
         }
         else {
-            self.framebuffer_manager.select(&FramebufferManager::screen());
+            self.framebuffer_manager.select(&self.render_target);
         }
 
         for pass in range(0u, camera.num_passes()) {
-            camera.start_pass(pass, &self.window);
+            match self.canvas.glfw_window() {
+                Some(window) => camera.start_pass(pass, window),
+                None         => { }
+            }
             self.render_scene(camera, pass);
         }
-        camera.render_complete(&self.window);
+
+        match self.canvas.glfw_window() {
+            Some(window) => camera.render_complete(window),
+            None         => { }
+        }
 
         let (znear, zfar) = camera.clip_planes();
 
@@ -517,8 +1003,8 @@ impl Window {
 
         match post_processing {
             Some(ref mut p) => {
-                // switch back to the screen framebuffer …
-                self.framebuffer_manager.select(&FramebufferManager::screen());
+                // switch back to the window framebuffer …
+                self.framebuffer_manager.select(&self.render_target);
                 // … and execute the post-process
                 // FIXME: use the real time value instead of 0.016!
                 p.update(0.016, w, h, znear, zfar);
@@ -527,28 +1013,17 @@ impl Window {
             None => { }
         }
 
-        self.text_renderer.render(w, h);
-
-        // We are done: swap buffers
-        self.window.swap_buffers();
-
-        // Limit the fps if needed.
-        match self.max_ms_per_frame {
-            None     => { },
-            Some(ms) => {
-                let elapsed = (time::precise_time_ns() - self.curr_time) / 1000000;
-                if elapsed < ms {
-                    self.timer.sleep(Duration::milliseconds((ms - elapsed) as i64));
+        {
+            let text_renderer = &mut self.text_renderer;
+            self.scene.data().apply_to_visible_labels(&mut |anchor, label| {
+                if !label.depth_test {
+                    text_renderer.draw_label(anchor, label)
                 }
-            }
+            });
         }
 
-        self.curr_time = time::precise_time_ns();
-
-        // self.transparent_objects.clear();
-        // self.opaque_objects.clear();
-
-        !self.should_close()
+        self.text_renderer.project_labels(&*camera, w, h);
+        self.text_renderer.render(w, h);
     }
 
     fn render_scene(&mut self, camera: &mut Camera, pass: uint) {
@@ -567,7 +1042,34 @@ impl Window {
             self.point_renderer.render(pass, camera);
         }
 
-        self.scene.data_mut().render(pass, camera, &self.light_mode);
+        self.scene.data_mut().render(pass, camera, self.lights.as_slice());
+
+        if self.thick_line_renderer.needs_rendering() {
+            self.thick_line_renderer.render(pass, camera);
+        }
+
+        // Depth-tested labels must be drawn before the 2D scene clears the depth buffer.
+        {
+            let label_renderer = &mut self.label_renderer;
+            self.scene.data().apply_to_visible_labels(&mut |anchor, label| {
+                if label.depth_test {
+                    label_renderer.draw_label(anchor, label)
+                }
+            });
+        }
+
+        let w = self.width();
+        let h = self.height();
+
+        self.label_renderer.project_labels(&*camera, w, h);
+        self.label_renderer.render(w, h);
+
+        if self.scene_2d.data().has_children() {
+            let planar_camera = self.planar_camera.clone();
+
+            verify!(gl::Clear(gl::DEPTH_BUFFER_BIT));
+            self.scene_2d.data_mut().render(0, &mut *planar_camera.borrow_mut(), &[]);
+        }
     }
 
 
@@ -579,7 +1081,8 @@ impl Window {
     }
 }
 
-fn init_gl() {
+#[doc(hidden)]
+pub fn init_gl() {
     /*
      * Misc configurations
      */
@@ -591,3 +1094,10 @@ fn init_gl() {
     verify!(gl::Enable(gl::CULL_FACE));
     verify!(gl::CullFace(gl::BACK));
 }
+
+// Sets the shadow map used by the default object material, the only one drawing shadows.
+fn set_default_shadow_map(shadow_map: Option<&ShadowMap>) {
+    let material = MaterialManager::get_global_manager(|mm| mm.get_default());
+
+    material.borrow_mut().set_shadow_map(shadow_map)
+}